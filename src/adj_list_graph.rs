@@ -1,22 +1,52 @@
 //! An adjacency list representation of a graph.
 
-use std::{collections::HashMap, marker::PhantomData};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet, TryReserveError},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
 
-use crate::graph_base::{Edge, EdgeInd, GraphBase, GraphType, NodeInd};
+use crate::graph_base::{
+    Directed, Edge, EdgeInd, GraphBase, GraphType, GraphView, NodeInd, Undirected, WouldCreateCycle,
+};
+use crate::traversal::{bfs_distances_within, can_reach};
+
+/// The map that node and edge data are stored in, by index. By default, this
+/// is a `HashMap`, so [`nodes`](GraphView::nodes) and
+/// [`edges`](GraphView::edges) go in an arbitrary order that can change from
+/// run to run. With the `ordered` feature, it's a `BTreeMap` instead, so they
+/// go in ascending order of index every time, which keeps snapshot tests and
+/// the like reproducible. The price is that looking up, adding and removing
+/// nodes and edges take O(log n) time instead of O(1), and the `reserve_*`
+/// methods and [`shrink_to_fit`](ALGraph::shrink_to_fit) can't do anything for
+/// the maps.
+#[cfg(not(feature = "ordered"))]
+type Storage<K, V> = HashMap<K, V>;
+#[cfg(feature = "ordered")]
+type Storage<K, V> = std::collections::BTreeMap<K, V>;
 
 /// Adjacency list representation of a graph. N and E are edge types.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Equality and hashing are structural: two graphs are equal if they have the
+/// same nodes, with the same indices and data, connected by the same edges,
+/// with the same data. Edge indices and the order edges were added in don't
+/// matter, and neither does which end of an undirected edge is the start.
+#[derive(Clone, Debug)]
 pub struct ALGraph<N, E, Ty: GraphType> {
     /// The node data.
-    nodes: HashMap<NodeInd, N>,
+    nodes: Storage<NodeInd, N>,
 
     /// The edge data.
-    edges: HashMap<EdgeInd, Edge<E>>,
+    edges: Storage<EdgeInd, Edge<E>>,
 
     /// The adjacency lists: a list of edges starting from each node. Edges keep
     /// track of start and end nodes as well as the index.
     adj: Vec<Vec<EdgeInd>>,
 
+    /// The list of edges ending at each node. Only used for directed graphs:
+    /// undirected graphs keep every incident edge in `adj`.
+    in_adj: Vec<Vec<EdgeInd>>,
+
     /// The current node index.
     curr_node: NodeInd,
 
@@ -26,7 +56,488 @@ pub struct ALGraph<N, E, Ty: GraphType> {
     ty: PhantomData<Ty>,
 }
 
-impl<N, E: Clone, Ty: GraphType> GraphBase<N, E, Ty> for ALGraph<N, E, Ty> {
+impl<N, E, Ty: GraphType> ALGraph<N, E, Ty> {
+    /// Makes a new, empty graph.
+    pub fn new() -> Self {
+        Self {
+            nodes: Storage::new(),
+            edges: Storage::new(),
+            adj: vec![],
+            in_adj: vec![],
+            curr_node: 0,
+            curr_edge: 0,
+            ty: PhantomData,
+        }
+    }
+
+    /// Consumes the graph, returning its nodes as (index, data) pairs and its
+    /// edges, both in ascending order of index.
+    pub fn into_nodes_edges(self) -> (Vec<(NodeInd, N)>, Vec<Edge<E>>) {
+        let mut nodes: Vec<(NodeInd, N)> = self.nodes.into_iter().collect();
+        nodes.sort_unstable_by_key(|(n, _)| *n);
+        let mut edges: Vec<Edge<E>> = self.edges.into_values().collect();
+        edges.sort_unstable_by_key(|e| e.index);
+        (nodes, edges)
+    }
+
+    /// Reserves space for at least `additional` more nodes.
+    pub fn reserve_nodes(&mut self, additional: usize) {
+        #[cfg(not(feature = "ordered"))]
+        self.nodes.reserve(additional);
+        self.adj.reserve(additional);
+        self.in_adj.reserve(additional);
+    }
+
+    /// Reserves space for at least `additional` more edges.
+    pub fn reserve_edges(&mut self, additional: usize) {
+        #[cfg(not(feature = "ordered"))]
+        self.edges.reserve(additional);
+        #[cfg(feature = "ordered")]
+        let _ = additional;
+    }
+
+    /// Shrinks the storage for nodes and edges as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        #[cfg(not(feature = "ordered"))]
+        {
+            self.nodes.shrink_to_fit();
+            self.edges.shrink_to_fit();
+        }
+        self.adj.shrink_to_fit();
+        self.in_adj.shrink_to_fit();
+        for list in self.adj.iter_mut().chain(self.in_adj.iter_mut()) {
+            list.shrink_to_fit();
+        }
+    }
+
+    /// Flips an edge in place, swapping its start and end, and keeping its index
+    /// and data. For undirected graphs, this only changes which end is called
+    /// the start.
+    pub fn reverse_edge(&mut self, e: &EdgeInd) {
+        let edge = self.edges.get_mut(e).unwrap();
+        let (start, end) = (edge.start, edge.end);
+        edge.start = end;
+        edge.end = start;
+        if Ty::is_directed() && start != end {
+            self.adj[start].retain(|i| i != e);
+            self.in_adj[end].retain(|i| i != e);
+            self.adj[end].push(*e);
+            self.in_adj[start].push(*e);
+        }
+    }
+
+    /// Puts a node at a specific index, replacing the data if there's already a
+    /// node there. Later nodes get indices after all of the ones used so far.
+    pub(crate) fn insert_node_at(&mut self, n: NodeInd, data: N) {
+        self.nodes.insert(n, data);
+        if self.adj.len() <= n {
+            self.adj.resize(n + 1, vec![]);
+            self.in_adj.resize(n + 1, vec![]);
+        }
+        self.curr_node = self.curr_node.max(n + 1);
+    }
+
+    /// Adds an edge keeping the index it has, which must be unused.
+    pub(crate) fn insert_edge_at(&mut self, edge: Edge<E>) {
+        assert!(
+            !self.edges.contains_key(&edge.index),
+            "edge {} already exists",
+            edge.index
+        );
+        let index = edge.index;
+        self.adj[edge.start].push(index);
+        if Ty::is_directed() {
+            self.in_adj[edge.end].push(index);
+        } else {
+            self.adj[edge.end].push(index);
+        }
+        self.curr_edge = self.curr_edge.max(index + 1);
+        self.edges.insert(index, edge);
+    }
+
+    /// Whether the graph has a node with this index.
+    pub(crate) fn contains_node(&self, n: &NodeInd) -> bool {
+        self.nodes.contains_key(n)
+    }
+
+    /// Whether the graph has an edge with this index.
+    pub(crate) fn contains_edge(&self, e: &EdgeInd) -> bool {
+        self.edges.contains_key(e)
+    }
+
+    /// Gets the indices that the next node and edge added will get.
+    pub(crate) fn next_indices(&self) -> (NodeInd, EdgeInd) {
+        (self.curr_node, self.curr_edge)
+    }
+
+    /// Makes room for nodes with indices up to `end`, without panicking if
+    /// there isn't enough memory for them.
+    pub(crate) fn try_reserve_node_indices(&mut self, end: NodeInd) -> Result<(), TryReserveError> {
+        let additional = end.saturating_sub(self.adj.len());
+        self.adj.try_reserve_exact(additional)?;
+        self.in_adj.try_reserve_exact(additional)
+    }
+
+    /// Makes the next node and edge added get at least the given indices.
+    pub(crate) fn skip_indices(&mut self, node: NodeInd, edge: EdgeInd) {
+        self.curr_node = self.curr_node.max(node);
+        self.adj.resize(self.curr_node, vec![]);
+        self.in_adj.resize(self.curr_node, vec![]);
+        self.curr_edge = self.curr_edge.max(edge);
+    }
+}
+
+impl<N: Clone, E, Ty: GraphType> ALGraph<N, E, Ty> {
+    /// Makes a graph with copies of the same nodes, at the same indices, but no
+    /// edges.
+    fn with_same_nodes<E2, Ty2: GraphType>(&self) -> ALGraph<N, E2, Ty2> {
+        ALGraph {
+            nodes: self.nodes.clone(),
+            edges: Storage::new(),
+            adj: vec![vec![]; self.curr_node],
+            in_adj: vec![vec![]; self.curr_node],
+            curr_node: self.curr_node,
+            curr_edge: 0,
+            ty: PhantomData,
+        }
+    }
+
+    /// Gets the edges, sorted by index.
+    fn sorted_edges(&self) -> Vec<&Edge<E>> {
+        let mut edges: Vec<&Edge<E>> = self.edges.values().collect();
+        edges.sort_by_key(|e| e.index);
+        edges
+    }
+}
+
+impl<N: Clone, E: Clone, Ty: GraphType> ALGraph<N, E, Ty> {
+    /// Gets the subgraph induced by the given nodes: those nodes, and every
+    /// edge between two of them. The nodes get new indices, in the same order
+    /// as their old ones, and the returned map takes old indices to new ones.
+    pub fn subgraph(&self, nodes: &[NodeInd]) -> (ALGraph<N, E, Ty>, HashMap<NodeInd, NodeInd>) {
+        let mut keep: Vec<NodeInd> = nodes.to_vec();
+        keep.sort_unstable();
+        keep.dedup();
+
+        let mut sub = ALGraph::new();
+        let new_index: HashMap<NodeInd, NodeInd> = keep
+            .iter()
+            .map(|n| (*n, sub.add_node(self.node(n).clone())))
+            .collect();
+        for edge in self.sorted_edges() {
+            if let (Some(start), Some(end)) = (new_index.get(&edge.start), new_index.get(&edge.end))
+            {
+                sub.add_edge(start, end, edge.data.clone());
+            }
+        }
+        (sub, new_index)
+    }
+
+    /// Makes a copy of the graph with the nodes given new indices in order of
+    /// `key` of their data, so the indices mean something and don't depend on
+    /// the order the nodes were added in. Nodes with equal keys stay in the
+    /// same order as their old indices, and edges get new indices in the same
+    /// order as their old ones. The returned map takes old indices to new ones.
+    pub fn relabel_by<K: Ord, F: Fn(&N) -> K>(
+        &self,
+        key: F,
+    ) -> (ALGraph<N, E, Ty>, HashMap<NodeInd, NodeInd>) {
+        let mut nodes = self.nodes_sorted();
+        nodes.sort_by_key(|n| key(self.node(n)));
+
+        let mut relabeled = ALGraph::new();
+        let new_index: HashMap<NodeInd, NodeInd> = nodes
+            .iter()
+            .map(|n| (*n, relabeled.add_node(self.node(n).clone())))
+            .collect();
+        for edge in self.sorted_edges() {
+            relabeled.add_edge(
+                &new_index[&edge.start],
+                &new_index[&edge.end],
+                edge.data.clone(),
+            );
+        }
+        (relabeled, new_index)
+    }
+
+    /// Makes a graph with copies of both this graph and `other`, side by side
+    /// with no edges between them and no nodes shared. This graph's nodes and
+    /// edges keep their indices, and `other`'s are added after them, in the
+    /// same order as their old indices. The returned map takes `other`'s old
+    /// node indices to their new ones.
+    pub fn disjoint_union(&self, other: &Self) -> (ALGraph<N, E, Ty>, HashMap<NodeInd, NodeInd>) {
+        let mut union = ALGraph {
+            nodes: self.nodes.clone(),
+            edges: self.edges.clone(),
+            adj: self.adj.clone(),
+            in_adj: self.in_adj.clone(),
+            curr_node: self.curr_node,
+            curr_edge: self.curr_edge,
+            ty: PhantomData,
+        };
+        let new_index: HashMap<NodeInd, NodeInd> = other
+            .nodes_sorted()
+            .into_iter()
+            .map(|n| (n, union.add_node(other.node(&n).clone())))
+            .collect();
+        for edge in other.sorted_edges() {
+            union.add_edge(
+                &new_index[&edge.start],
+                &new_index[&edge.end],
+                edge.data.clone(),
+            );
+        }
+        (union, new_index)
+    }
+
+    /// Gets each connected component as a graph of its own, made by
+    /// [`subgraph`](Self::subgraph), so its nodes have new indices in the same
+    /// order as their old ones. Edge direction is ignored, so for directed
+    /// graphs these are the weakly connected components. Components are in
+    /// order of their lowest node index, and each is only copied when the
+    /// iterator gets to it.
+    pub fn components(&self) -> impl Iterator<Item = ALGraph<N, E, Ty>> + '_ {
+        let mut seen = HashSet::new();
+        let mut components = vec![];
+        for root in self.nodes_sorted() {
+            if !seen.insert(root) {
+                continue;
+            }
+            let mut component = vec![root];
+            let mut i = 0;
+            while i < component.len() {
+                let curr = component[i];
+                for e in self.edges_at(&curr) {
+                    let next = e.opposite(&curr);
+                    if seen.insert(next) {
+                        component.push(next);
+                    }
+                }
+                i += 1;
+            }
+            components.push(component);
+        }
+        components
+            .into_iter()
+            .map(|component| self.subgraph(&component).0)
+    }
+}
+
+impl<N, E: Clone, Ty: GraphType> ALGraph<N, E, Ty> {
+    /// Adds many (start, end, data) edges at once, reserving space for them up
+    /// front, and returns their indices, which are contiguous and in the same
+    /// order as the edges.
+    pub fn add_edges<I: IntoIterator<Item = (NodeInd, NodeInd, E)>>(
+        &mut self,
+        edges: I,
+    ) -> Vec<EdgeInd> {
+        let edges = edges.into_iter();
+        let (additional, _) = edges.size_hint();
+        self.reserve_edges(additional);
+        let mut indices = Vec::with_capacity(additional);
+        for (start, end, data) in edges {
+            indices.push(self.add_edge(&start, &end, data));
+        }
+        indices
+    }
+}
+
+impl<N: Default, E: Clone, Ty: GraphType> ALGraph<N, E, Ty> {
+    /// Adds an edge, first adding either endpoint with default data if it
+    /// doesn't exist. Indices past the last node are filled in up to the new
+    /// endpoint, so loading an edge list with explicit node IDs gives the same
+    /// indices as the IDs, and removed nodes are put back at their old index.
+    pub fn add_edge_autonodes(&mut self, start: NodeInd, end: NodeInd, data: E) -> EdgeInd {
+        for n in [start, end] {
+            while self.curr_node <= n {
+                self.add_node(N::default());
+            }
+            if !self.nodes.contains_key(&n) {
+                self.insert_node_at(n, N::default());
+            }
+        }
+        self.add_edge(&start, &end, data)
+    }
+}
+
+impl<N: Clone, E: Clone, Ty: GraphType> ALGraph<N, E, Ty> {
+    /// Gets the k-th power of the graph: the same nodes, with an edge between
+    /// two different nodes exactly when there's a path of at most `k` edges
+    /// from one to the other in this graph, respecting edge direction. The
+    /// result is always simple, so the first power is this graph without
+    /// self-loops or parallel edges.
+    pub fn graph_power(&self, k: usize) -> ALGraph<N, (), Ty> {
+        let mut power = self.with_same_nodes();
+        let mut nodes: Vec<NodeInd> = self.nodes.keys().copied().collect();
+        nodes.sort_unstable();
+        for u in nodes {
+            let mut near: Vec<NodeInd> = bfs_distances_within(self, &u, k)
+                .into_keys()
+                .filter(|&v| if Ty::is_directed() { v != u } else { v > u })
+                .collect();
+            near.sort_unstable();
+            for v in near {
+                power.add_edge(&u, &v, ());
+            }
+        }
+        power
+    }
+}
+
+impl<N: Clone, E: Clone> ALGraph<N, E, Directed> {
+    /// Converts to an undirected graph with the same nodes, where every
+    /// directed edge becomes an undirected one. If `merge_antiparallel` is
+    /// true, each edge a -> b that has a matching edge b -> a is merged with
+    /// it into a single undirected edge, which keeps the data of whichever
+    /// edge was added first. Edges are given new indices.
+    pub fn to_undirected(&self, merge_antiparallel: bool) -> ALGraph<N, E, Undirected> {
+        let mut undirected = self.with_same_nodes();
+        // edges that haven't been merged with their reverse yet
+        let mut unmatched: HashMap<(NodeInd, NodeInd), usize> = HashMap::new();
+        for edge in self.sorted_edges() {
+            if merge_antiparallel && edge.start != edge.end {
+                if let Some(count) = unmatched.get_mut(&(edge.end, edge.start)) {
+                    if *count > 0 {
+                        *count -= 1;
+                        continue;
+                    }
+                }
+                *unmatched.entry((edge.start, edge.end)).or_default() += 1;
+            }
+            undirected.add_edge(&edge.start, &edge.end, edge.data.clone());
+        }
+        undirected
+    }
+
+    /// Gets the simple undirected graph underneath this one: the same nodes,
+    /// with a single edge between two different nodes whenever there's an edge
+    /// between them in either direction. Self-loops are dropped, and so is the
+    /// edge data.
+    pub fn underlying_simple(&self) -> ALGraph<N, (), Undirected> {
+        let mut simple = self.with_same_nodes();
+        let mut seen: HashSet<(NodeInd, NodeInd)> = HashSet::new();
+        for edge in self.sorted_edges() {
+            let key = (edge.start.min(edge.end), edge.start.max(edge.end));
+            if edge.start != edge.end && seen.insert(key) {
+                simple.add_edge(&key.0, &key.1, ());
+            }
+        }
+        simple
+    }
+}
+
+impl<N, E: Clone> ALGraph<N, E, Directed> {
+    /// Adds an edge like [`add_edge`](GraphBase::add_edge), unless it would
+    /// make a cycle, which is when `start` can already be reached from `end`.
+    /// That includes self-loops. Adding every edge this way keeps a DAG
+    /// acyclic, at the cost of a search from `end` each time.
+    pub fn try_add_edge_acyclic(
+        &mut self,
+        start: &NodeInd,
+        end: &NodeInd,
+        data: E,
+    ) -> Result<EdgeInd, WouldCreateCycle> {
+        if can_reach(self, end, start) {
+            return Err(WouldCreateCycle);
+        }
+        Ok(self.add_edge(start, end, data))
+    }
+}
+
+impl<N: Clone, E: Clone> ALGraph<N, E, Undirected> {
+    /// Converts to a directed graph with the same nodes, where every
+    /// undirected edge becomes two directed edges going in opposite directions.
+    /// Edges are given new indices.
+    pub fn to_directed(&self) -> ALGraph<N, E, Directed> {
+        let mut directed = self.with_same_nodes();
+        for edge in self.sorted_edges() {
+            directed.add_edge(&edge.start, &edge.end, edge.data.clone());
+            directed.add_edge(&edge.end, &edge.start, edge.data.clone());
+        }
+        directed
+    }
+}
+
+impl<N, E, Ty: GraphType> ALGraph<N, E, Ty> {
+    /// Gets the endpoints of an edge in a form that doesn't depend on the
+    /// order of the endpoints for undirected graphs.
+    fn edge_key(edge: &Edge<E>) -> (NodeInd, NodeInd) {
+        if Ty::is_directed() {
+            (edge.start, edge.end)
+        } else {
+            (edge.start.min(edge.end), edge.start.max(edge.end))
+        }
+    }
+}
+
+impl<N: PartialEq, E: PartialEq, Ty: GraphType> ALGraph<N, E, Ty> {
+    /// Whether the two graphs are structurally equal, as described for the
+    /// [`PartialEq`] implementation.
+    pub fn structurally_eq(&self, other: &Self) -> bool {
+        if self.nodes != other.nodes || self.edges.len() != other.edges.len() {
+            return false;
+        }
+
+        let mut unmatched: HashMap<(NodeInd, NodeInd), Vec<&E>> = HashMap::new();
+        for edge in self.edges.values() {
+            unmatched
+                .entry(Self::edge_key(edge))
+                .or_default()
+                .push(&edge.data);
+        }
+        other.edges.values().all(|edge| {
+            let Some(group) = unmatched.get_mut(&Self::edge_key(edge)) else {
+                return false;
+            };
+            match group.iter().position(|data| **data == edge.data) {
+                Some(i) => {
+                    group.swap_remove(i);
+                    true
+                }
+                None => false,
+            }
+        })
+    }
+}
+
+impl<N: PartialEq, E: PartialEq, Ty: GraphType> PartialEq for ALGraph<N, E, Ty> {
+    fn eq(&self, other: &Self) -> bool {
+        self.structurally_eq(other)
+    }
+}
+
+impl<N: Eq, E: Eq, Ty: GraphType> Eq for ALGraph<N, E, Ty> {}
+
+impl<N: Hash, E: Hash, Ty: GraphType> Hash for ALGraph<N, E, Ty> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut nodes: Vec<(&NodeInd, &N)> = self.nodes.iter().collect();
+        nodes.sort_unstable_by_key(|(n, _)| **n);
+        nodes.hash(state);
+
+        // edges don't have a canonical order, so hash each one separately and
+        // combine the hashes in an order that doesn't depend on the edges
+        let mut edge_hashes: Vec<u64> = self
+            .edges
+            .values()
+            .map(|edge| {
+                let mut hasher = DefaultHasher::new();
+                Self::edge_key(edge).hash(&mut hasher);
+                edge.data.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect();
+        edge_hashes.sort_unstable();
+        edge_hashes.hash(state);
+    }
+}
+
+impl<N, E, Ty: GraphType> Default for ALGraph<N, E, Ty> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, E: Clone, Ty: GraphType> GraphView<N, E, Ty> for ALGraph<N, E, Ty> {
     fn node(&self, n: &NodeInd) -> &N {
         self.nodes.get(n).unwrap()
     }
@@ -35,6 +546,53 @@ impl<N, E: Clone, Ty: GraphType> GraphBase<N, E, Ty> for ALGraph<N, E, Ty> {
         self.edges.get(e).unwrap()
     }
 
+    fn nodes(&self) -> Box<dyn Iterator<Item = NodeInd>> {
+        Box::new(
+            self.nodes
+                .keys()
+                .copied()
+                .collect::<Vec<NodeInd>>()
+                .into_iter(),
+        )
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeInd>> {
+        Box::new(
+            self.edges
+                .keys()
+                .copied()
+                .collect::<Vec<EdgeInd>>()
+                .into_iter(),
+        )
+    }
+
+    fn edges_from(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        Box::new(self.adj[*n].iter().map(|e| self.edge(e).clone()))
+    }
+
+    fn edges_to(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        if self.is_directed() {
+            Box::new(self.in_adj[*n].iter().map(|e| self.edge(e).clone()))
+        } else {
+            self.edges_from(n)
+        }
+    }
+
+    fn edges_at(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        if self.is_directed() {
+            Box::new(self.edges_from(n).chain(self.edges_to(n)))
+        } else {
+            self.edges_from(n)
+        }
+    }
+
+    fn edge_endpoints(&self, e: &EdgeInd) -> (NodeInd, NodeInd) {
+        let edge = self.edge(e);
+        (edge.start, edge.end)
+    }
+}
+
+impl<N, E: Clone, Ty: GraphType> GraphBase<N, E, Ty> for ALGraph<N, E, Ty> {
     fn node_mut(&mut self, n: &NodeInd) -> &mut N {
         self.nodes.get_mut(n).unwrap()
     }
@@ -44,9 +602,12 @@ impl<N, E: Clone, Ty: GraphType> GraphBase<N, E, Ty> for ALGraph<N, E, Ty> {
     }
 
     fn add_node(&mut self, data: N) -> NodeInd {
-        self.nodes.insert(self.curr_node, data);
+        let n = self.curr_node;
+        self.nodes.insert(n, data);
+        self.adj.push(vec![]);
+        self.in_adj.push(vec![]);
         self.curr_node += 1;
-        self.curr_node
+        n
     }
 
     fn add_edge(&mut self, start: &NodeInd, end: &NodeInd, data: E) -> EdgeInd {
@@ -56,68 +617,684 @@ impl<N, E: Clone, Ty: GraphType> GraphBase<N, E, Ty> for ALGraph<N, E, Ty> {
             index: self.curr_edge,
             data,
         };
+        let index = edge.index;
 
         // first, add edge information
-        self.edges.insert(self.curr_edge, edge.clone());
+        self.edges.insert(index, edge);
 
-        self.adj[*start].push(edge.index);
-        if !self.is_directed() {
+        self.adj[*start].push(index);
+        if self.is_directed() {
+            self.in_adj[*end].push(index);
+        } else {
             // if undirected, add edge to tail as well
-            self.adj[*end].push(edge.index);
+            self.adj[*end].push(index);
         }
 
         self.curr_edge += 1;
-        return edge.index;
+        index
     }
 
     fn remove_edge(&mut self, e: &EdgeInd) -> Edge<E> {
         let edge = self.edges.remove(e).unwrap();
 
         // remove from start and end lists
-        let mut start_edges = &mut self.adj[edge.start];
+        let start_edges = &mut self.adj[edge.start];
         start_edges.remove(start_edges.iter().position(|&i| i == edge.index).unwrap());
 
-        if !self.is_directed() {
-            let mut end_edges = &mut self.adj[edge.end];
-            end_edges.remove(end_edges.iter().position(|&i| i == edge.index).unwrap());
+        let end_edges = if self.is_directed() {
+            &mut self.in_adj[edge.end]
+        } else {
+            &mut self.adj[edge.end]
+        };
+        end_edges.remove(end_edges.iter().position(|&i| i == edge.index).unwrap());
+
+        edge
+    }
+
+    fn remove_node(&mut self, n: &NodeInd) -> N {
+        let data = self.nodes.remove(n).unwrap();
+
+        let mut incident: Vec<EdgeInd> = self.adj[*n]
+            .iter()
+            .chain(&self.in_adj[*n])
+            .copied()
+            .collect();
+        // undirected self-loops show up twice
+        incident.sort_unstable();
+        incident.dedup();
+        for e in incident {
+            self.remove_edge(&e);
         }
 
-        return edge;
+        data
     }
+}
 
-    fn nodes(&self) -> Box<dyn Iterator<Item = NodeInd>> {
-        Box::new(
-            self.nodes
-                .keys()
-                .map(|&n| n)
-                .collect::<Vec<NodeInd>>()
-                .into_iter(),
-        )
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::graph_from_edges;
+    use crate::traversal::{bfs_distances, reachable_from};
+
+    #[test]
+    fn test_directed_edges() {
+        let mut g: ALGraph<(), (), Directed> = ALGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let c = g.add_node(());
+        let ab = g.add_edge(&a, &b, ());
+        let cb = g.add_edge(&c, &b, ());
+
+        assert_eq!((a, b, c), (0, 1, 2));
+        assert_eq!(g.edge_endpoints(&ab), (a, b));
+        assert_eq!(g.neighbors(&a).collect::<Vec<_>>(), vec![b]);
+        assert_eq!(g.neighbors(&b).count(), 0);
+        assert_eq!(
+            g.edges_to(&b).map(|e| e.index).collect::<Vec<_>>(),
+            vec![ab, cb]
+        );
+
+        g.remove_edge(&ab);
+        assert_eq!(
+            g.edges_at(&b).map(|e| e.index).collect::<Vec<_>>(),
+            vec![cb]
+        );
     }
 
-    fn edges(&self) -> Box<dyn Iterator<Item = EdgeInd>> {
-        Box::new(
-            self.edges
-                .keys()
-                .map(|&n| n)
-                .collect::<Vec<EdgeInd>>()
-                .into_iter(),
-        )
+    #[test]
+    fn test_reverse_edge() {
+        let mut g: ALGraph<(), u8, Directed> = ALGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let ab = g.add_edge(&a, &b, 7);
+        g.add_edge(&a, &b, 8);
+        g.reverse_edge(&ab);
+
+        assert_eq!(g.edge_endpoints(&ab), (b, a));
+        assert_eq!(g.edge(&ab).data, 7);
+        let from_b: Vec<EdgeInd> = g.edges_from(&b).map(|e| e.index).collect();
+        assert_eq!(from_b, vec![ab]);
+        let to_a: Vec<EdgeInd> = g.edges_to(&a).map(|e| e.index).collect();
+        assert_eq!(to_a, vec![ab]);
+        assert_eq!(g.edges_from(&a).count(), 1);
+        assert_eq!(g.edges_to(&b).count(), 1);
+        assert!(!g.edges_from(&a).any(|e| e.index == ab));
+        assert!(!g.edges_to(&b).any(|e| e.index == ab));
     }
 
-    fn edges_from(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>>> {
-        todo!()
+    #[test]
+    fn test_subdivide_edge() {
+        let mut g: ALGraph<(), (), Undirected> = ALGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let c = g.add_node(());
+        let ab = g.add_edge(&a, &b, ());
+        g.add_edge(&b, &c, ());
+        assert_eq!(bfs_distances(&g, &a)[&c], 2);
+
+        let mid = g.subdivide_edge(&ab, (), ());
+        assert!(!g.edges().any(|e| e == ab));
+        assert_eq!(bfs_distances(&g, &a)[&c], 3);
+        assert_eq!(bfs_distances(&g, &a)[&mid], 1);
     }
 
-    fn edges_to(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>>> {
-        todo!()
+    #[test]
+    fn test_smooth() {
+        let mut path: ALGraph<(), f64, Undirected> = ALGraph::new();
+        for _ in 0..5 {
+            path.add_node(());
+        }
+        path.add_edges((1..5).map(|i| (i - 1, i, i as f64)));
+        assert_eq!(path.smooth(|a, b| a + b), 3);
+        assert_eq!(path.nodes().count(), 2);
+        let edges: Vec<EdgeInd> = path.edges().collect();
+        assert_eq!(edges.len(), 1);
+        let (start, end) = path.edge_endpoints(&edges[0]);
+        assert_eq!((start.min(end), start.max(end)), (0, 4));
+        assert_eq!(path.edge(&edges[0]).data, 10.0);
+
+        // a star's center and leaves don't have two edges, but the node
+        // subdividing a directed edge does, with one in and one out
+        let mut g: ALGraph<(), (), Directed> = ALGraph::new();
+        for _ in 0..4 {
+            g.add_node(());
+        }
+        g.add_edges([(0, 1, ()), (0, 2, ()), (0, 3, ())]);
+        let e = g.edges().next().unwrap();
+        let (start, end) = g.edge_endpoints(&e);
+        let mid = g.subdivide_edge(&e, (), ());
+        g.add_edge(&1, &1, ());
+        assert_eq!(g.smooth(|_, _| ()), 1);
+        assert!(!g.nodes().any(|n| n == mid));
+        assert!(g.neighbors(&start).any(|n| n == end));
+        assert_eq!(g.edges().count(), 4);
     }
 
-    fn edges_at(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>>> {
-        todo!()
+    #[test]
+    fn test_contract_nodes() {
+        // a and b share two edges, so those become self-loops when contracted
+        let mut g: ALGraph<(), (), Undirected> = ALGraph::new();
+        let nodes: Vec<NodeInd> = (0..5).map(|_| g.add_node(())).collect();
+        let (a, b) = (nodes[0], nodes[1]);
+        for (start, end) in [(a, b), (a, b), (a, 2), (a, 3), (b, 3), (b, 4)] {
+            g.add_edge(&start, &end, ());
+        }
+        let shared = 2;
+        let combined = g.edges_at(&a).count() + g.edges_at(&b).count();
+
+        g.contract_nodes(&a, &b, true);
+        assert_eq!(g.edges_at(&a).count(), combined - 2 * shared);
+        assert_eq!(g.nodes().count(), 4);
+        assert_eq!(g.edges().count(), 4);
+        let mut nbrs: Vec<NodeInd> = g.neighbors(&a).collect();
+        nbrs.sort();
+        assert_eq!(nbrs, vec![2, 3, 3, 4]);
     }
 
-    fn edge_endpoints(&self, e: &EdgeInd) -> (NodeInd, NodeInd) {
-        todo!()
+    #[test]
+    fn test_remove_node() {
+        let mut g: ALGraph<usize, (), Directed> = ALGraph::new();
+        let a = g.add_node(0);
+        let b = g.add_node(1);
+        let c = g.add_node(2);
+        g.add_edge(&a, &b, ());
+        g.add_edge(&b, &c, ());
+        g.add_edge(&b, &b, ());
+
+        assert_eq!(g.remove_node(&b), 1);
+        assert_eq!(g.nodes().count(), 2);
+        assert_eq!(g.edges().count(), 0);
+        assert_eq!(g.edges_at(&a).count(), 0);
+        assert_eq!(g.edges_at(&c).count(), 0);
+    }
+
+    #[test]
+    fn test_direction_conversion() {
+        let mut g: ALGraph<(), (), Directed> = ALGraph::new();
+        let nodes: Vec<NodeInd> = (0..3).map(|_| g.add_node(())).collect();
+        for (start, end) in [(0, 1), (1, 0), (1, 2), (2, 1)] {
+            g.add_edge(&nodes[start], &nodes[end], ());
+        }
+
+        let merged = g.to_undirected(true);
+        assert_eq!(merged.edges().count(), 2);
+        assert_eq!(merged.nodes().count(), 3);
+        assert_eq!(g.to_undirected(false).edges().count(), 4);
+
+        let directed = merged.to_directed();
+        assert_eq!(directed.edges().count(), 4);
+        let mut nbrs: Vec<NodeInd> = directed.neighbors(&1).collect();
+        nbrs.sort();
+        assert_eq!(nbrs, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_underlying_simple() {
+        let mut g: ALGraph<(), i32, Directed> = ALGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let c = g.add_node(());
+        g.add_edges([(a, b, 1), (b, a, 2), (a, b, 3), (b, b, 4), (c, b, 5)]);
+
+        let simple = g.underlying_simple();
+        assert_eq!(simple.nodes().count(), 3);
+        assert_eq!(simple.edges().count(), 2);
+        assert_eq!(simple.neighbors(&a).collect::<Vec<_>>(), vec![b]);
+        let mut nbrs: Vec<NodeInd> = simple.neighbors(&b).collect();
+        nbrs.sort_unstable();
+        assert_eq!(nbrs, vec![a, c]);
+    }
+
+    #[test]
+    fn test_try_add_edge_acyclic() {
+        let mut g: ALGraph<(), (), Directed> = ALGraph::new();
+        for _ in 0..4 {
+            g.add_node(());
+        }
+        assert_eq!(g.try_add_edge_acyclic(&0, &1, ()), Ok(0));
+        assert_eq!(g.try_add_edge_acyclic(&1, &2, ()), Ok(1));
+        // a forward edge skipping ahead is fine, but going back isn't
+        assert_eq!(g.try_add_edge_acyclic(&0, &2, ()), Ok(2));
+        assert_eq!(g.try_add_edge_acyclic(&2, &0, ()), Err(WouldCreateCycle));
+        assert_eq!(g.try_add_edge_acyclic(&3, &3, ()), Err(WouldCreateCycle));
+        assert_eq!(g.try_add_edge_acyclic(&2, &3, ()), Ok(3));
+        assert_eq!(g.try_add_edge_acyclic(&3, &1, ()), Err(WouldCreateCycle));
+        assert_eq!(g.edges().count(), 4);
+    }
+
+    #[test]
+    fn test_scale_edge_weights() {
+        let mut g: ALGraph<(), f64, Undirected> = ALGraph::new();
+        for _ in 0..3 {
+            g.add_node(());
+        }
+        g.add_edges([(0, 1, 2.0), (1, 2, 8.0), (2, 0, 4.0)]);
+        let before = g.clone();
+
+        let max = g.edge_references().map(|(_, _, w)| *w).fold(0.0, f64::max);
+        g.scale_edge_weights(|w| *w /= max);
+        let weights: Vec<f64> = g.edges_sorted().iter().map(|e| g.edge(e).data).collect();
+        assert_eq!(weights, vec![0.25, 1.0, 0.5]);
+        for e in g.edges() {
+            assert_eq!(g.edge_endpoints(&e), before.edge_endpoints(&e));
+        }
+        assert_eq!(g.nodes_sorted(), before.nodes_sorted());
+    }
+
+    #[test]
+    fn test_fold_neighbors() {
+        let mut g: ALGraph<(), f64, Directed> = ALGraph::new();
+        for _ in 0..3 {
+            g.add_node(());
+        }
+        g.add_edges([(0, 1, 1.5), (0, 2, 2.0), (0, 1, 0.25), (2, 0, 10.0)]);
+        let total = g.fold_neighbors(&0, 0.0, |acc, _, e| acc + e.data);
+        assert_eq!(total, 3.75);
+        let mut nbrs = g.fold_neighbors(&0, vec![], |mut acc, m, _| {
+            acc.push(m);
+            acc
+        });
+        nbrs.sort_unstable();
+        assert_eq!(nbrs, vec![1, 1, 2]);
+        assert_eq!(g.fold_neighbors(&1, 0, |acc, _, _| acc + 1), 0);
+    }
+
+    #[test]
+    fn test_propagate() {
+        let mut g: ALGraph<(), f64, Directed> = ALGraph::new();
+        for _ in 0..4 {
+            g.add_node(());
+        }
+        g.add_edges([
+            (0, 1, 1.0),
+            (2, 1, 2.0),
+            (1, 3, 0.5),
+            (0, 3, 1.0),
+            (3, 3, 1.0),
+        ]);
+        let init = HashMap::from([(0, 1.0), (1, 10.0), (2, 100.0), (3, 1000.0)]);
+
+        // each node gets the sum of its in-neighbors' values, scaled by weight
+        let next = g.propagate(&init, |v, e| v * e.data, |_, msgs| msgs.iter().sum());
+        let expected = HashMap::from([(0, 0.0), (1, 201.0), (2, 0.0), (3, 1006.0)]);
+        assert_eq!(next, expected);
+
+        // without a value for 2, node 1 only hears from 0
+        let partial: HashMap<NodeInd, usize> = HashMap::from([(0, 1)]);
+        let counts = g.propagate(&partial, |v, _| *v, |_, msgs| msgs.len());
+        assert_eq!(counts, HashMap::from([(0, 0), (1, 1), (2, 0), (3, 1)]));
+    }
+
+    #[test]
+    fn test_sorted_indices() {
+        let mut g: ALGraph<(), (), Directed> = ALGraph::new();
+        for _ in 0..50 {
+            g.add_node(());
+        }
+        for i in 0..49 {
+            g.add_edge(&(49 - i), &i, ());
+        }
+        g.remove_node(&20);
+        g.remove_edge(&3);
+
+        let nodes = g.nodes_sorted();
+        assert_eq!(nodes.len(), 49);
+        assert!(nodes.windows(2).all(|w| w[0] < w[1]));
+        let edges = g.edges_sorted();
+        assert!(edges.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(edges.len(), g.edges().count());
+
+        assert_eq!(g.clone().nodes_sorted(), nodes);
+        assert_eq!(g.edges_sorted(), edges);
+    }
+
+    #[test]
+    fn test_distinct_neighbors() {
+        let mut g: ALGraph<(), (), Undirected> = ALGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let c = g.add_node(());
+        g.add_edge(&a, &b, ());
+        g.add_edge(&a, &c, ());
+        g.add_edge(&b, &a, ());
+        assert_eq!(g.neighbors(&a).count(), 3);
+        assert_eq!(g.distinct_neighbors(&a).collect::<Vec<_>>(), vec![b, c]);
+        assert_eq!(g.distinct_neighbors(&c).collect::<Vec<_>>(), vec![a]);
+    }
+
+    #[test]
+    fn test_neighbors_with_edges() {
+        let mut g: ALGraph<(), f64, Directed> = ALGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let c = g.add_node(());
+        let ab = g.add_edge(&a, &b, 1.5);
+        let ac = g.add_edge(&a, &c, 2.5);
+        g.add_edge(&c, &a, 3.5);
+
+        let nbrs: Vec<(NodeInd, EdgeInd)> = g.neighbors_with_edges(&a).collect();
+        assert_eq!(nbrs, vec![(b, ab), (c, ac)]);
+        for (nbr, e) in nbrs {
+            assert_eq!(g.edge_endpoints(&e), (a, nbr));
+        }
+        assert_eq!(g.edge(&ac).data, 2.5);
+        assert_eq!(g.neighbors_with_edges(&b).count(), 0);
+    }
+
+    #[test]
+    fn test_edge_references() {
+        let mut g: ALGraph<(), usize, Undirected> = ALGraph::new();
+        let nodes: Vec<NodeInd> = (0..4).map(|_| g.add_node(())).collect();
+        let expected = vec![(0, 1, 10), (1, 2, 20), (2, 3, 30), (3, 3, 40), (0, 1, 50)];
+        for &(start, end, data) in &expected {
+            g.add_edge(&nodes[start], &nodes[end], data);
+        }
+
+        let mut refs: Vec<(NodeInd, NodeInd, usize)> = g
+            .edge_references()
+            .map(|(start, end, &data)| (start, end, data))
+            .collect();
+        refs.sort_by_key(|&(_, _, data)| data);
+        assert_eq!(refs, expected);
+    }
+
+    #[test]
+    fn test_node_references() {
+        let mut g: ALGraph<String, (), Directed> = ALGraph::new();
+        let names = ["a", "b", "c"];
+        let nodes: Vec<NodeInd> = names.iter().map(|s| g.add_node(s.to_string())).collect();
+        g.remove_node(&nodes[1]);
+
+        let mut refs: Vec<(NodeInd, &String)> = g.node_references().collect();
+        refs.sort();
+        assert_eq!(
+            refs,
+            vec![(nodes[0], &"a".to_string()), (nodes[2], &"c".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_into_nodes_edges() {
+        let mut g: ALGraph<String, usize, Directed> = ALGraph::new();
+        let a = g.add_node("a".to_string());
+        let b = g.add_node("b".to_string());
+        let c = g.add_node("c".to_string());
+        let ab = g.add_edge(&a, &b, 1);
+        let bc = g.add_edge(&b, &c, 2);
+
+        let (nodes, edges) = g.into_nodes_edges();
+        assert_eq!(
+            nodes,
+            vec![
+                (a, "a".to_string()),
+                (b, "b".to_string()),
+                (c, "c".to_string())
+            ]
+        );
+        assert_eq!(edges.len(), 2);
+        assert_eq!(
+            (edges[0].index, edges[0].start, edges[0].end, edges[0].data),
+            (ab, a, b, 1)
+        );
+        assert_eq!(
+            (edges[1].index, edges[1].start, edges[1].end, edges[1].data),
+            (bc, b, c, 2)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "ordered"))]
+    fn test_capacity() {
+        let mut g: ALGraph<(), (), Undirected> = ALGraph::new();
+        g.reserve_nodes(100);
+        g.reserve_edges(100);
+        let node_cap = g.nodes.capacity();
+        let edge_cap = g.edges.capacity();
+        assert!(node_cap >= 100 && edge_cap >= 100 && g.adj.capacity() >= 100);
+
+        for i in 0..100 {
+            g.add_node(());
+            if i > 0 {
+                g.add_edge(&(i - 1), &i, ());
+            }
+        }
+        assert_eq!(g.nodes.capacity(), node_cap);
+        assert_eq!(g.edges.capacity(), edge_cap);
+
+        for i in 10..100 {
+            g.remove_node(&i);
+        }
+        g.shrink_to_fit();
+        assert!(g.nodes.capacity() < node_cap);
+        assert!(g.edges.capacity() < edge_cap);
+        assert_eq!(g.nodes().count(), 10);
+    }
+
+    #[test]
+    fn test_add_edge_autonodes() {
+        let mut g: ALGraph<u8, (), Undirected> = ALGraph::new();
+        let e = g.add_edge_autonodes(5, 7, ());
+        let mut nodes: Vec<NodeInd> = g.nodes().collect();
+        nodes.sort_unstable();
+        assert_eq!(nodes, (0..8).collect::<Vec<_>>());
+        assert_eq!(g.edge_endpoints(&e), (5, 7));
+        assert_eq!(g.neighbors(&7).collect::<Vec<_>>(), vec![5]);
+        assert_eq!(*g.node(&6), 0);
+
+        // existing nodes are left alone, and removed ones come back
+        *g.node_mut(&1) = 1;
+        g.remove_node(&2);
+        g.add_edge_autonodes(1, 2, ());
+        assert_eq!(*g.node(&1), 1);
+        assert_eq!(g.nodes().count(), 8);
+        assert_eq!(g.add_node(0), 8);
+    }
+
+    #[test]
+    fn test_add_edges() {
+        let mut g: ALGraph<(), u32, Directed> = ALGraph::new();
+        for _ in 0..4 {
+            g.add_node(());
+        }
+        let first = g.add_edge(&0, &1, 0);
+        let added = g.add_edges((1..4).map(|i| (i - 1, i, i as u32)));
+        assert_eq!(added, vec![first + 1, first + 2, first + 3]);
+        for (i, e) in added.iter().enumerate() {
+            let edge = g.edge(e);
+            assert_eq!((edge.start, edge.end, edge.data), (i, i + 1, i as u32 + 1));
+        }
+        assert!(g.add_edges(vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_graph_power() {
+        let mut p4: ALGraph<(), (), Undirected> = ALGraph::new();
+        for _ in 0..4 {
+            p4.add_node(());
+        }
+        p4.add_edges([(0, 1, ()), (1, 2, ()), (2, 3, ())]);
+        let square = p4.graph_power(2);
+        let mut edges: Vec<(NodeInd, NodeInd)> =
+            square.edges().map(|e| square.edge_endpoints(&e)).collect();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![(0, 1), (0, 2), (1, 2), (1, 3), (2, 3)]);
+
+        let mut multi = p4.clone();
+        multi.add_edge(&1, &0, ());
+        multi.add_edge(&3, &3, ());
+        assert_eq!(multi.graph_power(1), p4);
+        assert_eq!(p4.graph_power(0).edges().count(), 0);
+
+        // directed powers only follow edges forwards
+        let mut g: ALGraph<(), (), Directed> = ALGraph::new();
+        for _ in 0..3 {
+            g.add_node(());
+        }
+        g.add_edges([(0, 1, ()), (1, 2, ())]);
+        let square = g.graph_power(2);
+        assert_eq!(square.edges().count(), 3);
+        assert!(square.neighbors(&0).any(|n| n == 2));
+        assert!(!square.neighbors(&2).any(|n| n == 0));
+    }
+
+    #[test]
+    #[cfg(feature = "ordered")]
+    fn test_ordered_iteration() {
+        let build = || {
+            let mut g: ALGraph<(), (), Undirected> = ALGraph::new();
+            for _ in 0..30 {
+                g.add_node(());
+            }
+            for i in 0..29 {
+                g.add_edge(&i, &((i * 7 + 3) % 30), ());
+            }
+            g.remove_node(&11);
+            g
+        };
+        let (a, b) = (build(), build());
+        assert_eq!(a.nodes().collect::<Vec<_>>(), b.nodes().collect::<Vec<_>>());
+        assert_eq!(a.edges().collect::<Vec<_>>(), b.edges().collect::<Vec<_>>());
+        assert_eq!(a.nodes().collect::<Vec<_>>(), a.nodes_sorted());
+        assert_eq!(a.edges().collect::<Vec<_>>(), a.edges_sorted());
+    }
+
+    #[test]
+    fn test_structural_eq_and_hash() {
+        use std::collections::HashSet;
+
+        let build = |edges: &[(NodeInd, NodeInd, usize)]| {
+            let mut g: ALGraph<usize, usize, Undirected> = ALGraph::new();
+            for i in 0..4 {
+                g.add_node(i * 10);
+            }
+            for (start, end, data) in edges {
+                g.add_edge(start, end, *data);
+            }
+            g
+        };
+        let a = build(&[(0, 1, 1), (1, 2, 2), (2, 3, 3), (0, 1, 4)]);
+        let b = build(&[(2, 1, 2), (1, 0, 4), (3, 2, 3), (0, 1, 1)]);
+        let c = build(&[(0, 1, 1), (1, 2, 2), (2, 3, 3), (0, 1, 1)]);
+
+        assert!(a.structurally_eq(&b));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let hash = |g: &ALGraph<usize, usize, Undirected>| {
+            let mut hasher = DefaultHasher::new();
+            g.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+        let set: HashSet<ALGraph<usize, usize, Undirected>> = [a, b, c].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_subgraph() {
+        let mut g: ALGraph<char, usize, Directed> = ALGraph::new();
+        let nodes: Vec<NodeInd> = "abcd".chars().map(|c| g.add_node(c)).collect();
+        for (i, (start, end)) in [(0, 1), (1, 2), (2, 3), (3, 1)].into_iter().enumerate() {
+            g.add_edge(&nodes[start], &nodes[end], i);
+        }
+
+        let (sub, new_index) = g.subgraph(&[nodes[3], nodes[1], nodes[2]]);
+        assert_eq!(new_index, HashMap::from([(1, 0), (2, 1), (3, 2)]));
+        assert_eq!(*sub.node(&0), 'b');
+        let mut edges: Vec<(NodeInd, NodeInd, usize)> =
+            sub.edge_references().map(|(s, e, &d)| (s, e, d)).collect();
+        edges.sort();
+        assert_eq!(edges, vec![(0, 1, 1), (1, 2, 2), (2, 0, 3)]);
+    }
+
+    #[test]
+    fn test_relabel_by() {
+        let mut g: ALGraph<&str, (), Directed> = ALGraph::new();
+        let names = ["carol", "alice", "dave", "bob"];
+        let nodes: Vec<NodeInd> = names.iter().map(|&name| g.add_node(name)).collect();
+        g.add_edge(&nodes[0], &nodes[1], ());
+        g.add_edge(&nodes[3], &nodes[2], ());
+
+        let (sorted, new_index) = g.relabel_by(|name| *name);
+        let labels: Vec<&str> = sorted
+            .nodes_sorted()
+            .iter()
+            .map(|n| *sorted.node(n))
+            .collect();
+        assert_eq!(labels, vec!["alice", "bob", "carol", "dave"]);
+        for n in &nodes {
+            assert_eq!(sorted.node(&new_index[n]), g.node(n));
+        }
+        // carol -> alice and bob -> dave, in the same order as before
+        assert_eq!(sorted.edge_endpoints(&0), (2, 0));
+        assert_eq!(sorted.edge_endpoints(&1), (1, 3));
+    }
+
+    #[test]
+    fn test_disjoint_union() {
+        let mut a: ALGraph<char, (), Undirected> = ALGraph::new();
+        for c in "xyz".chars() {
+            a.add_node(c);
+        }
+        a.add_edge(&0, &1, ());
+        a.add_edge(&1, &2, ());
+        // a gap in the indices of the second graph
+        let mut b: ALGraph<char, (), Undirected> = ALGraph::new();
+        for c in "pqr".chars() {
+            b.add_node(c);
+        }
+        b.remove_node(&0);
+        b.add_edge(&1, &2, ());
+
+        let (union, new_index) = a.disjoint_union(&b);
+        assert_eq!(union.nodes().count(), 5);
+        assert_eq!(union.edges().count(), 3);
+        assert_eq!(new_index, HashMap::from([(1, 3), (2, 4)]));
+        for n in [1, 2] {
+            assert_eq!(union.node(&new_index[&n]), b.node(&n));
+        }
+        // the first graph's part is unchanged
+        for n in a.nodes() {
+            assert_eq!(union.node(&n), a.node(&n));
+        }
+        assert_eq!(union.edge_endpoints(&0), (0, 1));
+
+        assert_eq!(reachable_from(&union, &0), HashSet::from([0, 1, 2]));
+        assert_eq!(reachable_from(&union, &3), HashSet::from([3, 4]));
+    }
+
+    #[test]
+    fn test_components() {
+        let triangle = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0)]);
+        let (g, _) = triangle.disjoint_union(&triangle);
+        let components: Vec<ALGraph<(), (), Undirected>> = g.components().collect();
+        assert_eq!(components.len(), 2);
+        for c in &components {
+            assert_eq!(c.nodes_sorted(), vec![0, 1, 2]);
+            assert!(c.structurally_eq(&triangle));
+        }
+
+        // direction doesn't matter, and lone nodes are components too
+        let g = graph_from_edges::<Directed>(4, &[(1, 0), (1, 3)]);
+        let sizes: Vec<(usize, usize)> = g
+            .components()
+            .map(|c| (c.nodes().count(), c.edges().count()))
+            .collect();
+        assert_eq!(sizes, vec![(3, 2), (1, 0)]);
+    }
+
+    #[test]
+    fn test_undirected_edges() {
+        let mut g: ALGraph<(), (), Undirected> = ALGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let ab = g.add_edge(&a, &b, ());
+
+        assert_eq!(g.neighbors(&a).collect::<Vec<_>>(), vec![b]);
+        assert_eq!(g.neighbors(&b).collect::<Vec<_>>(), vec![a]);
+
+        g.remove_edge(&ab);
+        assert_eq!(g.edges_at(&a).count(), 0);
+        assert_eq!(g.edges_at(&b).count(), 0);
     }
 }
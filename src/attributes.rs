@@ -0,0 +1,291 @@
+//! Graphs with named attributes of mixed types on their nodes and edges.
+
+use std::collections::HashMap;
+
+use crate::adj_list_graph::ALGraph;
+use crate::graph_base::{Edge, EdgeInd, GraphBase, GraphType, GraphView, NodeInd};
+
+/// The value of an attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+}
+
+/// A graph where nodes and edges can have any number of named attributes, each
+/// a [`Value`], on top of their data. This is for property graphs, where
+/// different nodes have different kinds of information, without having to fit
+/// it all into `N` and `E`. Attributes are stored by name, so finding every
+/// node with an attribute doesn't need to look at the rest. Removing a node or
+/// edge removes its attributes too.
+#[derive(Clone, Debug)]
+pub struct AttrGraph<N, E, Ty: GraphType> {
+    graph: ALGraph<N, E, Ty>,
+    node_attrs: HashMap<String, HashMap<NodeInd, Value>>,
+    edge_attrs: HashMap<String, HashMap<EdgeInd, Value>>,
+}
+
+impl<N, E, Ty: GraphType> AttrGraph<N, E, Ty> {
+    /// Makes a new, empty graph.
+    pub fn new() -> Self {
+        Self {
+            graph: ALGraph::new(),
+            node_attrs: HashMap::new(),
+            edge_attrs: HashMap::new(),
+        }
+    }
+
+    /// Gets the underlying graph.
+    pub fn graph(&self) -> &ALGraph<N, E, Ty> {
+        &self.graph
+    }
+
+    /// Sets an attribute of a node, returning the value it had before, if any.
+    /// Panics if the node isn't in the graph.
+    pub fn set_node_attr(&mut self, n: &NodeInd, key: &str, value: Value) -> Option<Value> {
+        assert!(self.graph.contains_node(n), "node {} doesn't exist", n);
+        self.node_attrs
+            .entry(key.to_string())
+            .or_default()
+            .insert(*n, value)
+    }
+
+    /// Gets an attribute of a node, if it has it.
+    pub fn get_node_attr(&self, n: &NodeInd, key: &str) -> Option<&Value> {
+        self.node_attrs.get(key)?.get(n)
+    }
+
+    /// Removes an attribute from a node, returning its value, if it had it.
+    pub fn remove_node_attr(&mut self, n: &NodeInd, key: &str) -> Option<Value> {
+        self.node_attrs.get_mut(key)?.remove(n)
+    }
+
+    /// Sets an attribute of an edge, returning the value it had before, if any.
+    /// Panics if the edge isn't in the graph.
+    pub fn set_edge_attr(&mut self, e: &EdgeInd, key: &str, value: Value) -> Option<Value> {
+        assert!(self.graph.contains_edge(e), "edge {} doesn't exist", e);
+        self.edge_attrs
+            .entry(key.to_string())
+            .or_default()
+            .insert(*e, value)
+    }
+
+    /// Gets an attribute of an edge, if it has it.
+    pub fn get_edge_attr(&self, e: &EdgeInd, key: &str) -> Option<&Value> {
+        self.edge_attrs.get(key)?.get(e)
+    }
+
+    /// Removes an attribute from an edge, returning its value, if it had it.
+    pub fn remove_edge_attr(&mut self, e: &EdgeInd, key: &str) -> Option<Value> {
+        self.edge_attrs.get_mut(key)?.remove(e)
+    }
+
+    /// Gets the nodes that have the attribute with a value matching `pred`, in
+    /// ascending order. Nodes without the attribute are never included.
+    pub fn nodes_where<F: Fn(&Value) -> bool>(&self, key: &str, pred: F) -> Vec<NodeInd> {
+        let Some(values) = self.node_attrs.get(key) else {
+            return vec![];
+        };
+        let mut nodes: Vec<NodeInd> = values
+            .iter()
+            .filter(|(_, value)| pred(value))
+            .map(|(&n, _)| n)
+            .collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    /// Gets the edges that have the attribute with a value matching `pred`, in
+    /// ascending order. Edges without the attribute are never included.
+    pub fn edges_where<F: Fn(&Value) -> bool>(&self, key: &str, pred: F) -> Vec<EdgeInd> {
+        let Some(values) = self.edge_attrs.get(key) else {
+            return vec![];
+        };
+        let mut edges: Vec<EdgeInd> = values
+            .iter()
+            .filter(|(_, value)| pred(value))
+            .map(|(&e, _)| e)
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+}
+
+impl<N, E, Ty: GraphType> Default for AttrGraph<N, E, Ty> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, E: Clone, Ty: GraphType> GraphView<N, E, Ty> for AttrGraph<N, E, Ty> {
+    fn node(&self, n: &NodeInd) -> &N {
+        self.graph.node(n)
+    }
+
+    fn edge(&self, e: &EdgeInd) -> &Edge<E> {
+        self.graph.edge(e)
+    }
+
+    fn nodes(&self) -> Box<dyn Iterator<Item = NodeInd>> {
+        self.graph.nodes()
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeInd>> {
+        self.graph.edges()
+    }
+
+    fn edges_from(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        self.graph.edges_from(n)
+    }
+
+    fn edges_to(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        self.graph.edges_to(n)
+    }
+
+    fn edges_at(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        self.graph.edges_at(n)
+    }
+
+    fn edge_endpoints(&self, e: &EdgeInd) -> (NodeInd, NodeInd) {
+        self.graph.edge_endpoints(e)
+    }
+}
+
+impl<N, E: Clone, Ty: GraphType> GraphBase<N, E, Ty> for AttrGraph<N, E, Ty> {
+    fn node_mut(&mut self, n: &NodeInd) -> &mut N {
+        self.graph.node_mut(n)
+    }
+
+    fn edge_mut(&mut self, e: &EdgeInd) -> &mut Edge<E> {
+        self.graph.edge_mut(e)
+    }
+
+    fn add_node(&mut self, data: N) -> NodeInd {
+        self.graph.add_node(data)
+    }
+
+    fn add_edge(&mut self, start: &NodeInd, end: &NodeInd, data: E) -> EdgeInd {
+        self.graph.add_edge(start, end, data)
+    }
+
+    fn remove_edge(&mut self, e: &EdgeInd) -> Edge<E> {
+        for values in self.edge_attrs.values_mut() {
+            values.remove(e);
+        }
+        self.graph.remove_edge(e)
+    }
+
+    fn remove_node(&mut self, n: &NodeInd) -> N {
+        let edges: Vec<EdgeInd> = self.graph.edges_at(n).map(|e| e.index).collect();
+        for values in self.edge_attrs.values_mut() {
+            for e in &edges {
+                values.remove(e);
+            }
+        }
+        for values in self.node_attrs.values_mut() {
+            values.remove(n);
+        }
+        self.graph.remove_node(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::Directed;
+
+    #[test]
+    fn test_attrs() {
+        let mut g: AttrGraph<(), (), Directed> = AttrGraph::new();
+        let alice = g.add_node(());
+        let post = g.add_node(());
+        let wrote = g.add_edge(&alice, &post, ());
+
+        g.set_node_attr(&alice, "name", Value::Str("alice".to_string()));
+        g.set_node_attr(&alice, "age", Value::Int(31));
+        g.set_node_attr(&post, "score", Value::Float(4.5));
+        g.set_node_attr(&post, "pinned", Value::Bool(true));
+        g.set_edge_attr(&wrote, "year", Value::Int(2024));
+
+        assert_eq!(
+            g.get_node_attr(&alice, "name"),
+            Some(&Value::Str("alice".to_string()))
+        );
+        assert_eq!(g.get_node_attr(&alice, "age"), Some(&Value::Int(31)));
+        assert_eq!(g.get_node_attr(&post, "score"), Some(&Value::Float(4.5)));
+        assert_eq!(g.get_node_attr(&post, "pinned"), Some(&Value::Bool(true)));
+        assert_eq!(g.get_node_attr(&post, "age"), None);
+        assert_eq!(g.get_node_attr(&alice, "missing"), None);
+        assert_eq!(g.get_edge_attr(&wrote, "year"), Some(&Value::Int(2024)));
+
+        // setting again replaces the value, whatever its type
+        assert_eq!(
+            g.set_node_attr(&alice, "age", Value::Str("unknown".to_string())),
+            Some(Value::Int(31))
+        );
+        assert_eq!(
+            g.get_node_attr(&alice, "age"),
+            Some(&Value::Str("unknown".to_string()))
+        );
+        assert_eq!(g.remove_node_attr(&post, "pinned"), Some(Value::Bool(true)));
+        assert_eq!(g.get_node_attr(&post, "pinned"), None);
+    }
+
+    #[test]
+    fn test_attrs_removed_with_graph() {
+        let mut g: AttrGraph<(), (), Directed> = AttrGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let e = g.add_edge(&a, &b, ());
+        g.set_node_attr(&a, "x", Value::Int(1));
+        g.set_edge_attr(&e, "x", Value::Int(2));
+
+        g.remove_node(&a);
+        assert_eq!(g.get_node_attr(&a, "x"), None);
+        assert_eq!(g.get_edge_attr(&e, "x"), None);
+    }
+
+    #[test]
+    fn test_where() {
+        let mut g: AttrGraph<(), (), Directed> = AttrGraph::new();
+        let user = Value::Str("user".to_string());
+        let users: Vec<NodeInd> = (0..3).map(|_| g.add_node(())).collect();
+        let group = g.add_node(());
+        let untagged = g.add_node(());
+        for u in &users {
+            g.set_node_attr(u, "type", user.clone());
+        }
+        g.set_node_attr(&group, "type", Value::Str("group".to_string()));
+        g.set_node_attr(&untagged, "name", Value::Str("user".to_string()));
+
+        assert_eq!(g.nodes_where("type", |v| v == &user), users);
+        assert_eq!(g.nodes_where("type", |_| true).len(), 4);
+        assert_eq!(g.nodes_where("missing", |_| true), vec![]);
+
+        let old = g.add_edge(&users[0], &group, ());
+        let new = g.add_edge(&users[1], &group, ());
+        g.set_edge_attr(&old, "since", Value::Int(2019));
+        g.set_edge_attr(&new, "since", Value::Int(2024));
+        let recent = g.edges_where("since", |v| matches!(v, Value::Int(y) if *y > 2020));
+        assert_eq!(recent, vec![new]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_attr_removed_node() {
+        let mut g: AttrGraph<(), (), Directed> = AttrGraph::new();
+        let a = g.add_node(());
+        g.remove_node(&a);
+        g.set_node_attr(&a, "x", Value::Int(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_attr_missing_edge() {
+        let mut g: AttrGraph<(), (), Directed> = AttrGraph::new();
+        let a = g.add_node(());
+        g.set_edge_attr(&a, "x", Value::Int(1));
+    }
+}
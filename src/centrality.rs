@@ -0,0 +1,174 @@
+//! Centrality measures: scores for how important each node is to the graph.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+use crate::graph_base::{Edge, EdgeInd, GraphType, GraphView, NodeInd};
+use crate::shortest_paths::dijkstra;
+use crate::traversal::bfs_distances;
+
+/// Gets the closeness of a node from its distances to the nodes it can reach,
+/// out of `n` nodes in the graph. Nodes that can only reach themselves get 0.
+fn closeness_from(dists: impl Iterator<Item = f64>, n: usize) -> f64 {
+    let (reached, total) = dists.fold((0, 0.0), |(r, t), d| (r + 1, t + d));
+    // the node itself is included, at distance 0
+    let others = reached - 1;
+    if others == 0 || total == 0.0 {
+        return 0.0;
+    }
+    let others = others as f64;
+    (others / total) * (others / (n - 1) as f64)
+}
+
+/// Gets the closeness centrality of every node: the reciprocal of the average
+/// number of edges on the shortest paths from it to the nodes it can reach.
+/// Distances respect edge direction, so for directed graphs this is about how
+/// easily a node reaches the others. For graphs that aren't connected, each
+/// score is scaled by the fraction of other nodes the node can reach, so nodes
+/// in small components aren't rated highly just for being close to the few
+/// nodes they can reach.
+pub fn closeness_centrality<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+) -> HashMap<NodeInd, f64> {
+    let n = graph.nodes().count();
+    graph
+        .nodes()
+        .map(|u| {
+            let dists = bfs_distances(graph, &u).into_values().map(|d| d as f64);
+            (u, closeness_from(dists, n))
+        })
+        .collect()
+}
+
+/// Like [`closeness_centrality`], but using weighted shortest path lengths
+/// from Dijkstra's algorithm instead of numbers of edges. Weights must be
+/// non-negative.
+pub fn closeness_centrality_weighted<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    weight: F,
+) -> HashMap<NodeInd, f64>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let n = graph.nodes().count();
+    graph
+        .nodes()
+        .map(|u| {
+            let dists = dijkstra(graph, &u, &weight).into_values();
+            (u, closeness_from(dists, n))
+        })
+        .collect()
+}
+
+/// Gets the betweenness of every edge: the number of shortest paths between
+/// pairs of nodes that go through it, with each pair's paths splitting one
+/// unit between them, using Brandes' algorithm with the credit going to edges
+/// instead of nodes. Paths are counted by their number of edges and respect
+/// edge direction, and for undirected graphs each pair only counts once.
+/// Parallel edges make separate paths, and self-loops are never on one.
+///
+/// This runs a BFS from every node, so it takes O(VE) time.
+pub fn edge_betweenness<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+) -> HashMap<EdgeInd, f64> {
+    let mut betweenness: HashMap<EdgeInd, f64> = graph.edges().map(|e| (e, 0.0)).collect();
+    for source in graph.nodes() {
+        // the nodes in the order they're found, the number of shortest paths
+        // to each, and the edges on them that come into each
+        let mut order = vec![];
+        let mut dist: HashMap<NodeInd, usize> = HashMap::from([(source, 0)]);
+        let mut paths: HashMap<NodeInd, f64> = HashMap::from([(source, 1.0)]);
+        let mut preds: HashMap<NodeInd, Vec<(NodeInd, EdgeInd)>> = HashMap::new();
+        let mut queue = VecDeque::from([source]);
+        while let Some(curr) = queue.pop_front() {
+            order.push(curr);
+            for (next, e) in graph.neighbors_with_edges(&curr) {
+                let d = dist[&curr] + 1;
+                if let Entry::Vacant(slot) = dist.entry(next) {
+                    slot.insert(d);
+                    queue.push_back(next);
+                }
+                if dist[&next] == d {
+                    *paths.entry(next).or_default() += paths[&curr];
+                    preds.entry(next).or_default().push((curr, e));
+                }
+            }
+        }
+
+        // how much of the paths from the source each node passes on
+        let mut credit: HashMap<NodeInd, f64> = HashMap::new();
+        for n in order.into_iter().rev() {
+            let passed = 1.0 + credit.get(&n).copied().unwrap_or(0.0);
+            for &(pred, e) in preds.get(&n).into_iter().flatten() {
+                let share = paths[&pred] / paths[&n] * passed;
+                *betweenness.get_mut(&e).unwrap() += share;
+                *credit.entry(pred).or_default() += share;
+            }
+        }
+    }
+
+    if !graph.is_directed() {
+        betweenness.values_mut().for_each(|b| *b /= 2.0);
+    }
+    betweenness
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::{Directed, Undirected};
+    use crate::test_util::{graph_from_edges, weighted_graph_from_edges};
+
+    #[test]
+    fn test_closeness() {
+        // on a star the center is one step from everything, and each leaf is
+        // one step from the center and two from the other leaves
+        let star = graph_from_edges::<Undirected>(5, &[(0, 1), (0, 2), (0, 3), (0, 4)]);
+        let c = closeness_centrality(&star);
+        assert_eq!(c[&0], 1.0);
+        assert_eq!(c[&1], 4.0 / 7.0);
+
+        // 2 can't reach anything, and 0 only reaches half the graph
+        let g = graph_from_edges::<Directed>(3, &[(0, 1), (1, 0)]);
+        let c = closeness_centrality(&g);
+        assert_eq!(c[&0], 0.5);
+        assert_eq!(c[&2], 0.0);
+    }
+
+    #[test]
+    fn test_closeness_weighted() {
+        let edges = [(0, 1), (1, 2), (2, 3), (3, 0), (0, 2), (3, 4)];
+        let g = graph_from_edges::<Undirected>(6, &edges);
+        let unit: Vec<(NodeInd, NodeInd, f64)> = edges.iter().map(|&(u, v)| (u, v, 1.0)).collect();
+        let weighted = weighted_graph_from_edges::<Undirected>(6, &unit);
+        assert_eq!(
+            closeness_centrality_weighted(&weighted, |e| e.data),
+            closeness_centrality(&g)
+        );
+
+        // doubling every weight halves every closeness
+        let doubled = closeness_centrality_weighted(&weighted, |e| 2.0 * e.data);
+        for (n, c) in closeness_centrality(&g) {
+            assert_eq!(doubled[&n], c / 2.0);
+        }
+    }
+
+    #[test]
+    fn test_edge_betweenness() {
+        // on a path, each edge is on the paths between the nodes on either
+        // side of it
+        let path = graph_from_edges::<Undirected>(4, &[(0, 1), (1, 2), (2, 3)]);
+        let b = edge_betweenness(&path);
+        assert_eq!(b, HashMap::from([(0, 3.0), (1, 4.0), (2, 3.0)]));
+
+        // a square has two shortest paths between opposite corners
+        let square = graph_from_edges::<Undirected>(4, &[(0, 1), (1, 2), (2, 3), (3, 0)]);
+        for b in edge_betweenness(&square).into_values() {
+            assert_eq!(b, 2.0);
+        }
+
+        let g = graph_from_edges::<Directed>(3, &[(0, 1), (1, 2), (1, 2), (2, 2)]);
+        let b = edge_betweenness(&g);
+        assert_eq!(b, HashMap::from([(0, 2.0), (1, 1.0), (2, 1.0), (3, 0.0)]));
+    }
+}
@@ -0,0 +1,153 @@
+//! Finding cliques: sets of nodes that are all adjacent to each other.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::graph_base::{GraphType, GraphView, NodeInd};
+
+/// The neighbors of each node, ignoring edge direction, self-loops, and
+/// parallel edges.
+pub(crate) type AdjSets = HashMap<NodeInd, BTreeSet<NodeInd>>;
+
+/// Gets the neighbors of each node, ignoring edge direction, self-loops, and
+/// parallel edges.
+pub(crate) fn adjacency_sets<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> AdjSets {
+    graph
+        .nodes()
+        .map(|n| {
+            let nbrs = graph
+                .edges_at(&n)
+                .map(|e| e.opposite(&n))
+                .filter(|m| *m != n)
+                .collect();
+            (n, nbrs)
+        })
+        .collect()
+}
+
+/// Runs the Bron-Kerbosch algorithm with pivoting to find the maximal cliques
+/// that contain all of `r`, some of `p`, and none of `x`. At each step,
+/// `report` is called with the current clique `r`, the size of `p`, and whether
+/// `x` is empty: `r` is a maximal clique when `p` and `x` are both empty. The
+/// branch is cut off if `report` returns false.
+fn bron_kerbosch<F: FnMut(&[NodeInd], usize, bool) -> bool>(
+    adj: &AdjSets,
+    r: &mut Vec<NodeInd>,
+    mut p: BTreeSet<NodeInd>,
+    mut x: BTreeSet<NodeInd>,
+    report: &mut F,
+) {
+    if !report(r, p.len(), x.is_empty()) || p.is_empty() {
+        return;
+    }
+    // the pivot with the most neighbors in p leaves the fewest branches
+    let pivot = p
+        .iter()
+        .chain(&x)
+        .max_by_key(|u| adj[u].intersection(&p).count())
+        .copied()
+        .unwrap();
+    let candidates: Vec<NodeInd> = p.difference(&adj[&pivot]).copied().collect();
+    for v in candidates {
+        r.push(v);
+        let nbrs = &adj[&v];
+        bron_kerbosch(
+            adj,
+            r,
+            p.intersection(nbrs).copied().collect(),
+            x.intersection(nbrs).copied().collect(),
+            report,
+        );
+        r.pop();
+        p.remove(&v);
+        x.insert(v);
+    }
+}
+
+/// Finds a largest clique given the adjacency sets.
+pub(crate) fn max_clique_of(adj: &AdjSets) -> Vec<NodeInd> {
+    let mut best: Vec<NodeInd> = vec![];
+    let mut report = |r: &[NodeInd], p_len: usize, _| {
+        if r.len() + p_len <= best.len() {
+            return false;
+        }
+        if p_len == 0 {
+            best = r.to_vec();
+        }
+        true
+    };
+    bron_kerbosch(
+        adj,
+        &mut vec![],
+        adj.keys().copied().collect(),
+        BTreeSet::new(),
+        &mut report,
+    );
+    best.sort_unstable();
+    best
+}
+
+/// Gets every maximal clique: every clique that isn't contained in a larger
+/// one. Edge direction is ignored. Each clique's nodes are in ascending order,
+/// and the cliques are sorted. There can be exponentially many maximal cliques.
+pub fn maximal_cliques<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+) -> Vec<Vec<NodeInd>> {
+    let adj = adjacency_sets(graph);
+    let mut cliques = vec![];
+    let mut report = |r: &[NodeInd], p_len: usize, x_empty: bool| {
+        if p_len == 0 && x_empty && !r.is_empty() {
+            let mut clique = r.to_vec();
+            clique.sort_unstable();
+            cliques.push(clique);
+        }
+        true
+    };
+    bron_kerbosch(
+        &adj,
+        &mut vec![],
+        adj.keys().copied().collect(),
+        BTreeSet::new(),
+        &mut report,
+    );
+    cliques.sort();
+    cliques
+}
+
+/// Finds a largest clique, with its nodes in ascending order, using
+/// Bron-Kerbosch with pruning of branches that can't beat the best clique so
+/// far. Edge direction is ignored. This takes exponential time in the worst
+/// case.
+pub fn max_clique<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> Vec<NodeInd> {
+    max_clique_of(&adjacency_sets(graph))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::Undirected;
+    use crate::test_util::graph_from_edges;
+
+    #[test]
+    fn test_cliques() {
+        // a 4-clique with a triangle hanging off it, and an isolated node
+        let g = graph_from_edges::<Undirected>(
+            7,
+            &[
+                (0, 1),
+                (0, 2),
+                (0, 3),
+                (1, 2),
+                (1, 3),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 3),
+            ],
+        );
+        assert_eq!(max_clique(&g), vec![0, 1, 2, 3]);
+        assert_eq!(
+            maximal_cliques(&g),
+            vec![vec![0, 1, 2, 3], vec![3, 4, 5], vec![6]]
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Graph coloring: assigning labels to nodes so no two adjacent nodes match.
+
+use std::collections::HashMap;
+
+use crate::graph_base::{GraphType, GraphView, NodeInd};
+use crate::properties::has_self_loops;
+
+/// The most nodes [`chromatic_number`] and [`is_k_colorable`] accept, since
+/// their running time is exponential in the number of nodes.
+pub const MAX_EXACT_COLORING_NODES: usize = 64;
+
+/// Gets each node's neighbors as positions in a list of nodes sorted by
+/// decreasing degree, ignoring edge direction and parallel edges. Coloring
+/// high-degree nodes first makes the search fail faster.
+fn coloring_order<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> Vec<Vec<usize>> {
+    let mut nodes = graph.nodes_sorted();
+    assert!(
+        nodes.len() <= MAX_EXACT_COLORING_NODES,
+        "exact coloring is only supported for graphs with at most {} nodes",
+        MAX_EXACT_COLORING_NODES
+    );
+    nodes.sort_by_key(|n| std::cmp::Reverse(graph.edges_at(n).count()));
+    let pos: HashMap<NodeInd, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    nodes
+        .iter()
+        .map(|n| {
+            let mut nbrs: Vec<usize> = graph.edges_at(n).map(|e| pos[&e.opposite(n)]).collect();
+            nbrs.sort_unstable();
+            nbrs.dedup();
+            nbrs
+        })
+        .collect()
+}
+
+/// Tries to color the nodes from position `i` onwards with `k` colors, given
+/// the colors of the nodes before them. `num_used` is the number of distinct
+/// colors used so far: trying more than one new color at a time would just give
+/// the same coloring with the colors renamed.
+fn extend_coloring(adj: &[Vec<usize>], colors: &mut Vec<usize>, k: usize, num_used: usize) -> bool {
+    let i = colors.len();
+    if i == adj.len() {
+        return true;
+    }
+    for c in 0..k.min(num_used + 1) {
+        if adj[i].iter().any(|&j| j < i && colors[j] == c) {
+            continue;
+        }
+        colors.push(c);
+        if extend_coloring(adj, colors, k, num_used.max(c + 1)) {
+            return true;
+        }
+        colors.pop();
+    }
+    false
+}
+
+/// Whether the nodes can be colored with at most `k` colors so that no edge
+/// connects two nodes of the same color. Edge direction is ignored, and graphs
+/// with self-loops can't be colored at all.
+///
+/// This uses backtracking search, which takes exponential time in the worst
+/// case, so it panics for graphs with more than [`MAX_EXACT_COLORING_NODES`]
+/// nodes.
+pub fn is_k_colorable<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G, k: usize) -> bool {
+    if has_self_loops(graph) {
+        return false;
+    }
+    let adj = coloring_order(graph);
+    extend_coloring(&adj, &mut Vec::with_capacity(adj.len()), k, 0)
+}
+
+/// Gets the chromatic number: the fewest colors needed to color the nodes such
+/// that no edge connects two nodes of the same color. Edge direction is
+/// ignored. Graphs with at least one node need at least one color, and graphs
+/// with no nodes need none.
+///
+/// This tries [`is_k_colorable`] with increasing `k`, so like it, it takes
+/// exponential time and panics for graphs with more than
+/// [`MAX_EXACT_COLORING_NODES`] nodes. It also panics for graphs with
+/// self-loops, which can't be colored.
+pub fn chromatic_number<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> usize {
+    assert!(
+        !has_self_loops(graph),
+        "graphs with self-loops can't be colored"
+    );
+    let adj = coloring_order(graph);
+    (0..=adj.len())
+        .find(|&k| extend_coloring(&adj, &mut Vec::with_capacity(adj.len()), k, 0))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::Undirected;
+    use crate::test_util::graph_from_edges;
+
+    #[test]
+    fn test_chromatic_number() {
+        let triangle = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(chromatic_number(&triangle), 3);
+        assert!(!is_k_colorable(&triangle, 2));
+
+        // the complete bipartite graph K_{3, 3}
+        let mut edges = vec![];
+        for i in 0..3 {
+            for j in 3..6 {
+                edges.push((i, j));
+            }
+        }
+        let bipartite = graph_from_edges::<Undirected>(6, &edges);
+        assert_eq!(chromatic_number(&bipartite), 2);
+
+        let empty = graph_from_edges::<Undirected>(4, &[]);
+        assert_eq!(chromatic_number(&empty), 1);
+
+        // odd cycles need 3 colors, and the Petersen graph does too
+        let mut petersen: Vec<(NodeInd, NodeInd)> = (0..5).map(|i| (i, (i + 1) % 5)).collect();
+        petersen.extend((0..5).map(|i| (i, i + 5)));
+        petersen.extend((0..5).map(|i| (i + 5, (i + 2) % 5 + 5)));
+        let g = graph_from_edges::<Undirected>(10, &petersen);
+        assert_eq!(chromatic_number(&g), 3);
+    }
+}
@@ -0,0 +1,604 @@
+//! Algorithms for finding and working with communities: groups of nodes that
+//! are more connected to each other than to the rest of the graph.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::ops::AddAssign;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::adj_list_graph::ALGraph;
+use crate::centrality::edge_betweenness;
+use crate::graph_base::{Edge, GraphBase, GraphView, NodeInd, Undirected};
+use crate::matrices::laplacian_matrix;
+use crate::traversal::reachable_from;
+
+/// Coarsens an undirected graph by merging every group of nodes with the same
+/// community id into a single super-node, whose data is the original nodes in
+/// ascending order. There's one edge between every pair of super-nodes that
+/// had edges between them, whose data is how many edges there were, and edges
+/// inside a community become a self-loop counting them the same way. The
+/// super-nodes are in ascending order of community id. This is the step
+/// between levels of multilevel algorithms like [`louvain`].
+pub fn coarsen<N, E, G: GraphView<N, E, Undirected>, F: Fn(NodeInd) -> usize>(
+    graph: &G,
+    community: F,
+) -> ALGraph<Vec<NodeInd>, usize, Undirected> {
+    coarsen_with(graph, community, |_| 1)
+}
+
+/// Like [`coarsen`], but the data of each coarse edge is the sum of `weight`
+/// over the edges it replaces instead of how many there were.
+fn coarsen_with<N, E, G, F, W, S>(
+    graph: &G,
+    community: F,
+    weight: W,
+) -> ALGraph<Vec<NodeInd>, S, Undirected>
+where
+    G: GraphView<N, E, Undirected>,
+    F: Fn(NodeInd) -> usize,
+    W: Fn(&Edge<E>) -> S,
+    S: AddAssign + Default + Clone,
+{
+    let mut members: BTreeMap<usize, Vec<NodeInd>> = BTreeMap::new();
+    for n in graph.nodes_sorted() {
+        members.entry(community(n)).or_default().push(n);
+    }
+
+    let mut coarse = ALGraph::new();
+    let mut super_node: HashMap<usize, NodeInd> = HashMap::new();
+    for (id, nodes) in members {
+        super_node.insert(id, coarse.add_node(nodes));
+    }
+
+    let mut totals: BTreeMap<(NodeInd, NodeInd), S> = BTreeMap::new();
+    for e in graph.edges_sorted() {
+        let edge = graph.edge(&e);
+        let (a, b) = (
+            super_node[&community(edge.start)],
+            super_node[&community(edge.end)],
+        );
+        *totals.entry((a.min(b), a.max(b))).or_default() += weight(edge);
+    }
+    for ((a, b), total) in totals {
+        coarse.add_edge(&a, &b, total);
+    }
+    coarse
+}
+
+/// Gets the modularity of a partition of an undirected graph into
+/// communities: the fraction of edges inside communities, minus the fraction
+/// expected if edges were placed at random between nodes with the same
+/// degrees. This ranges from -1/2 to 1, and higher is better. Nodes missing
+/// from `communities` are each in a community of their own. Graphs with no
+/// edges have a modularity of 0.
+pub fn modularity<N, E, G: GraphView<N, E, Undirected>>(
+    graph: &G,
+    communities: &HashMap<NodeInd, usize>,
+) -> f64 {
+    modularity_weighted(graph, communities, |_| 1.0)
+}
+
+/// Like [`modularity`], but with each edge counting for its weight instead of
+/// 1, as in a multigraph. Weights must be non-negative.
+pub fn modularity_weighted<N, E, G: GraphView<N, E, Undirected>, F>(
+    graph: &G,
+    communities: &HashMap<NodeInd, usize>,
+    weight: F,
+) -> f64
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    // nodes without a community are keyed by their own index instead
+    let key = |n: NodeInd| communities.get(&n).map_or(Err(n), |&c| Ok(c));
+    let mut inside: HashMap<Result<usize, NodeInd>, f64> = HashMap::new();
+    let mut degree: HashMap<Result<usize, NodeInd>, f64> = HashMap::new();
+    let mut m = 0.0;
+    for e in graph.edges() {
+        let edge = graph.edge(&e);
+        let w = weight(edge);
+        let (a, b) = (key(edge.start), key(edge.end));
+        if a == b {
+            *inside.entry(a).or_default() += w;
+        }
+        *degree.entry(a).or_default() += w;
+        *degree.entry(b).or_default() += w;
+        m += w;
+    }
+    if m == 0.0 {
+        return 0.0;
+    }
+
+    let inside: f64 = inside.values().sum();
+    let expected: f64 = degree.values().map(|d| (d / (2.0 * m)).powi(2)).sum();
+    inside / m - expected
+}
+
+/// Finds communities in an undirected graph with the Louvain method, which
+/// greedily optimizes [`modularity`]. Each level moves nodes one at
+/// a time into whichever neighboring community raises the modularity the
+/// most, until no move helps, and then [coarsens](coarsen) the graph so that
+/// each community is a single node for the next level. This stops when a level
+/// doesn't move anything. Higher `resolution` favors smaller communities, and
+/// 1 gives the usual modularity.
+///
+/// Returns the community of each node. Communities are numbered from 0 in
+/// order of their smallest node, and nodes are visited in ascending order of
+/// index, so the result is the same every time.
+pub fn louvain<N, E, G: GraphView<N, E, Undirected>>(
+    graph: &G,
+    resolution: f64,
+) -> HashMap<NodeInd, usize> {
+    // the node each original node has been merged into at the current level,
+    // which coarsening gives indices in ascending order
+    let mut merged: HashMap<NodeInd, NodeInd> = graph
+        .nodes_sorted()
+        .into_iter()
+        .enumerate()
+        .map(|(i, n)| (n, i))
+        .collect();
+    let mut level = coarsen_with(graph, |n| n, |_| 1.0);
+    while let Some(community) = louvain_level(&level, resolution) {
+        for m in merged.values_mut() {
+            *m = community[m];
+        }
+        level = coarsen_with(&level, |n| community[&n], |e| e.data);
+    }
+
+    number_communities(graph, &merged)
+}
+
+/// Renumbers communities given by any labels from 0, in order of their
+/// smallest node.
+fn number_communities<N, E, G: GraphView<N, E, Undirected>, L: Hash + Eq + Copy>(
+    graph: &G,
+    labels: &HashMap<NodeInd, L>,
+) -> HashMap<NodeInd, usize> {
+    let mut ids: HashMap<L, usize> = HashMap::new();
+    for n in graph.nodes_sorted() {
+        let next = ids.len();
+        ids.entry(labels[&n]).or_insert(next);
+    }
+    labels.iter().map(|(n, l)| (*n, ids[l])).collect()
+}
+
+/// Splits an undirected graph into at least `k` communities with the
+/// Girvan-Newman algorithm: the edge with the highest
+/// [betweenness](edge_betweenness) is removed, breaking ties by lowest index,
+/// and the betweenness is recomputed, until the graph falls apart into `k`
+/// connected components. Edges between communities carry the shortest paths
+/// from one to the other, so they're the ones that go first. If the graph
+/// already has `k` or more components, those are returned. Each community is
+/// in ascending order, and they're in order of their smallest node.
+///
+/// This recomputes the betweenness after every removal, so it takes O(VE²)
+/// time, and is only practical for small graphs.
+pub fn girvan_newman<N, E, G: GraphBase<N, E, Undirected> + Clone>(
+    graph: &G,
+    k: usize,
+) -> Vec<Vec<NodeInd>> {
+    let mut g = graph.clone();
+    loop {
+        let components = connected_components(&g);
+        if components.len() >= k {
+            return components;
+        }
+        let betweenness = edge_betweenness(&g);
+        let Some((&e, _)) = betweenness
+            .iter()
+            .max_by(|(e1, b1), (e2, b2)| b1.total_cmp(b2).then(e2.cmp(e1)))
+        else {
+            return components;
+        };
+        g.remove_edge(&e);
+    }
+}
+
+/// Gets the connected components of an undirected graph, each in ascending
+/// order, in order of their smallest node.
+fn connected_components<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> Vec<Vec<NodeInd>> {
+    let mut seen: HashSet<NodeInd> = HashSet::new();
+    let mut components = vec![];
+    for n in graph.nodes_sorted() {
+        if seen.contains(&n) {
+            continue;
+        }
+        let mut component: Vec<NodeInd> = reachable_from(graph, &n).into_iter().collect();
+        component.sort_unstable();
+        seen.extend(&component);
+        components.push(component);
+    }
+    components
+}
+
+/// Finds communities in an undirected graph by label propagation. Every node
+/// starts with its own label, and then in each iteration the nodes, in random
+/// order, adopt whichever label is most common among their neighbors, breaking
+/// ties at random but keeping their current label if it's one of the most
+/// common. Parallel edges count a neighbor more than once, and self-loops are
+/// ignored. This stops when an iteration changes nothing or after `max_iters`
+/// iterations.
+///
+/// This takes O(E) time per iteration, so it's much faster than [`louvain`],
+/// but the result depends on `rng` and doesn't optimize anything in
+/// particular. Communities are numbered from 0 in order of their smallest node.
+pub fn label_propagation<N, E, G: GraphView<N, E, Undirected>, R: Rng + ?Sized>(
+    graph: &G,
+    rng: &mut R,
+    max_iters: usize,
+) -> HashMap<NodeInd, usize> {
+    let mut nodes = graph.nodes_sorted();
+    let mut labels: HashMap<NodeInd, NodeInd> = nodes.iter().map(|n| (*n, *n)).collect();
+    for _ in 0..max_iters {
+        nodes.shuffle(rng);
+        let mut changed = false;
+        for n in &nodes {
+            let mut counts: BTreeMap<NodeInd, usize> = BTreeMap::new();
+            for other in graph.neighbors(n) {
+                if other != *n {
+                    *counts.entry(labels[&other]).or_default() += 1;
+                }
+            }
+            let Some(&most) = counts.values().max() else {
+                continue;
+            };
+            let best: Vec<NodeInd> = counts
+                .into_iter()
+                .filter(|(_, c)| *c == most)
+                .map(|(l, _)| l)
+                .collect();
+            if !best.contains(&labels[n]) {
+                labels.insert(*n, *best.choose(rng).unwrap());
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    number_communities(graph, &labels)
+}
+
+/// Runs one level of [`louvain`], returning the community of each node, or
+/// `None` if no node moved. Communities are numbered from 0, in the order that
+/// [`coarsen`] will put them in.
+fn louvain_level<N>(
+    graph: &ALGraph<N, f64, Undirected>,
+    resolution: f64,
+) -> Option<HashMap<NodeInd, usize>> {
+    let nodes = graph.nodes_sorted();
+    // self-loops count twice towards the degree
+    let degree: HashMap<NodeInd, f64> = nodes
+        .iter()
+        .map(|n| (*n, graph.edges_from(n).map(|e| e.data).sum()))
+        .collect();
+    let two_m: f64 = degree.values().sum();
+    if two_m == 0.0 {
+        return None;
+    }
+
+    let mut community: HashMap<NodeInd, NodeInd> = nodes.iter().map(|n| (*n, *n)).collect();
+    // the total degree of each community
+    let mut total = degree.clone();
+    let mut moved_any = false;
+    loop {
+        let mut moved = false;
+        for n in &nodes {
+            let k = degree[n];
+            let mut links: BTreeMap<NodeInd, f64> = BTreeMap::new();
+            for edge in graph.edges_from(n) {
+                let other = edge.opposite(n);
+                if other != *n {
+                    *links.entry(community[&other]).or_default() += edge.data;
+                }
+            }
+
+            let old = community[n];
+            *total.get_mut(&old).unwrap() -= k;
+            let gain = |c: NodeInd, link: f64| link - resolution * total[&c] * k / two_m;
+            let mut best = (old, gain(old, links.get(&old).copied().unwrap_or(0.0)));
+            for (&c, &link) in &links {
+                let g = gain(c, link);
+                if g > best.1 {
+                    best = (c, g);
+                }
+            }
+            *total.get_mut(&best.0).unwrap() += k;
+            if best.0 != old {
+                community.insert(*n, best.0);
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+        moved_any = true;
+    }
+    if !moved_any {
+        return None;
+    }
+
+    let mut ids: Vec<NodeInd> = community.values().copied().collect();
+    ids.sort_unstable();
+    ids.dedup();
+    Some(
+        community
+            .into_iter()
+            .map(|(n, c)| (n, ids.binary_search(&c).unwrap()))
+            .collect(),
+    )
+}
+
+/// The most power iterations [`spectral_bisection`] runs.
+const SPECTRAL_MAX_ITERS: usize = 10_000;
+
+/// How close successive iterates in [`spectral_bisection`] have to be to stop.
+const SPECTRAL_TOLERANCE: f64 = 1e-10;
+
+/// Splits an undirected graph in two by the signs of its Fiedler vector, the
+/// eigenvector of the [Laplacian](laplacian_matrix) with the second-smallest
+/// eigenvalue. Nodes that are well connected to each other get similar values
+/// in it, so this tends to cut few edges while keeping the halves balanced.
+/// The first half has the node with the smallest index, and both halves are in
+/// ascending order. Graphs with fewer than two nodes are all in the first half.
+///
+/// The eigenvector is found by power iteration on cI - L, where c is at least
+/// the largest eigenvalue of L, with the all-ones vector projected out each
+/// step, so this converges slowly when the two smallest nonzero eigenvalues are
+/// close. For disconnected graphs, the Fiedler vector isn't unique, so the
+/// split is some combination of the components.
+pub fn spectral_bisection<N, E, G: GraphView<N, E, Undirected>>(
+    graph: &G,
+) -> (Vec<NodeInd>, Vec<NodeInd>) {
+    let nodes = graph.nodes_sorted();
+    let n = nodes.len();
+    if n < 2 {
+        return (nodes, vec![]);
+    }
+    let laplacian = laplacian_matrix(graph);
+    // every eigenvalue of the Laplacian is at most twice the largest degree
+    let c = 2.0 * (0..n).map(|i| laplacian[i][i]).fold(0.0, f64::max) + 1.0;
+
+    let deflate = |x: &mut Vec<f64>| {
+        let mean = x.iter().sum::<f64>() / n as f64;
+        x.iter_mut().for_each(|v| *v -= mean);
+        let norm = x.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if norm > 0.0 {
+            x.iter_mut().for_each(|v| *v /= norm);
+        }
+    };
+    // any start that isn't orthogonal to the Fiedler vector works
+    let mut x: Vec<f64> = (0..n).map(|i| ((i + 1) as f64).sqrt()).collect();
+    deflate(&mut x);
+    for _ in 0..SPECTRAL_MAX_ITERS {
+        let mut next: Vec<f64> = (0..n)
+            .map(|i| c * x[i] - (0..n).map(|j| laplacian[i][j] * x[j]).sum::<f64>())
+            .collect();
+        deflate(&mut next);
+        let diff: f64 = next.iter().zip(&x).map(|(a, b)| (a - b).abs()).sum();
+        x = next;
+        if diff < SPECTRAL_TOLERANCE {
+            break;
+        }
+    }
+
+    if x[0] < 0.0 {
+        x.iter_mut().for_each(|v| *v = -*v);
+    }
+    let mut first = vec![];
+    let mut second = vec![];
+    for (node, v) in nodes.into_iter().zip(x) {
+        if v >= 0.0 {
+            first.push(node);
+        } else {
+            second.push(node);
+        }
+    }
+    (first, second)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{graph_from_edges, weighted_graph_from_edges};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_coarsen_one_community() {
+        let g = graph_from_edges::<Undirected>(4, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 3)]);
+        let coarse = coarsen(&g, |_| 7);
+        assert_eq!(coarse.nodes().count(), 1);
+        let n = coarse.nodes().next().unwrap();
+        assert_eq!(coarse.node(&n), &vec![0, 1, 2, 3]);
+        let edges: Vec<_> = coarse.edge_references().collect();
+        assert_eq!(edges, vec![(n, n, &5)]);
+    }
+
+    #[test]
+    fn test_coarsen_two_communities() {
+        // two triangles joined by two edges
+        let g = graph_from_edges::<Undirected>(
+            6,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (3, 4),
+                (4, 5),
+                (5, 3),
+                (2, 3),
+                (0, 5),
+            ],
+        );
+        let coarse = coarsen(&g, |n| if n < 3 { 1 } else { 0 });
+        assert_eq!(coarse.node(&0), &vec![3, 4, 5]);
+        assert_eq!(coarse.node(&1), &vec![0, 1, 2]);
+        let mut edges: Vec<(NodeInd, NodeInd, usize)> = coarse
+            .edge_references()
+            .map(|(a, b, c)| (a, b, *c))
+            .collect();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![(0, 0, 3), (0, 1, 2), (1, 1, 3)]);
+    }
+
+    #[test]
+    fn test_louvain_two_cliques() {
+        // two 5-cliques joined by a single edge
+        let mut edges = vec![(4, 5)];
+        for offset in [0, 5] {
+            for a in 0..5 {
+                for b in a + 1..5 {
+                    edges.push((a + offset, b + offset));
+                }
+            }
+        }
+        let g = graph_from_edges::<Undirected>(10, &edges);
+        let communities = louvain(&g, 1.0);
+        let expected: HashMap<NodeInd, usize> = (0..10).map(|n| (n, n / 5)).collect();
+        assert_eq!(communities, expected);
+    }
+
+    #[test]
+    fn test_louvain_no_edges() {
+        let g = graph_from_edges::<Undirected>(3, &[]);
+        let communities = louvain(&g, 1.0);
+        assert_eq!(communities, HashMap::from([(0, 0), (1, 1), (2, 2)]));
+    }
+
+    #[test]
+    fn test_louvain_ring_of_cliques() {
+        // six 4-cliques in a ring, after an extra node that gets removed so the
+        // indices don't start at 0
+        let mut edges = vec![];
+        for c in 0..6 {
+            let offset = 1 + 4 * c;
+            for a in 0..4 {
+                for b in a + 1..4 {
+                    edges.push((a + offset, b + offset));
+                }
+            }
+            edges.push((offset + 3, 1 + (4 * c + 4) % 24));
+        }
+        let mut g = graph_from_edges::<Undirected>(25, &edges);
+        g.remove_node(&0);
+        let communities = louvain(&g, 1.0);
+        let expected: HashMap<NodeInd, usize> = (1..25).map(|n| (n, (n - 1) / 4)).collect();
+        assert_eq!(communities, expected);
+    }
+
+    #[test]
+    fn test_modularity() {
+        // two triangles joined by a single edge
+        let g = graph_from_edges::<Undirected>(
+            6,
+            &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)],
+        );
+        let split: HashMap<NodeInd, usize> = (0..6).map(|n| (n, n / 3)).collect();
+        let mixed: HashMap<NodeInd, usize> = (0..6).map(|n| (n, n % 2)).collect();
+        // 6 of 7 edges inside, and each side has half of the degree
+        assert!((modularity(&g, &split) - (6.0 / 7.0 - 0.5)).abs() < 1e-12);
+        assert!(modularity(&g, &split) > modularity(&g, &mixed));
+
+        let together: HashMap<NodeInd, usize> = (0..6).map(|n| (n, 0)).collect();
+        assert!(modularity(&g, &together).abs() < 1e-12);
+        let alone: HashMap<NodeInd, usize> = (0..6).map(|n| (n, n)).collect();
+        assert!((modularity(&g, &HashMap::new()) - modularity(&g, &alone)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_modularity_weighted() {
+        // a heavy edge inside each community and a light one between them
+        let g =
+            weighted_graph_from_edges::<Undirected>(4, &[(0, 1, 3.0), (2, 3, 3.0), (1, 2, 2.0)]);
+        let split = HashMap::from([(0, 0), (1, 0), (2, 1), (3, 1)]);
+        let q = modularity_weighted(&g, &split, |e| e.data);
+        assert!((q - (6.0 / 8.0 - 0.5)).abs() < 1e-12);
+        assert!(q > modularity(&g, &split));
+    }
+
+    #[test]
+    fn test_label_propagation_two_clusters() {
+        // two 6-cliques joined by two edges
+        let mut edges = vec![(0, 6), (5, 11)];
+        for offset in [0, 6] {
+            for a in 0..6 {
+                for b in a + 1..6 {
+                    edges.push((a + offset, b + offset));
+                }
+            }
+        }
+        let g = graph_from_edges::<Undirected>(12, &edges);
+        let expected: HashMap<NodeInd, usize> = (0..12).map(|n| (n, n / 6)).collect();
+        for seed in 0..10 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            assert_eq!(label_propagation(&g, &mut rng, 100), expected);
+        }
+    }
+
+    #[test]
+    fn test_label_propagation_no_iters() {
+        let g = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2)]);
+        let mut rng = StdRng::seed_from_u64(0);
+        let communities = label_propagation(&g, &mut rng, 0);
+        assert_eq!(communities, HashMap::from([(0, 0), (1, 1), (2, 2)]));
+    }
+
+    #[test]
+    fn test_spectral_bisection() {
+        // two 5-cliques joined by a path of two edges through node 10
+        let mut edges = vec![(4, 10), (10, 5)];
+        for offset in [0, 5] {
+            for a in 0..5 {
+                for b in a + 1..5 {
+                    edges.push((a + offset, b + offset));
+                }
+            }
+        }
+        let g = graph_from_edges::<Undirected>(11, &edges);
+        let (first, second) = spectral_bisection(&g);
+        // the bridge node could go either way
+        let first: Vec<NodeInd> = first.into_iter().filter(|&n| n != 10).collect();
+        let second: Vec<NodeInd> = second.into_iter().filter(|&n| n != 10).collect();
+        assert_eq!(first, vec![0, 1, 2, 3, 4]);
+        assert_eq!(second, vec![5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_spectral_bisection_path() {
+        let g = graph_from_edges::<Undirected>(6, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]);
+        assert_eq!(spectral_bisection(&g), (vec![0, 1, 2], vec![3, 4, 5]));
+
+        let single = graph_from_edges::<Undirected>(1, &[]);
+        assert_eq!(spectral_bisection(&single), (vec![0], vec![]));
+    }
+
+    #[test]
+    fn test_girvan_newman_barbell() {
+        // two 4-cliques joined by the edge (3, 4), which is added last
+        let mut edges = vec![];
+        for offset in [0, 4] {
+            for a in 0..4 {
+                for b in a + 1..4 {
+                    edges.push((a + offset, b + offset));
+                }
+            }
+        }
+        edges.push((3, 4));
+        let g = graph_from_edges::<Undirected>(8, &edges);
+
+        // the bridge is the first edge to go
+        let betweenness = edge_betweenness(&g);
+        let bridge = betweenness
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(e, _)| *e);
+        assert_eq!(bridge, Some(12));
+
+        let split = girvan_newman(&g, 2);
+        assert_eq!(split, vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]]);
+        assert_eq!(girvan_newman(&g, 1), vec![(0..8).collect::<Vec<_>>()]);
+        assert_eq!(girvan_newman(&g, 8).len(), 8);
+    }
+}
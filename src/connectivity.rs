@@ -0,0 +1,339 @@
+//! Algorithms dealing with how well-connected graphs are.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::flow::{max_flow, FlowNetwork};
+use crate::graph_base::{EdgeInd, GraphType, GraphView, NodeInd, Undirected};
+
+/// A node on the DFS stack in [`bridges`].
+struct BridgeFrame {
+    node: NodeInd,
+    parent_edge: Option<EdgeInd>,
+    nbrs: Vec<(NodeInd, EdgeInd)>,
+    next: usize,
+}
+
+/// Finds the bridges of an undirected graph: the edges whose removal would
+/// disconnect their endpoints. This uses Tarjan's DFS-based algorithm, so it's
+/// linear time. One of a pair of parallel edges is never a bridge, and neither
+/// are self-loops. Returns the edges in ascending order of index.
+pub fn bridges<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> Vec<EdgeInd> {
+    // discovery time of each node, and the earliest discovery time reachable
+    // from its DFS subtree using at most one back edge
+    let mut disc: HashMap<NodeInd, usize> = HashMap::new();
+    let mut low: HashMap<NodeInd, usize> = HashMap::new();
+    let mut found = vec![];
+
+    for root in graph.nodes_sorted() {
+        if disc.contains_key(&root) {
+            continue;
+        }
+        disc.insert(root, disc.len());
+        low.insert(root, disc[&root]);
+        let mut stack = vec![BridgeFrame {
+            node: root,
+            parent_edge: None,
+            nbrs: graph.neighbors_with_edges(&root).collect(),
+            next: 0,
+        }];
+
+        while let Some(frame) = stack.last_mut() {
+            let n = frame.node;
+            if let Some(&(next, e)) = frame.nbrs.get(frame.next) {
+                frame.next += 1;
+                if Some(e) == frame.parent_edge {
+                    continue;
+                }
+                match disc.get(&next) {
+                    Some(&d) => {
+                        let l = low[&n].min(d);
+                        low.insert(n, l);
+                    }
+                    None => {
+                        disc.insert(next, disc.len());
+                        low.insert(next, disc[&next]);
+                        stack.push(BridgeFrame {
+                            node: next,
+                            parent_edge: Some(e),
+                            nbrs: graph.neighbors_with_edges(&next).collect(),
+                            next: 0,
+                        });
+                    }
+                }
+            } else {
+                let parent_edge = frame.parent_edge;
+                stack.pop();
+                if let (Some(parent), Some(e)) = (stack.last(), parent_edge) {
+                    let p = parent.node;
+                    let l = low[&p].min(low[&n]);
+                    low.insert(p, l);
+                    if low[&n] > disc[&p] {
+                        found.push(e);
+                    }
+                }
+            }
+        }
+    }
+
+    found.sort_unstable();
+    found
+}
+
+/// Builds the flow network for counting edge-disjoint paths: each edge can be
+/// used once, in either direction.
+fn edge_network<N, E, G: GraphView<N, E, Undirected>>(
+    graph: &G,
+    pos: &HashMap<NodeInd, usize>,
+) -> FlowNetwork {
+    let mut network = FlowNetwork::new(pos.len());
+    for e in graph.edges() {
+        let (u, v) = graph.edge_endpoints(&e);
+        if u != v {
+            network.add_arc(pos[&u], pos[&v], 1.0);
+            network.add_arc(pos[&v], pos[&u], 1.0);
+        }
+    }
+    network
+}
+
+/// Gets the positions of the nodes in ascending order of index.
+fn node_positions<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> HashMap<NodeInd, usize> {
+    graph
+        .nodes_sorted()
+        .into_iter()
+        .enumerate()
+        .map(|(i, n)| (n, i))
+        .collect()
+}
+
+/// Gets the number of edges that have to be removed to disconnect `target`
+/// from `source`, which by Menger's theorem is the largest number of paths
+/// between them that don't share any edges. This is a maximum flow where each
+/// edge has capacity 1.
+pub fn local_edge_connectivity<N, E, G: GraphView<N, E, Undirected>>(
+    graph: &G,
+    source: &NodeInd,
+    target: &NodeInd,
+) -> usize {
+    let pos = node_positions(graph);
+    edge_network(graph, &pos).max_flow(pos[source], pos[target]) as usize
+}
+
+/// Gets as many paths from `source` to `target` as possible that don't share
+/// any edges, as lists of edges in order. There are as many as the local edge
+/// connectivity between them. This decomposes a maximum flow with unit
+/// capacities into paths. For directed graphs, the paths follow edge
+/// direction.
+pub fn edge_disjoint_paths<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    source: &NodeInd,
+    target: &NodeInd,
+) -> Vec<Vec<EdgeInd>> {
+    if source == target {
+        return vec![];
+    }
+    let (_, flows) = max_flow(graph, source, target, |e| {
+        if e.start == e.end {
+            0.0
+        } else {
+            1.0
+        }
+    });
+    // the edges carrying flow out of each node, in the direction of the flow
+    let mut out: HashMap<NodeInd, Vec<(NodeInd, EdgeInd)>> = HashMap::new();
+    let mut used: Vec<(EdgeInd, f64)> = flows.into_iter().filter(|(_, f)| *f != 0.0).collect();
+    used.sort_unstable_by_key(|(e, _)| Reverse(*e));
+    for (e, f) in used {
+        let (u, v) = graph.edge_endpoints(&e);
+        let (from, to) = if f > 0.0 { (u, v) } else { (v, u) };
+        out.entry(from).or_default().push((to, e));
+    }
+
+    let mut paths = vec![];
+    while out.get(source).is_some_and(|es| !es.is_empty()) {
+        let mut nodes = vec![*source];
+        let mut edges = vec![];
+        let mut curr = *source;
+        while &curr != target {
+            let (next, e) = out.get_mut(&curr).unwrap().pop().unwrap();
+            // flow can go around cycles that aren't needed to reach the
+            // target, so cut them out of the path
+            if let Some(i) = nodes.iter().position(|&n| n == next) {
+                nodes.truncate(i + 1);
+                edges.truncate(i);
+            } else {
+                nodes.push(next);
+                edges.push(e);
+            }
+            curr = next;
+        }
+        paths.push(edges);
+    }
+    paths
+}
+
+/// Gets the edge connectivity of an undirected graph: the smallest number of
+/// edges whose removal disconnects it. Any cut separates the lowest node from
+/// some other node, so this is the smallest local edge connectivity between
+/// the lowest node and the others. Graphs with fewer than two nodes have edge
+/// connectivity 0.
+pub fn edge_connectivity<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> usize {
+    let pos = node_positions(graph);
+    let nodes = graph.nodes_sorted();
+    let Some((first, rest)) = nodes.split_first() else {
+        return 0;
+    };
+    rest.iter()
+        .map(|n| edge_network(graph, &pos).max_flow(pos[first], pos[n]) as usize)
+        .min()
+        .unwrap_or(0)
+}
+
+/// Gets the smallest number of nodes, other than the two given ones, whose
+/// removal disconnects `target` from `source`, which must not be adjacent.
+/// Each node is split into an entrance and an exit joined by an arc of
+/// capacity 1, so the maximum flow counts paths that don't share any nodes.
+fn local_vertex_connectivity<N, E, G: GraphView<N, E, Undirected>>(
+    graph: &G,
+    pos: &HashMap<NodeInd, usize>,
+    source: &NodeInd,
+    target: &NodeInd,
+) -> usize {
+    let n = pos.len();
+    let (entrance, exit) = (|i: usize| 2 * i, |i: usize| 2 * i + 1);
+    let mut network = FlowNetwork::new(2 * n);
+    for i in 0..n {
+        network.add_arc(entrance(i), exit(i), 1.0);
+    }
+    for e in graph.edges() {
+        let (u, v) = graph.edge_endpoints(&e);
+        if u != v {
+            let (u, v) = (pos[&u], pos[&v]);
+            network.add_arc(exit(u), entrance(v), n as f64);
+            network.add_arc(exit(v), entrance(u), n as f64);
+        }
+    }
+    network.max_flow(exit(pos[source]), entrance(pos[target])) as usize
+}
+
+/// Gets the vertex connectivity of an undirected graph: the smallest number of
+/// nodes whose removal disconnects it or leaves a single node. For a complete
+/// graph on n nodes, this is n - 1, and otherwise it's the smallest local
+/// vertex connectivity between two nodes that aren't adjacent, found by
+/// maximum flow with node splitting. This takes a flow computation for every
+/// such pair, so it's best kept to small graphs.
+pub fn vertex_connectivity<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> usize {
+    let pos = node_positions(graph);
+    let nodes = graph.nodes_sorted();
+    let mut best = nodes.len().saturating_sub(1);
+    for (i, u) in nodes.iter().enumerate() {
+        let adjacent: HashSet<NodeInd> = graph.distinct_neighbors(u).collect();
+        for v in &nodes[i + 1..] {
+            if !adjacent.contains(v) {
+                best = best.min(local_vertex_connectivity(graph, &pos, u, v));
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::Directed;
+    use crate::test_util::graph_from_edges;
+
+    #[test]
+    fn test_bridges() {
+        // two triangles joined by a path, with a pendant edge and a doubled edge
+        let g = graph_from_edges::<Undirected>(
+            9,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 6),
+                (6, 4),
+                (6, 7),
+                (7, 8),
+                (7, 8),
+                (8, 8),
+            ],
+        );
+        assert_eq!(bridges(&g), vec![3, 4, 8]);
+    }
+
+    #[test]
+    fn test_connectivity() {
+        let cycle: Vec<(NodeInd, NodeInd)> = (0..6).map(|i| (i, (i + 1) % 6)).collect();
+        let g = graph_from_edges::<Undirected>(6, &cycle);
+        assert_eq!(vertex_connectivity(&g), 2);
+        assert_eq!(edge_connectivity(&g), 2);
+
+        for n in 2..7 {
+            let mut edges = vec![];
+            for u in 0..n {
+                for v in u + 1..n {
+                    edges.push((u, v));
+                }
+            }
+            let complete = graph_from_edges::<Undirected>(n, &edges);
+            assert_eq!(vertex_connectivity(&complete), n - 1);
+            assert_eq!(edge_connectivity(&complete), n - 1);
+        }
+
+        // two triangles sharing a node: one node disconnects it, but it takes
+        // two edges
+        let bowtie =
+            graph_from_edges::<Undirected>(5, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 2)]);
+        assert_eq!(vertex_connectivity(&bowtie), 1);
+        assert_eq!(edge_connectivity(&bowtie), 2);
+        assert_eq!(local_edge_connectivity(&bowtie, &0, &1), 2);
+
+        let disconnected = graph_from_edges::<Undirected>(4, &[(0, 1), (2, 3)]);
+        assert_eq!(vertex_connectivity(&disconnected), 0);
+        assert_eq!(edge_connectivity(&disconnected), 0);
+    }
+
+    #[test]
+    fn test_edge_disjoint_paths() {
+        let g = graph_from_edges::<Undirected>(
+            7,
+            &[
+                (0, 1),
+                (0, 2),
+                (0, 3),
+                (1, 4),
+                (2, 4),
+                (3, 5),
+                (4, 5),
+                (4, 6),
+                (5, 6),
+                (1, 2),
+                (6, 6),
+            ],
+        );
+        let paths = edge_disjoint_paths(&g, &0, &6);
+        assert_eq!(paths.len(), local_edge_connectivity(&g, &0, &6));
+        assert_eq!(paths.len(), 2);
+
+        let mut seen = HashSet::new();
+        for path in &paths {
+            let mut curr = 0;
+            for e in path {
+                assert!(seen.insert(*e));
+                curr = g.edge(e).opposite(&curr);
+            }
+            assert_eq!(curr, 6);
+        }
+
+        // directed paths have to follow the edges
+        let g = graph_from_edges::<Directed>(4, &[(0, 1), (1, 3), (2, 0), (3, 2), (0, 3)]);
+        assert_eq!(edge_disjoint_paths(&g, &0, &3), vec![vec![0, 1], vec![4]]);
+        assert!(edge_disjoint_paths(&g, &0, &0).is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+//! Vertex covers, independent sets, and related problems.
+
+use std::collections::HashSet;
+
+use crate::cliques::{adjacency_sets, max_clique_of, AdjSets};
+use crate::graph_base::{GraphType, GraphView, NodeInd};
+
+/// Finds a vertex cover, a set of nodes such that every edge has at least one
+/// endpoint in the set, using the classic 2-approximation: for each edge that
+/// isn't covered yet, add both of its endpoints. The chosen edges form a
+/// matching, and any cover needs one node from each of them, so the result is
+/// at most twice the size of a minimum cover. Returns the nodes in ascending
+/// order.
+pub fn vertex_cover_2approx<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+) -> Vec<NodeInd> {
+    let edges = graph.edges_sorted();
+
+    let mut cover: HashSet<NodeInd> = HashSet::new();
+    for e in edges {
+        let (start, end) = graph.edge_endpoints(&e);
+        if !cover.contains(&start) && !cover.contains(&end) {
+            cover.insert(start);
+            cover.insert(end);
+        }
+    }
+
+    let mut cover: Vec<NodeInd> = cover.into_iter().collect();
+    cover.sort_unstable();
+    cover
+}
+
+/// Gets the nodes adjacent to `n`, ignoring edge direction.
+fn adjacent<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G, n: &NodeInd) -> Vec<NodeInd> {
+    graph.edges_at(n).map(|e| e.opposite(n)).collect()
+}
+
+/// Finds a maximal independent set: a set of nodes, no two of which are
+/// adjacent, that can't have any other node added to it. This is not
+/// necessarily a maximum independent set, which is NP-hard to find. The nodes
+/// left out form a vertex cover, as in [`vertex_cover_2approx`]. Nodes are
+/// considered greedily from lowest to highest degree, because low-degree nodes
+/// rule out fewer others. Edge direction is ignored, and nodes with self-loops
+/// are never chosen. Returns the nodes in ascending order.
+pub fn maximal_independent_set<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+) -> Vec<NodeInd> {
+    let mut nodes: Vec<NodeInd> = graph.nodes().collect();
+    nodes.sort_unstable_by_key(|n| (graph.edges_at(n).count(), *n));
+
+    let mut blocked: HashSet<NodeInd> = HashSet::new();
+    let mut chosen = vec![];
+    for n in nodes {
+        if blocked.contains(&n) {
+            continue;
+        }
+        let nbrs = adjacent(graph, &n);
+        if nbrs.contains(&n) {
+            continue;
+        }
+        chosen.push(n);
+        blocked.insert(n);
+        blocked.extend(nbrs);
+    }
+
+    chosen.sort_unstable();
+    chosen
+}
+
+/// Finds a maximum independent set: a largest set of nodes, no two of which are
+/// adjacent. This is a maximum clique of the complement graph, so it's found
+/// with the same Bron-Kerbosch search as [`max_clique`](crate::cliques::max_clique),
+/// and takes exponential time in the worst case: use
+/// [`maximal_independent_set`] for large graphs. Edge direction is ignored,
+/// and nodes with self-loops are never chosen. Returns the nodes in ascending
+/// order.
+pub fn max_independent_set<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> Vec<NodeInd> {
+    let adj = adjacency_sets(graph);
+    let looped: HashSet<NodeInd> = graph
+        .edges()
+        .map(|e| graph.edge_endpoints(&e))
+        .filter(|(start, end)| start == end)
+        .map(|(start, _)| start)
+        .collect();
+    let complement: AdjSets = adj
+        .iter()
+        .filter(|(n, _)| !looped.contains(n))
+        .map(|(n, nbrs)| {
+            let non_nbrs = adj
+                .keys()
+                .filter(|m| *m != n && !nbrs.contains(m) && !looped.contains(m))
+                .copied()
+                .collect();
+            (*n, non_nbrs)
+        })
+        .collect();
+    max_clique_of(&complement)
+}
+
+/// Finds a small dominating set, a set of nodes such that every node is either
+/// in it or is a neighbor of a node in it, by repeatedly choosing the node that
+/// dominates the most nodes that aren't dominated yet. This is within a
+/// logarithmic factor of the minimum. Ties go to the lowest node index. For
+/// directed graphs, a node dominates the nodes its edges go to. Returns the
+/// nodes in ascending order.
+pub fn dominating_set_greedy<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+) -> Vec<NodeInd> {
+    let mut nodes: Vec<NodeInd> = graph.nodes().collect();
+    nodes.sort_unstable();
+    let closed_nbrs: Vec<HashSet<NodeInd>> = nodes
+        .iter()
+        .map(|n| graph.neighbors(n).chain([*n]).collect())
+        .collect();
+
+    let mut undominated: HashSet<NodeInd> = nodes.iter().copied().collect();
+    let mut chosen = vec![];
+    while !undominated.is_empty() {
+        // max_by_key takes the last maximum, so go in reverse for the lowest index
+        let (best, _) = closed_nbrs
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, nbrs)| nbrs.intersection(&undominated).count())
+            .unwrap();
+        chosen.push(nodes[best]);
+        for n in &closed_nbrs[best] {
+            undominated.remove(n);
+        }
+    }
+
+    chosen.sort_unstable();
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::Undirected;
+    use crate::test_util::graph_from_edges;
+
+    fn is_cover(edges: &[(NodeInd, NodeInd)], cover: &[NodeInd]) -> bool {
+        edges
+            .iter()
+            .all(|(a, b)| cover.contains(a) || cover.contains(b))
+    }
+
+    #[test]
+    fn test_vertex_cover_2approx() {
+        let cases: Vec<(usize, Vec<(NodeInd, NodeInd)>)> = vec![
+            (4, vec![(0, 1), (0, 2), (0, 3)]),
+            (5, vec![(0, 1), (1, 2), (2, 3), (3, 4)]),
+            (5, vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 0), (0, 2)]),
+            (6, vec![(0, 1), (2, 3), (4, 5), (1, 2), (3, 4), (5, 5)]),
+        ];
+        for (n, edges) in cases {
+            let g = graph_from_edges::<Undirected>(n, &edges);
+            let cover = vertex_cover_2approx(&g);
+            assert!(is_cover(&edges, &cover));
+
+            // brute force the minimum cover
+            let optimal = (0u32..(1 << n))
+                .filter(|mask| {
+                    let subset: Vec<NodeInd> = (0..n).filter(|i| mask & (1 << i) != 0).collect();
+                    is_cover(&edges, &subset)
+                })
+                .map(|mask| mask.count_ones() as usize)
+                .min()
+                .unwrap();
+            assert!(cover.len() <= 2 * optimal);
+        }
+    }
+
+    fn is_dominating(n: usize, edges: &[(NodeInd, NodeInd)], set: &[NodeInd]) -> bool {
+        (0..n).all(|v| {
+            set.contains(&v)
+                || edges
+                    .iter()
+                    .any(|&(a, b)| (a == v && set.contains(&b)) || (b == v && set.contains(&a)))
+        })
+    }
+
+    #[test]
+    fn test_dominating_set_star() {
+        let edges: Vec<(NodeInd, NodeInd)> = (1..6).map(|i| (i, 0)).collect();
+        let g = graph_from_edges::<Undirected>(6, &edges);
+        assert_eq!(dominating_set_greedy(&g), vec![0]);
+    }
+
+    #[test]
+    fn test_dominating_set_path() {
+        let edges: Vec<(NodeInd, NodeInd)> = (0..6).map(|i| (i, i + 1)).collect();
+        let g = graph_from_edges::<Undirected>(7, &edges);
+        let set = dominating_set_greedy(&g);
+        assert!(is_dominating(7, &edges, &set));
+        assert_eq!(set, vec![1, 4, 5]);
+    }
+
+    #[test]
+    fn test_max_independent_set() {
+        let c5: Vec<(NodeInd, NodeInd)> = (0..5).map(|i| (i, (i + 1) % 5)).collect();
+        let set = max_independent_set(&graph_from_edges::<Undirected>(5, &c5));
+        assert_eq!(set.len(), 2);
+        assert!(set
+            .iter()
+            .all(|a| set.iter().all(|b| !c5.contains(&(*a, *b)))));
+
+        let p4 = graph_from_edges::<Undirected>(4, &[(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(max_independent_set(&p4).len(), 2);
+
+        // node 4 has a self-loop, so it can never be chosen
+        let star = graph_from_edges::<Undirected>(5, &[(0, 1), (0, 2), (0, 3), (0, 4), (4, 4)]);
+        assert_eq!(max_independent_set(&star), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_maximal_independent_set() {
+        let edges = [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 0),
+            (0, 5),
+            (5, 6),
+            (6, 6),
+        ];
+        let g = graph_from_edges::<Undirected>(7, &edges);
+        let set = maximal_independent_set(&g);
+
+        let adjacent = |a: NodeInd, b: NodeInd| edges.contains(&(a, b)) || edges.contains(&(b, a));
+        for &a in &set {
+            for &b in &set {
+                assert!(!adjacent(a, b));
+            }
+        }
+        // every other node has a neighbor in the set, or can't be in one
+        for n in 0..7 {
+            assert!(set.contains(&n) || adjacent(n, n) || set.iter().any(|&s| adjacent(n, s)));
+        }
+    }
+}
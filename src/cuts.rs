@@ -0,0 +1,97 @@
+//! Algorithms for finding cuts in graphs.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::graph_base::{EdgeInd, GraphBase, NodeInd, Undirected};
+
+/// Runs one trial of Karger's randomized contraction algorithm on an undirected
+/// graph, returning the size of the cut it found. Random edges are contracted
+/// until only two nodes remain, at which point the edges left between them are
+/// a cut. The result is always at least the minimum cut, and is equal to it
+/// with probability at least 2 / (V(V - 1)): use [`karger_min_cut_repeated`]
+/// to get the right answer with high probability.
+///
+/// Graphs with fewer than two nodes have no cuts, so this returns 0 for them,
+/// as it does for disconnected graphs. Self-loops are ignored.
+pub fn karger_min_cut<N, E, G: GraphBase<N, E, Undirected> + Clone, R: Rng + ?Sized>(
+    graph: &G,
+    rng: &mut R,
+) -> usize {
+    let mut contracted = graph.clone();
+    // self-loops never cross a cut, and contracting one would merge a node
+    // into itself
+    contracted.remove_self_loops();
+    let mut num_nodes = contracted.nodes().count();
+    while num_nodes > 2 {
+        let edges: Vec<EdgeInd> = contracted.edges().collect();
+        let e = match edges.choose(rng) {
+            Some(e) => e,
+            // more than one component left, so there's an empty cut
+            None => return 0,
+        };
+        let (keep, merge): (NodeInd, NodeInd) = contracted.edge_endpoints(e);
+        contracted.contract_nodes(&keep, &merge, true);
+        num_nodes -= 1;
+    }
+    contracted.edges().count()
+}
+
+/// Runs [`karger_min_cut`] `trials` times and returns the smallest cut found.
+/// Using about V^2 log V trials finds the minimum cut with high probability.
+pub fn karger_min_cut_repeated<N, E, G: GraphBase<N, E, Undirected> + Clone, R: Rng + ?Sized>(
+    graph: &G,
+    trials: usize,
+    rng: &mut R,
+) -> usize {
+    (0..trials)
+        .map(|_| karger_min_cut(graph, rng))
+        .min()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::graph_from_edges;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_karger_bottleneck() {
+        // two 4-cliques joined by a single edge
+        let mut edges = vec![(3, 4)];
+        for offset in [0, 4] {
+            for i in 0..4 {
+                for j in (i + 1)..4 {
+                    edges.push((offset + i, offset + j));
+                }
+            }
+        }
+        let g = graph_from_edges::<Undirected>(8, &edges);
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..10 {
+            assert!(karger_min_cut(&g, &mut rng) >= 1);
+            assert_eq!(karger_min_cut_repeated(&g, 100, &mut rng), 1);
+        }
+    }
+
+    #[test]
+    fn test_karger_disconnected() {
+        let g = graph_from_edges::<Undirected>(4, &[(0, 1), (2, 3)]);
+        let mut rng = StdRng::seed_from_u64(5);
+        assert_eq!(karger_min_cut_repeated(&g, 10, &mut rng), 0);
+    }
+
+    #[test]
+    fn test_karger_self_loop() {
+        // a triangle with a self-loop still has a minimum cut of 2
+        let g = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0), (0, 0)]);
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..10 {
+            assert_eq!(karger_min_cut(&g, &mut rng), 2);
+        }
+        assert_eq!(karger_min_cut_repeated(&g, 100, &mut rng), 2);
+    }
+}
@@ -0,0 +1,361 @@
+//! Algorithms dealing with cycles.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::graph_base::{Directed, EdgeInd, GraphType, GraphView, NodeInd, Undirected};
+
+/// Gets the girth of the graph: the length of its shortest cycle, or `None` if
+/// the graph has no cycles. Self-loops count as cycles of length 1, and for
+/// undirected graphs two parallel edges count as a cycle of length 2, but going
+/// back along the edge just used does not.
+///
+/// This runs a BFS from every node, so it takes O(V(V + E)) time.
+pub fn girth<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for source in graph.nodes() {
+        if let Some(len) = shortest_cycle_from(graph, &source) {
+            best = Some(best.map_or(len, |b| b.min(len)));
+        }
+    }
+    best
+}
+
+/// Finds the length of the shortest cycle found by a BFS from `source`. For
+/// directed graphs, this is the shortest cycle through `source`. For undirected
+/// graphs, the cycle might not pass through `source`, but the minimum over all
+/// sources is still the girth.
+fn shortest_cycle_from<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    source: &NodeInd,
+) -> Option<usize> {
+    // distance from the source and the edge used to get to each node
+    let mut dist: Vec<Option<(usize, Option<EdgeInd>)>> = vec![];
+    let mut queue = VecDeque::from([*source]);
+    let mut best: Option<usize> = None;
+
+    let set = |dist: &mut Vec<Option<(usize, Option<EdgeInd>)>>, n: NodeInd, val| {
+        if dist.len() <= n {
+            dist.resize(n + 1, None);
+        }
+        dist[n] = Some(val);
+    };
+    set(&mut dist, *source, (0, None));
+
+    while let Some(curr) = queue.pop_front() {
+        let (d, parent_edge) = dist[curr].unwrap();
+        // the shortest cycle we could still find from here: directed cycles
+        // have to close at the source, undirected ones can close anywhere
+        let lower_bound = if graph.is_directed() {
+            d + 1
+        } else {
+            2 * d + 1
+        };
+        if best.is_some_and(|b| lower_bound >= b) {
+            break;
+        }
+        for edge in graph.edges_from(&curr) {
+            if Some(edge.index) == parent_edge {
+                continue;
+            }
+            let next = edge.opposite(&curr);
+            let cycle_len = match dist.get(next).copied().flatten() {
+                None => {
+                    set(&mut dist, next, (d + 1, Some(edge.index)));
+                    queue.push_back(next);
+                    continue;
+                }
+                Some((next_d, _)) if graph.is_directed() => {
+                    if next != *source {
+                        continue;
+                    }
+                    d + 1 + next_d
+                }
+                Some((next_d, _)) => d + 1 + next_d,
+            };
+            best = Some(best.map_or(cycle_len, |b| b.min(cycle_len)));
+        }
+    }
+
+    best
+}
+
+/// Finds a cycle basis of an undirected graph: a set of cycles such that every
+/// cycle in the graph is a symmetric difference of some of them. This builds a
+/// BFS spanning forest and returns the fundamental cycle of each edge not in
+/// it, so there are E - V + C cycles, where C is the number of connected
+/// components. Each cycle is given as its nodes in order, starting and ending
+/// at the endpoints of the non-tree edge that closes it. Self-loops give cycles
+/// with one node, and parallel edges give cycles with two.
+pub fn cycle_basis<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> Vec<Vec<NodeInd>> {
+    // the parent of each node in the forest, with the edge to it, and its depth
+    let mut parent: HashMap<NodeInd, Option<(NodeInd, EdgeInd)>> = HashMap::new();
+    let mut depth: HashMap<NodeInd, usize> = HashMap::new();
+    let mut tree_edges: HashSet<EdgeInd> = HashSet::new();
+
+    for root in graph.nodes_sorted() {
+        if parent.contains_key(&root) {
+            continue;
+        }
+        parent.insert(root, None);
+        depth.insert(root, 0);
+        let mut queue = VecDeque::from([root]);
+        while let Some(curr) = queue.pop_front() {
+            for (next, e) in graph.neighbors_with_edges(&curr) {
+                if let Entry::Vacant(slot) = parent.entry(next) {
+                    slot.insert(Some((curr, e)));
+                    depth.insert(next, depth[&curr] + 1);
+                    tree_edges.insert(e);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    let up = |n: NodeInd| parent[&n].unwrap().0;
+    let mut cycles = vec![];
+    for e in graph.edges_sorted() {
+        if tree_edges.contains(&e) {
+            continue;
+        }
+        let (mut a, mut b) = graph.edge_endpoints(&e);
+        // walk both ends up to their lowest common ancestor
+        let mut from_a = vec![];
+        let mut from_b = vec![];
+        while depth[&a] > depth[&b] {
+            from_a.push(a);
+            a = up(a);
+        }
+        while depth[&b] > depth[&a] {
+            from_b.push(b);
+            b = up(b);
+        }
+        while a != b {
+            from_a.push(a);
+            from_b.push(b);
+            a = up(a);
+            b = up(b);
+        }
+        from_a.push(a);
+        from_a.extend(from_b.into_iter().rev());
+        cycles.push(from_a);
+    }
+    cycles
+}
+
+/// Lists every simple cycle (elementary circuit) of a directed graph: every
+/// cycle that doesn't repeat any nodes. This uses Johnson's algorithm. Each
+/// cycle is given as its nodes in order, starting from the one with the
+/// smallest index, and is listed only once. Self-loops are cycles with one
+/// node, and parallel edges don't give separate cycles.
+///
+/// Johnson's algorithm takes O((V + E)(C + 1)) time to find C cycles, plus the
+/// O(V(V + E)) it takes here to find the part of the graph each search runs in.
+/// C can be exponential in the size of the graph, so only use this on graphs
+/// you know don't have too many cycles.
+pub fn simple_cycles<N, E, G: GraphView<N, E, Directed>>(graph: &G) -> Vec<Vec<NodeInd>> {
+    let mut cycles = vec![];
+    for start in graph.nodes_sorted() {
+        // the strongly connected component of start among the nodes with at
+        // least its index: cycles outside of that have already been found or
+        // don't go through start
+        let forward = reach_within(graph, start, true);
+        let backward = reach_within(graph, start, false);
+        let comp: HashSet<NodeInd> = forward.intersection(&backward).copied().collect();
+        let nbrs = |n: &NodeInd| -> Vec<NodeInd> {
+            graph
+                .distinct_neighbors(n)
+                .filter(|m| comp.contains(m))
+                .collect()
+        };
+
+        let mut blocked: HashSet<NodeInd> = HashSet::from([start]);
+        // the nodes to unblock when each node is unblocked
+        let mut unblock_with: HashMap<NodeInd, HashSet<NodeInd>> = HashMap::new();
+        let mut path = vec![start];
+        // the neighbors left to try from each node on the path, and whether
+        // any cycle has been found through it
+        let mut stack: Vec<(Vec<NodeInd>, bool)> = vec![(nbrs(&start), false)];
+
+        while let Some((left, closed)) = stack.last_mut() {
+            if let Some(next) = left.pop() {
+                if next == start {
+                    cycles.push(path.clone());
+                    *closed = true;
+                } else if blocked.insert(next) {
+                    path.push(next);
+                    stack.push((nbrs(&next), false));
+                }
+                continue;
+            }
+
+            let closed = *closed;
+            stack.pop();
+            let curr = path.pop().unwrap();
+            if closed {
+                let mut to_unblock = vec![curr];
+                while let Some(n) = to_unblock.pop() {
+                    if blocked.remove(&n) {
+                        to_unblock.extend(unblock_with.remove(&n).unwrap_or_default());
+                    }
+                }
+                if let Some((_, parent_closed)) = stack.last_mut() {
+                    *parent_closed = true;
+                }
+            } else {
+                for next in nbrs(&curr) {
+                    unblock_with.entry(next).or_default().insert(curr);
+                }
+            }
+        }
+    }
+    cycles
+}
+
+/// Gets the nodes with at least the index of `start` that can be reached from
+/// it, or that can reach it if `forward` is false, using only those nodes.
+fn reach_within<N, E, G: GraphView<N, E, Directed>>(
+    graph: &G,
+    start: NodeInd,
+    forward: bool,
+) -> HashSet<NodeInd> {
+    let mut found = HashSet::from([start]);
+    let mut stack = vec![start];
+    while let Some(curr) = stack.pop() {
+        let edges = if forward {
+            graph.edges_from(&curr)
+        } else {
+            graph.edges_to(&curr)
+        };
+        for edge in edges {
+            let next = edge.opposite(&curr);
+            if next >= start && found.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::graph_from_edges;
+
+    #[test]
+    fn test_girth_triangle() {
+        let g = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(girth(&g), Some(3));
+    }
+
+    #[test]
+    fn test_girth_square() {
+        let g = graph_from_edges::<Undirected>(5, &[(0, 1), (1, 2), (2, 3), (3, 0), (3, 4)]);
+        assert_eq!(girth(&g), Some(4));
+    }
+
+    #[test]
+    fn test_girth_tree() {
+        let g = graph_from_edges::<Undirected>(5, &[(0, 1), (0, 2), (1, 3), (1, 4)]);
+        assert_eq!(girth(&g), None);
+    }
+
+    #[test]
+    fn test_girth_directed() {
+        // a DAG has no cycles even though its underlying graph does
+        let dag = graph_from_edges::<Directed>(3, &[(0, 1), (1, 2), (0, 2)]);
+        assert_eq!(girth(&dag), None);
+
+        let g = graph_from_edges::<Directed>(4, &[(0, 1), (1, 2), (2, 3), (3, 0), (2, 0)]);
+        assert_eq!(girth(&g), Some(3));
+    }
+
+    #[test]
+    fn test_girth_multigraph() {
+        let parallel = graph_from_edges::<Undirected>(2, &[(0, 1), (0, 1)]);
+        assert_eq!(girth(&parallel), Some(2));
+
+        let lp = graph_from_edges::<Undirected>(2, &[(0, 1), (1, 1)]);
+        assert_eq!(girth(&lp), Some(1));
+    }
+
+    #[test]
+    fn test_cycle_basis() {
+        // two triangles sharing an edge, a square hanging off them, a tree, a
+        // self-loop, a doubled edge, and an isolated node
+        let edges = [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (1, 3),
+            (3, 2),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (6, 3),
+            (7, 8),
+            (8, 9),
+            (9, 9),
+            (10, 11),
+            (10, 11),
+        ];
+        let g = graph_from_edges::<Undirected>(13, &edges);
+        let cycles = cycle_basis(&g);
+        // 4 components: the cycles, the tree, the doubled edge, and the
+        // isolated node
+        assert_eq!(cycles.len(), edges.len() - 13 + 4);
+
+        for cycle in &cycles {
+            // every consecutive pair, wrapping around, is joined by an edge
+            for (i, a) in cycle.iter().enumerate() {
+                let b = cycle[(i + 1) % cycle.len()];
+                assert!(g.neighbors(a).any(|n| n == b));
+            }
+            let distinct: HashSet<&NodeInd> = cycle.iter().collect();
+            assert_eq!(distinct.len(), cycle.len());
+        }
+        let mut lens: Vec<usize> = cycles.iter().map(|c| c.len()).collect();
+        lens.sort_unstable();
+        assert_eq!(lens[..3], [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_simple_cycles() {
+        // two cycles sharing the edge 1 -> 2, plus a self-loop, a doubled edge
+        // into the cycles, and an edge going nowhere
+        let g = graph_from_edges::<Directed>(
+            6,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 1),
+                (3, 3),
+                (4, 0),
+                (4, 0),
+                (2, 5),
+            ],
+        );
+        let mut cycles = simple_cycles(&g);
+        cycles.sort();
+        assert_eq!(cycles, vec![vec![0, 1, 2], vec![1, 2, 3], vec![3]]);
+
+        let dag = graph_from_edges::<Directed>(3, &[(0, 1), (1, 2), (0, 2)]);
+        assert!(simple_cycles(&dag).is_empty());
+    }
+
+    #[test]
+    fn test_simple_cycles_complete() {
+        // the complete directed graph on 4 nodes has 6 2-cycles, 8 3-cycles,
+        // and 6 4-cycles
+        let edges: Vec<(NodeInd, NodeInd)> = (0..4)
+            .flat_map(|a| (0..4).filter(move |&b| b != a).map(move |b| (a, b)))
+            .collect();
+        let g = graph_from_edges::<Directed>(4, &edges);
+        let cycles = simple_cycles(&g);
+        assert_eq!(cycles.len(), 20);
+        let distinct: HashSet<&Vec<NodeInd>> = cycles.iter().collect();
+        assert_eq!(distinct.len(), 20);
+    }
+}
@@ -0,0 +1,180 @@
+//! Differences between two versions of a graph.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::adj_list_graph::ALGraph;
+use crate::graph_base::{Edge, EdgeInd, GraphBase, GraphType, GraphView, NodeInd};
+
+/// The changes that turn one graph into another. Nodes and edges are matched
+/// by index: one that's in only one of the graphs, or that has different data
+/// (or, for edges, different endpoints) in the two, is removed from the first
+/// and added from the second. Everything is in ascending order of index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphDiff<N, E> {
+    /// The nodes in the second graph that aren't in the first, with their data.
+    pub added_nodes: Vec<(NodeInd, N)>,
+
+    /// The nodes in the first graph that aren't in the second.
+    pub removed_nodes: Vec<NodeInd>,
+
+    /// The edges in the second graph that aren't in the first.
+    pub added_edges: Vec<Edge<E>>,
+
+    /// The edges in the first graph that aren't in the second.
+    pub removed_edges: Vec<EdgeInd>,
+}
+
+impl<N, E> GraphDiff<N, E> {
+    /// Whether the two graphs were the same.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+/// Gets the keys of `a` that `b` doesn't have under the same key with the same
+/// value, in order.
+fn missing_from<K: Copy + Ord + Hash, V: PartialEq>(
+    a: &HashMap<K, &V>,
+    b: &HashMap<K, &V>,
+) -> Vec<K> {
+    let mut missing: Vec<K> = a
+        .iter()
+        .filter(|(k, v)| b.get(k).is_none_or(|w| w != *v))
+        .map(|(k, _)| *k)
+        .collect();
+    missing.sort_unstable();
+    missing
+}
+
+fn node_map<N, E: Clone, Ty: GraphType>(graph: &ALGraph<N, E, Ty>) -> HashMap<NodeInd, &N> {
+    graph.nodes().map(|n| (n, graph.node(&n))).collect()
+}
+
+fn edge_map<N, E: Clone, Ty: GraphType>(graph: &ALGraph<N, E, Ty>) -> HashMap<EdgeInd, &Edge<E>> {
+    graph.edges().map(|e| (e, graph.edge(&e))).collect()
+}
+
+impl<N: Clone + PartialEq, E: Clone + PartialEq, Ty: GraphType> ALGraph<N, E, Ty> {
+    /// Gets the changes that turn this graph into `other`.
+    pub fn diff(&self, other: &Self) -> GraphDiff<N, E> {
+        let (self_nodes, other_nodes) = (node_map(self), node_map(other));
+        let (self_edges, other_edges) = (edge_map(self), edge_map(other));
+
+        GraphDiff {
+            added_nodes: missing_from(&other_nodes, &self_nodes)
+                .into_iter()
+                .map(|n| (n, other.node(&n).clone()))
+                .collect(),
+            removed_nodes: missing_from(&self_nodes, &other_nodes),
+            added_edges: missing_from(&other_edges, &self_edges)
+                .into_iter()
+                .map(|e| other.edge(&e).clone())
+                .collect(),
+            removed_edges: missing_from(&self_edges, &other_edges),
+        }
+    }
+
+    /// Makes the changes in `diff` to this graph, so `a.apply(&a.diff(&b))`
+    /// turns `a` into `b`, with the same node and edge indices. Nodes that are
+    /// both removed and added keep their edges that aren't removed, and just
+    /// get the new data. Panics if the diff doesn't fit the graph, by removing
+    /// something that isn't there or adding an edge at an index that's in use.
+    pub fn apply(&mut self, diff: &GraphDiff<N, E>) {
+        for e in &diff.removed_edges {
+            self.remove_edge(e);
+        }
+        let readded: HashSet<NodeInd> = diff.added_nodes.iter().map(|(n, _)| *n).collect();
+        for n in &diff.removed_nodes {
+            if !readded.contains(n) {
+                self.remove_node(n);
+            }
+        }
+        for (n, data) in &diff.added_nodes {
+            self.insert_node_at(*n, data.clone());
+        }
+        for edge in &diff.added_edges {
+            self.insert_edge_at(edge.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::{Directed, Undirected};
+    use crate::test_util::graph_from_edges;
+
+    #[test]
+    fn test_diff_extra_edge() {
+        let g = graph_from_edges::<Undirected>(4, &[(0, 1), (1, 2)]);
+        let mut h = g.clone();
+        let e = h.add_edge(&2, &3, ());
+
+        let diff = g.diff(&h);
+        assert_eq!(diff.added_edges, vec![h.edge(&e).clone()]);
+        assert!(diff.added_nodes.is_empty());
+        assert!(diff.removed_nodes.is_empty());
+        assert!(diff.removed_edges.is_empty());
+
+        // the other way round, the edge was removed
+        let back = h.diff(&g);
+        assert_eq!(back.removed_edges, vec![e]);
+        assert!(back.added_edges.is_empty());
+
+        assert!(g.diff(&g).is_empty());
+    }
+
+    #[test]
+    fn test_diff_changed_data() {
+        let mut g: ALGraph<char, i32, Directed> = ALGraph::new();
+        let a = g.add_node('a');
+        let b = g.add_node('b');
+        let ab = g.add_edge(&a, &b, 1);
+
+        let mut h = g.clone();
+        *h.node_mut(&b) = 'c';
+        h.edge_mut(&ab).data = 2;
+        h.remove_node(&a);
+
+        let diff = g.diff(&h);
+        assert_eq!(diff.added_nodes, vec![(b, 'c')]);
+        assert_eq!(diff.removed_nodes, vec![a, b]);
+        assert!(diff.added_edges.is_empty());
+        assert_eq!(diff.removed_edges, vec![ab]);
+    }
+
+    #[test]
+    fn test_apply_round_trip() {
+        let mut a: ALGraph<char, i32, Directed> = ALGraph::new();
+        for c in ['a', 'b', 'c', 'd'] {
+            a.add_node(c);
+        }
+        a.add_edge(&0, &1, 1);
+        a.add_edge(&1, &2, 2);
+        let cd = a.add_edge(&2, &3, 3);
+
+        let mut b = a.clone();
+        b.remove_node(&0);
+        *b.node_mut(&2) = 'C';
+        b.edge_mut(&cd).data = 30;
+        let e = b.add_node('e');
+        b.add_edge(&e, &2, 4);
+        b.add_edge(&3, &3, 5);
+
+        let mut patched = a.clone();
+        patched.apply(&a.diff(&b));
+        assert_eq!(patched, b);
+        // indices match too, so there's nothing left to change
+        assert!(patched.diff(&b).is_empty());
+        // and new nodes and edges don't collide with the patched-in ones
+        assert_eq!(patched.clone().add_node('f'), b.clone().add_node('f'));
+
+        // going back works the same way
+        patched.apply(&b.diff(&a));
+        assert!(patched.diff(&a).is_empty());
+    }
+}
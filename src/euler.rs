@@ -0,0 +1,123 @@
+//! Eulerian trails: walks that use every edge exactly once.
+
+use crate::connectivity::bridges;
+use crate::graph_base::{EdgeInd, GraphBase, NodeInd, Undirected};
+
+/// Finds an Eulerian trail of an undirected graph using Fleury's algorithm,
+/// returning the edges in the order they're walked, or `None` if there's no
+/// such trail. A trail exists when every edge is in the same component and
+/// either no nodes or exactly two nodes have odd degree: in the first case the
+/// trail is a circuit starting at the lowest-indexed node with edges, and in
+/// the second it starts at the lower-indexed odd node.
+///
+/// Fleury's algorithm walks the edges, removing them as it goes, and never
+/// crosses a bridge of what's left unless there's no other choice. Bridges are
+/// recomputed at every step, so this takes O(E^2) time.
+pub fn fleury_eulerian<N, E, G: GraphBase<N, E, Undirected> + Clone>(
+    graph: &G,
+) -> Option<Vec<EdgeInd>> {
+    let nodes = graph.nodes_sorted();
+    let odd: Vec<NodeInd> = nodes
+        .iter()
+        .copied()
+        .filter(|n| graph.edges_at(n).count() % 2 == 1)
+        .collect();
+    let mut curr = match odd.len() {
+        0 => match nodes
+            .into_iter()
+            .find(|n| graph.edges_at(n).next().is_some())
+        {
+            Some(n) => n,
+            None => return Some(vec![]),
+        },
+        2 => odd[0],
+        _ => return None,
+    };
+
+    let num_edges = graph.edges().count();
+    let mut remaining = graph.clone();
+    let mut trail = Vec::with_capacity(num_edges);
+    loop {
+        let options: Vec<(NodeInd, EdgeInd)> = remaining.neighbors_with_edges(&curr).collect();
+        let (next, e) = match options.len() {
+            0 => break,
+            1 => options[0],
+            _ => {
+                let bridges = bridges(&remaining);
+                *options
+                    .iter()
+                    .find(|(_, e)| !bridges.contains(e))
+                    .unwrap_or(&options[0])
+            }
+        };
+        remaining.remove_edge(&e);
+        trail.push(e);
+        curr = next;
+    }
+
+    // if we got stuck early, some edges weren't connected to the start
+    (trail.len() == num_edges).then_some(trail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::graph_from_edges;
+
+    /// Checks that the trail uses every edge once, and that each edge starts
+    /// where the last one ended.
+    fn assert_eulerian(edges: &[(NodeInd, NodeInd)], trail: &[EdgeInd]) {
+        let mut sorted = trail.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, (0..edges.len()).collect::<Vec<_>>());
+
+        let walks_from = |start: NodeInd| {
+            let mut curr = start;
+            trail.iter().all(|&e| {
+                let (x, y) = edges[e];
+                let connected = x == curr || y == curr;
+                curr = if x == curr { y } else { x };
+                connected
+            })
+        };
+        let (a, b) = edges[trail[0]];
+        assert!(walks_from(a) || walks_from(b));
+    }
+
+    #[test]
+    fn test_fleury_circuit() {
+        // two triangles sharing a node, plus a self-loop
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 2), (4, 4)];
+        let g = graph_from_edges::<Undirected>(5, &edges);
+        let trail = fleury_eulerian(&g).unwrap();
+        assert_eulerian(&edges, &trail);
+    }
+
+    #[test]
+    fn test_fleury_trail() {
+        // a bridge between two cycles forces the walk to finish one first
+        let edges = [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (0, 3),
+            (3, 4),
+            (4, 5),
+            (5, 3),
+            (3, 6),
+        ];
+        let g = graph_from_edges::<Undirected>(7, &edges);
+        let trail = fleury_eulerian(&g).unwrap();
+        assert_eulerian(&edges, &trail);
+    }
+
+    #[test]
+    fn test_fleury_none() {
+        let star = graph_from_edges::<Undirected>(4, &[(0, 1), (0, 2), (0, 3)]);
+        assert_eq!(fleury_eulerian(&star), None);
+
+        let disconnected =
+            graph_from_edges::<Undirected>(6, &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+        assert_eq!(fleury_eulerian(&disconnected), None);
+    }
+}
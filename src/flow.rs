@@ -0,0 +1,253 @@
+//! Network flow algorithms.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::graph_base::{Edge, EdgeInd, GraphType, GraphView, NodeInd};
+
+/// One direction of an arc in a residual network. Arcs are stored in pairs, so
+/// the reverse of arc `i` is arc `i ^ 1`.
+#[derive(Copy, Clone, Debug)]
+struct Arc {
+    to: usize,
+    cap: f64,
+    flow: f64,
+    cost: f64,
+}
+
+/// A flow network on nodes `0..n`, for the flow-based algorithms to build their
+/// own networks in, with node splitting and the like.
+#[derive(Clone, Debug)]
+pub(crate) struct FlowNetwork {
+    adj: Vec<Vec<usize>>,
+    arcs: Vec<Arc>,
+}
+
+impl FlowNetwork {
+    /// Makes a network with `n` nodes and no arcs.
+    pub(crate) fn new(n: usize) -> Self {
+        Self {
+            adj: vec![vec![]; n],
+            arcs: vec![],
+        }
+    }
+
+    /// Adds an arc from `u` to `v` with the given capacity, returning its index.
+    pub(crate) fn add_arc(&mut self, u: usize, v: usize, cap: f64) -> usize {
+        self.add_costed_arc(u, v, cap, 0.0)
+    }
+
+    /// Adds an arc from `u` to `v` with the given capacity and cost per unit of
+    /// flow, returning its index.
+    pub(crate) fn add_costed_arc(&mut self, u: usize, v: usize, cap: f64, cost: f64) -> usize {
+        let i = self.arcs.len();
+        self.arcs.push(Arc {
+            to: v,
+            cap,
+            flow: 0.0,
+            cost,
+        });
+        self.arcs.push(Arc {
+            to: u,
+            cap: 0.0,
+            flow: 0.0,
+            cost: -cost,
+        });
+        self.adj[u].push(i);
+        self.adj[v].push(i + 1);
+        i
+    }
+
+    /// Gets the flow along an arc.
+    pub(crate) fn flow(&self, arc: usize) -> f64 {
+        self.arcs[arc].flow
+    }
+
+    /// Pushes as much flow as possible from `s` to `t`, on top of whatever flow
+    /// there already is, and returns the amount added. This is Edmonds-Karp:
+    /// each augmenting path is a shortest one, found by BFS, so it takes
+    /// O(VE²) time whatever the capacities are.
+    pub(crate) fn max_flow(&mut self, s: usize, t: usize) -> f64 {
+        if s == t {
+            return 0.0;
+        }
+        let mut total = 0.0;
+        loop {
+            // the arc each node was reached by
+            let mut pred: Vec<Option<usize>> = vec![None; self.adj.len()];
+            let mut queue = VecDeque::from([s]);
+            while let Some(u) = queue.pop_front() {
+                if u == t {
+                    break;
+                }
+                for &i in &self.adj[u] {
+                    let arc = self.arcs[i];
+                    if arc.to != s && pred[arc.to].is_none() && arc.cap - arc.flow > 0.0 {
+                        pred[arc.to] = Some(i);
+                        queue.push_back(arc.to);
+                    }
+                }
+            }
+            if pred[t].is_none() {
+                return total;
+            }
+
+            let mut path = vec![];
+            let mut v = t;
+            while let Some(i) = pred[v] {
+                path.push(i);
+                v = self.arcs[i ^ 1].to;
+            }
+            let push = path
+                .iter()
+                .map(|&i| self.arcs[i].cap - self.arcs[i].flow)
+                .fold(f64::INFINITY, f64::min);
+            for i in path {
+                self.arcs[i].flow += push;
+                self.arcs[i ^ 1].flow -= push;
+            }
+            total += push;
+        }
+    }
+
+    /// Pushes flow from `s` to `t` along cheapest augmenting paths, found by
+    /// Bellman-Ford, for as long as they cost less than nothing, and returns the
+    /// total cost added. This gets the cheapest flow of any amount, as long as
+    /// the network has no cycles of negative cost to begin with.
+    pub(crate) fn cheapest_flow(&mut self, s: usize, t: usize) -> f64 {
+        let mut total = 0.0;
+        loop {
+            let mut dist: Vec<f64> = vec![f64::INFINITY; self.adj.len()];
+            let mut pred: Vec<Option<usize>> = vec![None; self.adj.len()];
+            dist[s] = 0.0;
+            for _ in 0..self.adj.len() {
+                let mut changed = false;
+                for u in 0..self.adj.len() {
+                    if dist[u] == f64::INFINITY {
+                        continue;
+                    }
+                    for &i in &self.adj[u] {
+                        let arc = self.arcs[i];
+                        if arc.cap - arc.flow > 0.0 && dist[u] + arc.cost < dist[arc.to] {
+                            dist[arc.to] = dist[u] + arc.cost;
+                            pred[arc.to] = Some(i);
+                            changed = true;
+                        }
+                    }
+                }
+                if !changed {
+                    break;
+                }
+            }
+            if dist[t] >= 0.0 {
+                return total;
+            }
+
+            let mut path = vec![];
+            let mut v = t;
+            while let Some(i) = pred[v] {
+                path.push(i);
+                v = self.arcs[i ^ 1].to;
+            }
+            let push = path
+                .iter()
+                .map(|&i| self.arcs[i].cap - self.arcs[i].flow)
+                .fold(f64::INFINITY, f64::min);
+            for i in path {
+                self.arcs[i].flow += push;
+                self.arcs[i ^ 1].flow -= push;
+            }
+            total += push * dist[t];
+        }
+    }
+}
+
+/// Gets the maximum flow from `source` to `sink`, where each edge can carry up
+/// to its capacity, along with the flow along each edge. Undirected edges can
+/// carry flow either way: their flow is positive if it goes from start to end,
+/// and negative otherwise. Capacities must be non-negative.
+pub fn max_flow<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    sink: &NodeInd,
+    capacity: F,
+) -> (f64, HashMap<EdgeInd, f64>)
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let nodes = graph.nodes_sorted();
+    let pos: HashMap<NodeInd, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let mut network = FlowNetwork::new(nodes.len());
+    let edges = graph.edges_sorted();
+    let arcs: Vec<(EdgeInd, usize, Option<usize>)> = edges
+        .into_iter()
+        .map(|e| {
+            let edge = graph.edge(&e);
+            let (u, v) = (pos[&edge.start], pos[&edge.end]);
+            let cap = capacity(edge);
+            let forward = network.add_arc(u, v, cap);
+            let backward = (!graph.is_directed()).then(|| network.add_arc(v, u, cap));
+            (e, forward, backward)
+        })
+        .collect();
+
+    let total = network.max_flow(pos[source], pos[sink]);
+    let flows = arcs
+        .into_iter()
+        .map(|(e, forward, backward)| {
+            let back = backward.map_or(0.0, |b| network.flow(b));
+            (e, network.flow(forward) - back)
+        })
+        .collect();
+    (total, flows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::{Directed, Undirected};
+    use crate::test_util::weighted_graph_from_edges;
+
+    #[test]
+    fn test_max_flow() {
+        // the classic example from CLRS, with maximum flow 23
+        let g = weighted_graph_from_edges::<Directed>(
+            6,
+            &[
+                (0, 1, 16.0),
+                (0, 2, 13.0),
+                (2, 1, 4.0),
+                (1, 3, 12.0),
+                (3, 2, 9.0),
+                (2, 4, 14.0),
+                (4, 3, 7.0),
+                (3, 5, 20.0),
+                (4, 5, 4.0),
+            ],
+        );
+        let (total, flows) = max_flow(&g, &0, &5, |e| e.data);
+        assert_eq!(total, 23.0);
+        for e in g.edges() {
+            assert!(flows[&e] >= 0.0 && flows[&e] <= g.edge(&e).data);
+        }
+        // flow is conserved everywhere but the source and sink
+        for n in 1..5 {
+            let inflow: f64 = g.edges_to(&n).map(|e| flows[&e.index]).sum();
+            let outflow: f64 = g.edges_from(&n).map(|e| flows[&e.index]).sum();
+            assert_eq!(inflow, outflow);
+        }
+        assert_eq!(max_flow(&g, &5, &0, |e| e.data).0, 0.0);
+    }
+
+    #[test]
+    fn test_max_flow_undirected() {
+        let g = weighted_graph_from_edges::<Undirected>(
+            4,
+            &[(0, 1, 3.0), (2, 1, 2.0), (1, 3, 1.0), (3, 2, 5.0)],
+        );
+        let (total, flows) = max_flow(&g, &0, &3, |e| e.data);
+        assert_eq!(total, 3.0);
+        assert_eq!(flows[&0], 3.0);
+        assert_eq!(flows[&1], -2.0);
+        assert_eq!(flows[&3], -2.0);
+    }
+}
@@ -0,0 +1,97 @@
+//! A read-only view of a graph.
+
+use crate::graph_base::{Edge, EdgeInd, GraphType, GraphView, NodeInd};
+
+/// A graph that can't be changed, for functions that need a guarantee that
+/// nothing else will change the graph while they use it. This is a
+/// [`GraphView`] over the inner graph, so it can be passed to any algorithm
+/// that only reads a graph, but it has none of the methods of
+/// [`GraphBase`](crate::graph_base::GraphBase) and there's no way to get a
+/// mutable reference back out of it. It's `Send` and `Sync` whenever the inner
+/// graph is `Sync`, so it can be handed to parallel algorithms freely.
+#[derive(Debug)]
+pub struct FrozenGraph<'a, G> {
+    graph: &'a G,
+}
+
+impl<'a, G> FrozenGraph<'a, G> {
+    /// Freezes the graph.
+    pub fn new(graph: &'a G) -> Self {
+        Self { graph }
+    }
+
+    /// Gets the inner graph, with the lifetime of the original borrow.
+    pub fn get(&self) -> &'a G {
+        self.graph
+    }
+}
+
+impl<G> Clone for FrozenGraph<'_, G> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<G> Copy for FrozenGraph<'_, G> {}
+
+impl<N, E, Ty: GraphType, G: GraphView<N, E, Ty>> GraphView<N, E, Ty> for FrozenGraph<'_, G> {
+    fn node(&self, n: &NodeInd) -> &N {
+        self.graph.node(n)
+    }
+
+    fn edge(&self, e: &EdgeInd) -> &Edge<E> {
+        self.graph.edge(e)
+    }
+
+    fn nodes(&self) -> Box<dyn Iterator<Item = NodeInd>> {
+        self.graph.nodes()
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeInd>> {
+        self.graph.edges()
+    }
+
+    fn edges_from(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        self.graph.edges_from(n)
+    }
+
+    fn edges_to(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        self.graph.edges_to(n)
+    }
+
+    fn edges_at(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        self.graph.edges_at(n)
+    }
+
+    fn edge_endpoints(&self, e: &EdgeInd) -> (NodeInd, NodeInd) {
+        self.graph.edge_endpoints(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adj_list_graph::ALGraph;
+    use crate::cycles::girth;
+    use crate::graph_base::{GraphBase, GraphView, Undirected};
+    use crate::test_util::graph_from_edges;
+
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    #[test]
+    fn test_frozen_graph() {
+        let mut g: ALGraph<usize, (), Undirected> = ALGraph::new();
+        let a = g.add_node(10);
+        let b = g.add_node(20);
+        let e = g.add_edge(&a, &b, ());
+
+        let frozen = g.freeze();
+        assert_send_sync(&frozen);
+        assert_eq!(*frozen.node(&a), 10);
+        assert_eq!(frozen.edge_endpoints(&e), (a, b));
+        assert_eq!(frozen.neighbors(&a).collect::<Vec<_>>(), vec![b]);
+        assert_eq!(frozen.nodes().count(), 2);
+
+        let triangle = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(girth(&triangle.freeze()), Some(3));
+    }
+}
@@ -0,0 +1,111 @@
+//! Generators for well-known families of graphs.
+
+use std::collections::BTreeSet;
+
+use crate::adj_list_graph::ALGraph;
+use crate::graph_base::{GraphBase, NodeInd, Undirected};
+
+/// Makes the Harary graph H(k, n): the graph on nodes `0..n` with as few edges
+/// as possible that stays connected after removing any k - 1 nodes, which is
+/// ⌈kn / 2⌉ edges for k ≥ 2.
+/// The nodes are arranged in a circle and each is joined to the ⌊k / 2⌋
+/// nearest nodes on either side. For odd k, each node is also joined to the
+/// node across the circle from it, except that with an odd number of nodes
+/// there's one node left over that gets two. H(1, n) is the path. Panics
+/// unless k < n.
+pub fn harary_graph(k: usize, n: usize) -> ALGraph<(), (), Undirected> {
+    assert!(
+        k < n,
+        "the Harary graph H({k}, {n}) needs fewer than {n} connections per node"
+    );
+    let mut edges: BTreeSet<(NodeInd, NodeInd)> = BTreeSet::new();
+    let mut join = |u: NodeInd, v: NodeInd| {
+        edges.insert((u.min(v), u.max(v)));
+    };
+    if k == 1 {
+        (1..n).for_each(|i| join(i - 1, i));
+    } else {
+        for i in 0..n {
+            for j in 1..=k / 2 {
+                join(i, (i + j) % n);
+            }
+        }
+        if k % 2 == 1 {
+            // across the circle: for odd n, this is to the node just before
+            // the opposite point, and the middle node is visited from both
+            // sides
+            for i in 0..n.div_ceil(2) {
+                join(i, (i + n / 2) % n);
+            }
+        }
+    }
+
+    let mut graph = ALGraph::new();
+    for _ in 0..n {
+        graph.add_node(());
+    }
+    for (u, v) in edges {
+        graph.add_edge(&u, &v, ());
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectivity::vertex_connectivity;
+    use crate::graph_base::GraphView;
+    use crate::traversal::reachable_from;
+
+    /// Whether the graph is still connected after removing `removed` nodes.
+    fn connected_without(g: &ALGraph<(), (), Undirected>, removed: &[NodeInd]) -> bool {
+        let mut g = g.clone();
+        for n in removed {
+            g.remove_node(n);
+        }
+        let Some(start) = g.nodes().next() else {
+            return true;
+        };
+        reachable_from(&g, &start).len() == g.nodes().count()
+    }
+
+    /// Calls `f` on every set of `size` nodes out of `0..n`.
+    fn for_subsets(
+        n: usize,
+        size: usize,
+        chosen: &mut Vec<NodeInd>,
+        f: &mut impl FnMut(&[NodeInd]),
+    ) {
+        if chosen.len() == size {
+            f(chosen);
+            return;
+        }
+        let next = chosen.last().map_or(0, |&c| c + 1);
+        for i in next..n {
+            chosen.push(i);
+            for_subsets(n, size, chosen, f);
+            chosen.pop();
+        }
+    }
+
+    #[test]
+    fn test_harary_graph() {
+        for n in 2..10 {
+            for k in 1..n {
+                let g = harary_graph(k, n);
+                let m = if k == 1 { n - 1 } else { (k * n).div_ceil(2) };
+                assert_eq!(g.edges().count(), m, "H({k}, {n})");
+                assert_eq!(g.nodes().map(|u| g.neighbors(&u).count()).min(), Some(k));
+
+                // vertex connectivity is exactly k: removing fewer nodes never
+                // disconnects it, and it can't be more than the smallest degree
+                let mut all_connected = true;
+                for_subsets(n, k - 1, &mut vec![], &mut |removed| {
+                    all_connected &= connected_without(&g, removed);
+                });
+                assert!(all_connected, "H({k}, {n})");
+                assert_eq!(vertex_connectivity(&g), k);
+            }
+        }
+    }
+}
@@ -1,10 +1,16 @@
-/// Represents a graph, either undirected or directed, with nodes and edges
-/// indexed by usize. (`petgraph`, the premier graph library in Rust, allows you
-/// to choose this, but that adds unnecessary generics here) You can store
-/// whatever data you like in the nodes and edges. This library also
-/// totally ditches the safety of petgraph, because things just panic if you try
-/// and access a node/edge that doesn't exist. This is bad for a big library,
-/// but it saves a *lot* of unwraps.
+//! Represents a graph, either undirected or directed, with nodes and edges
+//! indexed by usize. (`petgraph`, the premier graph library in Rust, allows you
+//! to choose this, but that adds unnecessary generics here) You can store
+//! whatever data you like in the nodes and edges. This library also
+//! totally ditches the safety of petgraph, because things just panic if you try
+//! and access a node/edge that doesn't exist. This is bad for a big library,
+//! but it saves a *lot* of unwraps.
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+use crate::frozen::FrozenGraph;
 
 pub type NodeInd = usize;
 pub type EdgeInd = usize;
@@ -15,7 +21,7 @@ pub trait GraphType {
     fn is_directed() -> bool;
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 /// A directed graph.
 pub enum Directed {}
 
@@ -25,6 +31,7 @@ impl GraphType for Directed {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 /// An undirected graph.
 pub enum Undirected {}
 
@@ -47,49 +54,108 @@ pub struct Edge<E> {
     pub data: E,
 }
 
-/// Graph base trait. N is the node data, E is the edge data. Ty is the type of graph.
-pub trait GraphBase<N, E, Ty: GraphType> {
-    /// Get the data for a specific node.
-    fn node(&self, n: &NodeInd) -> &N;
+/// Edge data that has a weight, for algorithms that need one and don't want a
+/// closure to get it.
+pub trait Weighted {
+    /// The weight.
+    fn weight(&self) -> f64;
+}
 
-    /// Get the data for a specific edge.
-    fn edge(&self, e: &EdgeInd) -> &Edge<E>;
+macro_rules! impl_weighted {
+    ($($t:ty),*) => {
+        $(
+            impl Weighted for $t {
+                fn weight(&self) -> f64 {
+                    *self as f64
+                }
+            }
+        )*
+    };
+}
 
-    /// Get the data for a specific node mutably.
-    fn node_mut(&mut self, n: &NodeInd) -> &mut N;
+impl_weighted!(f64, f32, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
 
-    /// Get the data for a specific edge mutably.
-    fn edge_mut(&mut self, e: &EdgeInd) -> &mut Edge<E>;
+/// The number of edges removed by [`GraphBase::simplify`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SimplifyReport {
+    /// The number of self-loops removed.
+    pub self_loops: usize,
+    /// The number of parallel edges removed.
+    pub parallel_edges: usize,
+}
 
-    /// Add a node with given data. Returns the new index.
-    fn add_node(&mut self, data: N) -> NodeInd;
+/// The error for when adding an edge to a directed graph would make a cycle in
+/// a graph that's meant to stay acyclic.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WouldCreateCycle;
 
-    /// Adds an edge with the given data, connecting the two nodes. Returns the index for that edge.
-    fn add_edge(&mut self, start: &NodeInd, end: &NodeInd, data: E) -> EdgeInd;
+impl fmt::Display for WouldCreateCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "edge would create a cycle")
+    }
+}
 
-    /// Removes an edge with the given index. Returns the data with that edge.
-    fn remove_edge(&mut self, e: &EdgeInd) -> Edge<E>;
+impl Error for WouldCreateCycle {}
 
-    // Gets all of the nodes.
+impl<E> Edge<E> {
+    /// Gets the endpoint of the edge opposite to `n`. For a self-loop, this is
+    /// just `n`.
+    pub fn opposite(&self, n: &NodeInd) -> NodeInd {
+        if &self.start == n {
+            self.end
+        } else {
+            self.start
+        }
+    }
+}
+
+/// The read-only part of a graph: looking up nodes and edges and going
+/// through them, but never changing anything. Algorithms that only read a
+/// graph take this, so they work on a [`FrozenGraph`] too.
+pub trait GraphView<N, E, Ty: GraphType> {
+    /// Get the data for a specific node.
+    fn node(&self, n: &NodeInd) -> &N;
+
+    /// Get the data for a specific edge.
+    fn edge(&self, e: &EdgeInd) -> &Edge<E>;
+
+    /// Gets all of the nodes.
     fn nodes(&self) -> Box<dyn Iterator<Item = NodeInd>>;
 
     /// Gets all of the edges.
     fn edges(&self) -> Box<dyn Iterator<Item = EdgeInd>>;
 
+    /// Gets all of the nodes in ascending order of index. Unlike
+    /// [`nodes`](GraphView::nodes), the order doesn't depend on how the graph
+    /// stores them, so it's the same every time.
+    fn nodes_sorted(&self) -> Vec<NodeInd> {
+        let mut nodes: Vec<NodeInd> = self.nodes().collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    /// Gets all of the edges in ascending order of index, like
+    /// [`nodes_sorted`](GraphView::nodes_sorted).
+    fn edges_sorted(&self) -> Vec<EdgeInd> {
+        let mut edges: Vec<EdgeInd> = self.edges().collect();
+        edges.sort_unstable();
+        edges
+    }
+
     /// Gets all of the edges from a specific node, as an iterator. For
     /// undirected graphs, this is all edges incident on the node: for directed
     /// graphs, only the edges going out from this node.
-    fn edges_from(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>>>;
+    fn edges_from(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_>;
 
     /// Gets all of the edges to a specific node, as an iterator. For
     /// undirected graphs, this is all edges incident on the node: for directed
-    /// graphs, only the edges going from from this node.
-    fn edges_to(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>>>;
+    /// graphs, only the edges going into this node.
+    fn edges_to(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_>;
 
     /// Gets all of the edges at a specific node, as an iterator. For
     /// undirected graphs, this is all edges incident on the node. For directed graphs,
     /// it is the edges going from and the edges going to this node.
-    fn edges_at(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>>>;
+    fn edges_at(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_>;
 
     /// Get the nodes connected by the edge as a tuple (start, end).
     fn edge_endpoints(&self, e: &EdgeInd) -> (NodeInd, NodeInd);
@@ -112,21 +178,297 @@ pub trait GraphBase<N, E, Ty: GraphType> {
     /// Gets the nodes that the given node has an edge going towards, if
     /// directed, or any node connected by an edge if undirected.
     fn neighbors<'a>(&'a self, n: &'a NodeInd) -> Box<dyn Iterator<Item = NodeInd> + 'a> {
-        if self.is_directed() {
-            return Box::new(
-                self.edges_from(n)
-                    .map(|e| self.edge_start(&e.index))
-                    .collect::<Vec<NodeInd>>()
-                    .into_iter(),
-            );
-        } else {
-            return Box::new(
-                self.edges_at(n)
-                    .map(|e| self.edge_endpoints(&e.index))
-                    .map(move |(start, end)| if &start == n { end } else { start })
-                    .collect::<Vec<NodeInd>>()
-                    .into_iter(),
-            );
-        };
+        Box::new(
+            self.edges_from(n)
+                .map(|e| e.opposite(n))
+                .collect::<Vec<NodeInd>>()
+                .into_iter(),
+        )
+    }
+
+    /// Like [`neighbors`](GraphView::neighbors), but yields each neighbor only
+    /// once, in the order they're first found, even if parallel edges connect
+    /// it to the given node more than once.
+    fn distinct_neighbors<'a>(&'a self, n: &'a NodeInd) -> Box<dyn Iterator<Item = NodeInd> + 'a> {
+        let mut seen = HashSet::new();
+        Box::new(self.neighbors(n).filter(move |m| seen.insert(*m)))
+    }
+
+    /// Like [`neighbors`](GraphView::neighbors), but also yields the index of
+    /// the edge connecting each neighbor to the given node.
+    fn neighbors_with_edges<'a>(
+        &'a self,
+        n: &'a NodeInd,
+    ) -> Box<dyn Iterator<Item = (NodeInd, EdgeInd)> + 'a> {
+        Box::new(
+            self.edges_from(n)
+                .map(|e| (e.opposite(n), e.index))
+                .collect::<Vec<(NodeInd, EdgeInd)>>()
+                .into_iter(),
+        )
+    }
+
+    /// Folds `f` over the edges from a node, as in
+    /// [`edges_from`](GraphView::edges_from), passing along the neighbor at the
+    /// other end of each edge. This is the usual building block for message
+    /// passing: for example, summing up the weights of a node's edges.
+    fn fold_neighbors<A, F>(&self, n: &NodeInd, init: A, mut f: F) -> A
+    where
+        F: FnMut(A, NodeInd, &Edge<E>) -> A,
+    {
+        self.edges_from(n)
+            .fold(init, |acc, edge| f(acc, edge.opposite(n), &edge))
+    }
+
+    /// Runs one synchronous round of message passing: every node in `init`
+    /// sends `message` of its value along each edge to the node at the other
+    /// end, and then every node combines what it got into its new value with
+    /// `aggregate`, which is given the node and its messages. Messages follow
+    /// edge direction, so for directed graphs a node hears from the starts of
+    /// the edges going into it. Nodes missing from `init` send nothing, but
+    /// every node gets a new value, even if it got no messages.
+    ///
+    /// One round of this can be a step of PageRank, label propagation, or a
+    /// GNN layer.
+    fn propagate<M, F, G>(
+        &self,
+        init: &HashMap<NodeInd, M>,
+        mut message: F,
+        mut aggregate: G,
+    ) -> HashMap<NodeInd, M>
+    where
+        F: FnMut(&M, &Edge<E>) -> M,
+        G: FnMut(NodeInd, Vec<M>) -> M,
+    {
+        self.nodes_sorted()
+            .into_iter()
+            .map(|n| {
+                let messages: Vec<M> = self
+                    .edges_to(&n)
+                    .filter_map(|edge| init.get(&edge.opposite(&n)).map(|m| message(m, &edge)))
+                    .collect();
+                (n, aggregate(n, messages))
+            })
+            .collect()
+    }
+
+    /// Gets all of the edges as (start, end, data) tuples.
+    fn edge_references<'a>(&'a self) -> Box<dyn Iterator<Item = (NodeInd, NodeInd, &'a E)> + 'a>
+    where
+        E: 'a,
+    {
+        Box::new(self.edges().map(move |e| {
+            let edge = self.edge(&e);
+            (edge.start, edge.end, &edge.data)
+        }))
+    }
+
+    /// Gets all of the nodes as (index, data) tuples.
+    fn node_references<'a>(&'a self) -> Box<dyn Iterator<Item = (NodeInd, &'a N)> + 'a>
+    where
+        N: 'a,
+    {
+        Box::new(self.nodes().map(move |n| (n, self.node(&n))))
+    }
+
+    /// Gets a read-only view of the graph.
+    fn freeze(&self) -> FrozenGraph<'_, Self>
+    where
+        Self: Sized,
+    {
+        FrozenGraph::new(self)
+    }
+}
+
+/// Graph base trait. N is the node data, E is the edge data. Ty is the type of graph.
+/// This is a [`GraphView`] that can also be changed.
+pub trait GraphBase<N, E, Ty: GraphType>: GraphView<N, E, Ty> {
+    /// Get the data for a specific node mutably.
+    fn node_mut(&mut self, n: &NodeInd) -> &mut N;
+
+    /// Get the data for a specific edge mutably.
+    fn edge_mut(&mut self, e: &EdgeInd) -> &mut Edge<E>;
+
+    /// Add a node with given data. Returns the new index.
+    fn add_node(&mut self, data: N) -> NodeInd;
+
+    /// Adds an edge with the given data, connecting the two nodes. Returns the index for that edge.
+    fn add_edge(&mut self, start: &NodeInd, end: &NodeInd, data: E) -> EdgeInd;
+
+    /// Removes an edge with the given index. Returns the data with that edge.
+    fn remove_edge(&mut self, e: &EdgeInd) -> Edge<E>;
+
+    /// Removes a node with the given index, along with every edge incident on
+    /// it. Returns the data for that node.
+    fn remove_node(&mut self, n: &NodeInd) -> N;
+
+    /// Contracts `merge` into `keep`: every edge incident on `merge` is moved
+    /// to `keep` instead, and then `merge` is removed. Edges between the two
+    /// nodes become self-loops on `keep`, which are removed if
+    /// `drop_self_loops` is true. Moved edges are given new indices.
+    fn contract_nodes(&mut self, keep: &NodeInd, merge: &NodeInd, drop_self_loops: bool) {
+        let mut moved: Vec<EdgeInd> = self.edges_at(merge).map(|e| e.index).collect();
+        // self-loops show up twice
+        moved.sort_unstable();
+        moved.dedup();
+
+        let relabel = |n: NodeInd| if &n == merge { *keep } else { n };
+        for e in moved {
+            let edge = self.remove_edge(&e);
+            let (start, end) = (relabel(edge.start), relabel(edge.end));
+            if !(drop_self_loops && start == end) {
+                self.add_edge(&start, &end, edge.data);
+            }
+        }
+        self.remove_node(merge);
+    }
+
+    /// Removes every edge going from a node to itself. Returns the number of
+    /// edges removed.
+    fn remove_self_loops(&mut self) -> usize {
+        let loops: Vec<EdgeInd> = self
+            .edges()
+            .filter(|e| {
+                let (start, end) = self.edge_endpoints(e);
+                start == end
+            })
+            .collect();
+        for e in &loops {
+            self.remove_edge(e);
+        }
+        loops.len()
+    }
+
+    /// Collapses every group of parallel edges into the one with the smallest
+    /// index, whose data becomes the result of folding `combine` over the
+    /// group's data in index order. For directed graphs, only edges going in
+    /// the same direction are parallel. Returns the number of edges removed.
+    fn remove_parallel_edges<F: FnMut(&E, &E) -> E>(&mut self, mut combine: F) -> usize
+    where
+        Self: Sized,
+    {
+        let mut edges: Vec<EdgeInd> = self.edges().collect();
+        edges.sort_unstable();
+
+        let mut kept: HashMap<(NodeInd, NodeInd), EdgeInd> = HashMap::new();
+        let mut removed = 0;
+        for e in edges {
+            let (start, end) = self.edge_endpoints(&e);
+            let key = if self.is_directed() {
+                (start, end)
+            } else {
+                (start.min(end), start.max(end))
+            };
+            match kept.get(&key) {
+                Some(keep) => {
+                    let edge = self.remove_edge(&e);
+                    let combined = combine(&self.edge(keep).data, &edge.data);
+                    self.edge_mut(keep).data = combined;
+                    removed += 1;
+                }
+                None => {
+                    kept.insert(key, e);
+                }
+            }
+        }
+        removed
+    }
+
+    /// Merges parallel edges into one whose data combines theirs, for example by
+    /// summing the weights of a weighted multigraph. This is
+    /// [`remove_parallel_edges`](GraphBase::remove_parallel_edges) without the
+    /// count, so edges are parallel if they have the same start and end, in
+    /// either order for undirected graphs.
+    fn merge_parallel_edges<F: Fn(&E, &E) -> E>(&mut self, combine: F)
+    where
+        Self: Sized,
+    {
+        self.remove_parallel_edges(combine);
+    }
+
+    /// Makes the graph simple by removing self-loops and collapsing parallel
+    /// edges, using `combine` to merge their data as in
+    /// [`remove_parallel_edges`](GraphBase::remove_parallel_edges). Returns a
+    /// report of how many edges were removed.
+    fn simplify<F: FnMut(&E, &E) -> E>(&mut self, combine: F) -> SimplifyReport
+    where
+        Self: Sized,
+    {
+        let self_loops = self.remove_self_loops();
+        let parallel_edges = self.remove_parallel_edges(combine);
+        SimplifyReport {
+            self_loops,
+            parallel_edges,
+        }
+    }
+
+    /// Subdivides an edge (u, v): removes it, adds a new node w with the given
+    /// data, and connects u to w and w to v with edges that each have a copy of
+    /// `edge_data`. Returns w.
+    fn subdivide_edge(&mut self, e: &EdgeInd, node_data: N, edge_data: E) -> NodeInd
+    where
+        E: Clone,
+    {
+        let edge = self.remove_edge(e);
+        let mid = self.add_node(node_data);
+        self.add_edge(&edge.start, &mid, edge_data.clone());
+        self.add_edge(&mid, &edge.end, edge_data);
+        mid
+    }
+
+    /// Smooths out every node that's just a stop along the way, the inverse of
+    /// [`subdivide_edge`](GraphBase::subdivide_edge): a node with exactly two
+    /// edges, u - w and w - v, is removed and the edges are merged into one
+    /// edge u - v whose data is `combine` of the two edges' data, lower edge
+    /// index first. For directed graphs, this is a node with one edge u -> w in
+    /// and one edge w -> v out, giving u -> v. Nodes with a self-loop are left
+    /// alone. Returns the number of nodes removed.
+    fn smooth<F: FnMut(&E, &E) -> E>(&mut self, mut combine: F) -> usize
+    where
+        Self: Sized,
+    {
+        let mut nodes: Vec<NodeInd> = self.nodes().collect();
+        nodes.sort_unstable();
+
+        // merging the edges at a node doesn't change any other node's degree,
+        // so a single pass removes everything
+        let mut removed = 0;
+        for n in nodes {
+            let (first, second) = if self.is_directed() {
+                let ins: Vec<Edge<E>> = self.edges_to(&n).collect();
+                let outs: Vec<Edge<E>> = self.edges_from(&n).collect();
+                match (ins.as_slice(), outs.as_slice()) {
+                    ([i], [o]) if i.index != o.index => (i.index, o.index),
+                    _ => continue,
+                }
+            } else {
+                let edges: Vec<EdgeInd> = self.edges_from(&n).map(|e| e.index).collect();
+                match edges.as_slice() {
+                    [a, b] if a != b => (*a.min(b), *a.max(b)),
+                    _ => continue,
+                }
+            };
+            let first = self.remove_edge(&first);
+            let second = self.remove_edge(&second);
+            let data = combine(&first.data, &second.data);
+            // for directed graphs, the first edge is the one coming in
+            let (start, end) = (first.opposite(&n), second.opposite(&n));
+            self.remove_node(&n);
+            self.add_edge(&start, &end, data);
+            removed += 1;
+        }
+        removed
+    }
+
+    /// Transforms the data of every edge in place, in ascending order of edge
+    /// index, for example to normalize the weights. The structure of the
+    /// graph doesn't change.
+    fn scale_edge_weights<F: FnMut(&mut E)>(&mut self, mut f: F)
+    where
+        Self: Sized,
+    {
+        for e in self.edges_sorted() {
+            f(&mut self.edge_mut(&e).data);
+        }
     }
 }
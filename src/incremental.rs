@@ -0,0 +1,580 @@
+//! Structures that keep answers about a graph up to date as it changes, so
+//! queries don't have to search the whole graph again.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+
+use crate::adj_list_graph::ALGraph;
+use crate::graph_base::{
+    Directed, EdgeInd, GraphBase, GraphType, GraphView, NodeInd, WouldCreateCycle,
+};
+use crate::union_find::UnionFind;
+
+/// A graph that keeps track of which nodes are connected to each other as
+/// edges are added, using a [`UnionFind`], so [`connected`] takes nearly
+/// constant time instead of a BFS. Edge direction is ignored, so for directed
+/// graphs this is weak connectivity.
+///
+/// Union-find can only merge sets, not split them, so there's no way to remove
+/// edges or nodes: it dereferences to the inner graph for everything that
+/// takes `&self`, but only has its own methods for adding to it. Use
+/// [`into_inner`](ConnectivityTracker::into_inner) to get the graph back out if
+/// it needs other changes, and make a new tracker afterwards. For forests,
+/// [`DynamicForest`] can remove edges too.
+///
+/// [`connected`]: ConnectivityTracker::connected
+#[derive(Clone, Debug)]
+pub struct ConnectivityTracker<N, E, Ty: GraphType> {
+    graph: ALGraph<N, E, Ty>,
+    components: UnionFind,
+}
+
+impl<N, E: Clone, Ty: GraphType> ConnectivityTracker<N, E, Ty> {
+    /// Makes a tracker for a new, empty graph.
+    pub fn new() -> Self {
+        Self::from_graph(ALGraph::new())
+    }
+
+    /// Starts tracking the connectivity of an existing graph.
+    pub fn from_graph(graph: ALGraph<N, E, Ty>) -> Self {
+        let mut components = UnionFind::new();
+        for e in graph.edges() {
+            let (start, end) = graph.edge_endpoints(&e);
+            components.union(start, end);
+        }
+        Self { graph, components }
+    }
+
+    /// Adds a node with the given data, which starts out connected only to
+    /// itself. Returns the new index.
+    pub fn add_node(&mut self, data: N) -> NodeInd {
+        self.graph.add_node(data)
+    }
+
+    /// Adds an edge with the given data, connecting everything connected to
+    /// `start` with everything connected to `end`. Returns the new index.
+    pub fn add_edge(&mut self, start: &NodeInd, end: &NodeInd, data: E) -> EdgeInd {
+        self.components.union(*start, *end);
+        self.graph.add_edge(start, end, data)
+    }
+
+    /// Whether there's a path between `a` and `b`, ignoring edge direction.
+    pub fn connected(&mut self, a: &NodeInd, b: &NodeInd) -> bool {
+        self.components.connected(*a, *b)
+    }
+
+    /// Stops tracking connectivity, giving the graph back.
+    pub fn into_inner(self) -> ALGraph<N, E, Ty> {
+        self.graph
+    }
+}
+
+impl<N, E: Clone, Ty: GraphType> Default for ConnectivityTracker<N, E, Ty> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, E, Ty: GraphType> Deref for ConnectivityTracker<N, E, Ty> {
+    type Target = ALGraph<N, E, Ty>;
+
+    fn deref(&self) -> &ALGraph<N, E, Ty> {
+        &self.graph
+    }
+}
+
+impl<N, E: Clone, Ty: GraphType> ALGraph<N, E, Ty> {
+    /// Wraps the graph in a [`ConnectivityTracker`], so connectivity queries
+    /// stay fast as edges are added.
+    pub fn track_connectivity(self) -> ConnectivityTracker<N, E, Ty> {
+        ConnectivityTracker::from_graph(self)
+    }
+}
+
+/// A directed acyclic graph that keeps a topological order of its nodes as
+/// edges are added, using the Pearce-Kelly algorithm, so [`is_before`] takes
+/// constant time. Adding an edge that goes forwards in the order is constant
+/// time too, and one that goes backwards only has to search and reorder the
+/// nodes between its ends in the order. Edges that would make a cycle are
+/// refused.
+///
+/// Like [`ConnectivityTracker`], this dereferences to the inner graph for
+/// everything that takes `&self`, and only has its own methods for adding to
+/// it.
+///
+/// [`is_before`]: IncrementalTopoOrder::is_before
+#[derive(Clone, Debug)]
+pub struct IncrementalTopoOrder<N, E> {
+    graph: ALGraph<N, E, Directed>,
+    /// The position of each node in the order. These are distinct, but don't
+    /// have to be consecutive.
+    position: HashMap<NodeInd, usize>,
+    /// The position the next node added gets.
+    next_position: usize,
+}
+
+impl<N, E: Clone> IncrementalTopoOrder<N, E> {
+    /// Makes a tracker for a new, empty graph.
+    pub fn new() -> Self {
+        Self {
+            graph: ALGraph::new(),
+            position: HashMap::new(),
+            next_position: 0,
+        }
+    }
+
+    /// Starts keeping a topological order of an existing graph, or returns an
+    /// error if it has a cycle.
+    pub fn from_graph(graph: ALGraph<N, E, Directed>) -> Result<Self, WouldCreateCycle> {
+        // Kahn's algorithm
+        let mut in_degree: HashMap<NodeInd, usize> = graph
+            .nodes()
+            .map(|n| (n, graph.edges_to(&n).count()))
+            .collect();
+        let mut ready: Vec<NodeInd> = graph
+            .nodes_sorted()
+            .into_iter()
+            .filter(|n| in_degree[n] == 0)
+            .rev()
+            .collect();
+        let mut position = HashMap::new();
+        while let Some(n) = ready.pop() {
+            position.insert(n, position.len());
+            for next in graph.neighbors(&n) {
+                let d = in_degree.get_mut(&next).unwrap();
+                *d -= 1;
+                if *d == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+        if position.len() < in_degree.len() {
+            return Err(WouldCreateCycle);
+        }
+        Ok(Self {
+            next_position: position.len(),
+            graph,
+            position,
+        })
+    }
+
+    /// Adds a node with the given data, at the end of the order. Returns the
+    /// new index.
+    pub fn add_node(&mut self, data: N) -> NodeInd {
+        let n = self.graph.add_node(data);
+        self.position.insert(n, self.next_position);
+        self.next_position += 1;
+        n
+    }
+
+    /// Adds an edge with the given data, moving nodes around in the order if
+    /// it has to, unless `start` can already be reached from `end`, in which
+    /// case the edge would make a cycle and nothing changes. Returns the new
+    /// index.
+    pub fn add_edge(
+        &mut self,
+        start: &NodeInd,
+        end: &NodeInd,
+        data: E,
+    ) -> Result<EdgeInd, WouldCreateCycle> {
+        let (lower, upper) = (self.position[end], self.position[start]);
+        if start == end {
+            return Err(WouldCreateCycle);
+        }
+        if lower < upper {
+            // everything that end reaches and that comes before start has to
+            // move after everything that reaches start and comes after end
+            let forward = self.search(end, true, |p| p <= upper);
+            if forward.contains(start) {
+                return Err(WouldCreateCycle);
+            }
+            let backward = self.search(start, false, |p| p >= lower);
+
+            let mut positions: Vec<usize> = backward
+                .iter()
+                .chain(&forward)
+                .map(|n| self.position[n])
+                .collect();
+            positions.sort_unstable();
+            for (n, p) in backward.into_iter().chain(forward).zip(positions) {
+                self.position.insert(n, p);
+            }
+        }
+        Ok(self.graph.add_edge(start, end, data))
+    }
+
+    /// Finds the nodes that can be reached from `from`, or that can reach it if
+    /// `forward` is false, going only through nodes whose positions pass
+    /// `keep`. Returns them in order of their positions.
+    fn search<F: Fn(usize) -> bool>(&self, from: &NodeInd, forward: bool, keep: F) -> Vec<NodeInd> {
+        let mut found = HashSet::from([*from]);
+        let mut stack = vec![*from];
+        while let Some(curr) = stack.pop() {
+            let edges = if forward {
+                self.graph.edges_from(&curr)
+            } else {
+                self.graph.edges_to(&curr)
+            };
+            for edge in edges {
+                let next = edge.opposite(&curr);
+                if keep(self.position[&next]) && found.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        let mut found: Vec<NodeInd> = found.into_iter().collect();
+        found.sort_unstable_by_key(|n| self.position[n]);
+        found
+    }
+
+    /// Whether `a` comes before `b` in the order. If there's a path from `a`
+    /// to `b`, this is always true, but it can be true without one.
+    pub fn is_before(&self, a: &NodeInd, b: &NodeInd) -> bool {
+        self.position[a] < self.position[b]
+    }
+
+    /// Gets every node in the topological order.
+    pub fn order(&self) -> Vec<NodeInd> {
+        let mut nodes: Vec<NodeInd> = self.position.keys().copied().collect();
+        nodes.sort_unstable_by_key(|n| self.position[n]);
+        nodes
+    }
+
+    /// Stops keeping the order, giving the graph back.
+    pub fn into_inner(self) -> ALGraph<N, E, Directed> {
+        self.graph
+    }
+}
+
+impl<N, E: Clone> Default for IncrementalTopoOrder<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, E> Deref for IncrementalTopoOrder<N, E> {
+    type Target = ALGraph<N, E, Directed>;
+
+    fn deref(&self) -> &ALGraph<N, E, Directed> {
+        &self.graph
+    }
+}
+
+impl<N, E: Clone> ALGraph<N, E, Directed> {
+    /// Wraps the graph in an [`IncrementalTopoOrder`], so it keeps a
+    /// topological order as edges are added, or returns an error if it already
+    /// has a cycle.
+    pub fn track_topo_order(self) -> Result<IncrementalTopoOrder<N, E>, WouldCreateCycle> {
+        IncrementalTopoOrder::from_graph(self)
+    }
+}
+
+/// A node of the splay trees in a [`DynamicForest`].
+#[derive(Clone, Copy, Debug, Default)]
+struct ForestNode {
+    /// The parent in the splay tree, or for the root of a splay tree, the node
+    /// above its path in the forest.
+    parent: Option<NodeInd>,
+    /// The left and right children in the splay tree.
+    children: [Option<NodeInd>; 2],
+    /// Whether the children of every node in this subtree have to be swapped.
+    flipped: bool,
+}
+
+/// A forest that can have edges added and removed, which keeps track of which
+/// nodes are connected, using link-cut trees. [`link`], [`cut`] and
+/// [`connected`] all take O(log n) amortized time. Unlike
+/// [`ConnectivityTracker`], this handles removing edges, but only for forests:
+/// linking two nodes that are already connected would make a cycle, so it's
+/// refused. Every node starts out on its own, and nodes don't need to be added
+/// before they're used.
+///
+/// [`link`]: DynamicForest::link
+/// [`cut`]: DynamicForest::cut
+/// [`connected`]: DynamicForest::connected
+#[derive(Clone, Debug, Default)]
+pub struct DynamicForest {
+    nodes: Vec<ForestNode>,
+}
+
+impl DynamicForest {
+    /// Makes a new forest with every node on its own.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an edge between `a` and `b`. Returns `false`, without changing
+    /// anything, if they were already connected.
+    pub fn link(&mut self, a: NodeInd, b: NodeInd) -> bool {
+        if self.connected(a, b) {
+            return false;
+        }
+        self.make_root(a);
+        self.nodes[a].parent = Some(b);
+        true
+    }
+
+    /// Removes the edge between `a` and `b`. Returns `false` if there wasn't
+    /// one.
+    pub fn cut(&mut self, a: NodeInd, b: NodeInd) -> bool {
+        if a == b || !self.connected(a, b) {
+            return false;
+        }
+        self.make_root(a);
+        self.access(b);
+        // a is the root, so it's adjacent to b exactly when it's the only
+        // thing before b on the path
+        self.push(a);
+        if self.nodes[b].children[0] != Some(a) || self.nodes[a].children[1].is_some() {
+            return false;
+        }
+        self.nodes[b].children[0] = None;
+        self.nodes[a].parent = None;
+        true
+    }
+
+    /// Whether there's a path between `a` and `b`.
+    pub fn connected(&mut self, a: NodeInd, b: NodeInd) -> bool {
+        a == b || self.find_root(a) == self.find_root(b)
+    }
+
+    /// Makes sure there's room for `n`.
+    fn ensure(&mut self, n: NodeInd) {
+        if self.nodes.len() <= n {
+            self.nodes.resize(n + 1, ForestNode::default());
+        }
+    }
+
+    /// Whether `n` is the root of its splay tree.
+    fn is_splay_root(&self, n: NodeInd) -> bool {
+        match self.nodes[n].parent {
+            Some(p) => !self.nodes[p].children.contains(&Some(n)),
+            None => true,
+        }
+    }
+
+    /// Applies a pending flip at `n` to its children.
+    fn push(&mut self, n: NodeInd) {
+        if self.nodes[n].flipped {
+            self.nodes[n].flipped = false;
+            self.nodes[n].children.swap(0, 1);
+            for c in self.nodes[n].children.into_iter().flatten() {
+                self.nodes[c].flipped ^= true;
+            }
+        }
+    }
+
+    /// Rotates `n` above its parent in the splay tree.
+    fn rotate(&mut self, n: NodeInd) {
+        let p = self.nodes[n].parent.unwrap();
+        let grandparent = self.nodes[p].parent;
+        let dir = (self.nodes[p].children[1] == Some(n)) as usize;
+        if !self.is_splay_root(p) {
+            let g = grandparent.unwrap();
+            let p_dir = (self.nodes[g].children[1] == Some(p)) as usize;
+            self.nodes[g].children[p_dir] = Some(n);
+        }
+        self.nodes[n].parent = grandparent;
+
+        let moved = self.nodes[n].children[1 - dir];
+        self.nodes[p].children[dir] = moved;
+        if let Some(m) = moved {
+            self.nodes[m].parent = Some(p);
+        }
+        self.nodes[n].children[1 - dir] = Some(p);
+        self.nodes[p].parent = Some(n);
+    }
+
+    /// Moves `n` to the root of its splay tree.
+    fn splay(&mut self, n: NodeInd) {
+        // flips have to be pushed down from the top before anything moves
+        let mut path = vec![n];
+        while !self.is_splay_root(*path.last().unwrap()) {
+            path.push(self.nodes[*path.last().unwrap()].parent.unwrap());
+        }
+        for &m in path.iter().rev() {
+            self.push(m);
+        }
+
+        while !self.is_splay_root(n) {
+            let p = self.nodes[n].parent.unwrap();
+            if !self.is_splay_root(p) {
+                let g = self.nodes[p].parent.unwrap();
+                let zig_zig = (self.nodes[p].children[1] == Some(n))
+                    == (self.nodes[g].children[1] == Some(p));
+                self.rotate(if zig_zig { p } else { n });
+            }
+            self.rotate(n);
+        }
+    }
+
+    /// Makes the path from the root of `n`'s tree to `n` a single splay tree,
+    /// with `n` at its root and nothing after it.
+    fn access(&mut self, n: NodeInd) {
+        self.ensure(n);
+        let mut below: Option<NodeInd> = None;
+        let mut curr = Some(n);
+        while let Some(c) = curr {
+            self.splay(c);
+            self.nodes[c].children[1] = below;
+            below = Some(c);
+            curr = self.nodes[c].parent;
+        }
+        self.splay(n);
+    }
+
+    /// Makes `n` the root of its tree.
+    fn make_root(&mut self, n: NodeInd) {
+        self.access(n);
+        self.nodes[n].flipped ^= true;
+    }
+
+    /// Gets the root of `n`'s tree.
+    fn find_root(&mut self, n: NodeInd) -> NodeInd {
+        self.access(n);
+        let mut root = n;
+        self.push(root);
+        while let Some(c) = self.nodes[root].children[0] {
+            root = c;
+            self.push(root);
+        }
+        self.splay(root);
+        root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::Undirected;
+    use crate::test_util::graph_from_edges;
+    use crate::traversal::can_reach;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_connectivity_tracker() {
+        let mut tracker = graph_from_edges::<Undirected>(6, &[(0, 1), (2, 3)]).track_connectivity();
+        let steps = [(1, 2), (4, 4), (5, 4), (3, 0), (4, 1)];
+        for (start, end) in steps {
+            tracker.add_edge(&start, &end, ());
+            for a in 0..6 {
+                for b in 0..6 {
+                    assert_eq!(tracker.connected(&a, &b), can_reach(&*tracker, &a, &b));
+                }
+            }
+        }
+        let n = tracker.add_node(());
+        assert!(!tracker.connected(&n, &0));
+        assert_eq!(tracker.into_inner().edges().count(), 7);
+    }
+
+    #[test]
+    fn test_connectivity_tracker_directed() {
+        let mut tracker = ConnectivityTracker::<(), (), Directed>::new();
+        for _ in 0..3 {
+            tracker.add_node(());
+        }
+        tracker.add_edge(&1, &0, ());
+        tracker.add_edge(&2, &0, ());
+        // 1 and 2 can't reach each other, but they're weakly connected
+        assert!(tracker.connected(&1, &2));
+    }
+
+    #[test]
+    fn test_dynamic_forest() {
+        let mut forest = DynamicForest::new();
+        assert!(forest.link(0, 1));
+        assert!(forest.link(1, 2));
+        assert!(forest.link(3, 2));
+        assert!(!forest.link(0, 3));
+        assert!(forest.connected(0, 3));
+        assert!(!forest.cut(0, 2));
+        assert!(forest.cut(2, 1));
+        assert!(!forest.connected(0, 3));
+        assert!(forest.connected(0, 1));
+        assert!(forest.connected(2, 3));
+        assert!(!forest.connected(5, 6));
+        assert!(forest.connected(7, 7));
+    }
+
+    #[test]
+    fn test_dynamic_forest_random() {
+        let n = 30;
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut forest = DynamicForest::new();
+        let mut edges: Vec<(NodeInd, NodeInd)> = vec![];
+        for _ in 0..2000 {
+            if !edges.is_empty() && rng.gen_bool(0.4) {
+                let (a, b) = edges.swap_remove(rng.gen_range(0..edges.len()));
+                assert!(forest.cut(b, a));
+            } else {
+                let (a, b) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                let g = graph_from_edges::<Undirected>(n, &edges);
+                let linked = forest.link(a, b);
+                assert_eq!(linked, !can_reach(&g, &a, &b));
+                if linked {
+                    edges.push((a, b));
+                }
+            }
+
+            let g = graph_from_edges::<Undirected>(n, &edges);
+            let (a, b) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            assert_eq!(forest.connected(a, b), can_reach(&g, &a, &b));
+        }
+    }
+
+    /// Checks that every edge goes forwards in the order.
+    fn assert_valid_order(topo: &IncrementalTopoOrder<(), ()>) {
+        let order = topo.order();
+        assert_eq!(order.len(), topo.nodes().count());
+        for e in topo.edges() {
+            let (start, end) = topo.edge_endpoints(&e);
+            assert!(topo.is_before(&start, &end));
+            let pos = |n| order.iter().position(|&m| m == n).unwrap();
+            assert!(pos(start) < pos(end));
+        }
+    }
+
+    #[test]
+    fn test_incremental_topo_order() {
+        let g = graph_from_edges::<Directed>(4, &[(0, 1), (2, 3)]);
+        let mut topo = g.track_topo_order().unwrap();
+        assert_eq!(topo.order(), vec![0, 1, 2, 3]);
+        // going backwards in the order makes 2 and 3 move before 0 and 1
+        assert_eq!(topo.add_edge(&3, &0, ()), Ok(2));
+        assert_eq!(topo.order(), vec![2, 3, 0, 1]);
+        assert_eq!(topo.add_edge(&1, &2, ()), Err(WouldCreateCycle));
+        assert_eq!(topo.add_edge(&1, &1, ()), Err(WouldCreateCycle));
+        assert_eq!(topo.edges().count(), 3);
+        assert_valid_order(&topo);
+
+        let cyclic = graph_from_edges::<Directed>(2, &[(0, 1), (1, 0)]);
+        assert!(cyclic.track_topo_order().is_err());
+    }
+
+    #[test]
+    fn test_incremental_topo_order_random() {
+        let n = 40;
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut topo = IncrementalTopoOrder::new();
+        for _ in 0..n {
+            topo.add_node(());
+        }
+        // edges follow a hidden order, so they never make a cycle, but are
+        // added in an order that keeps moving nodes around
+        let mut hidden: Vec<NodeInd> = (0..n).collect();
+        hidden.shuffle(&mut rng);
+        for _ in 0..300 {
+            let (i, j) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            if i == j {
+                continue;
+            }
+            let (a, b) = (hidden[i.min(j)], hidden[i.max(j)]);
+            assert!(topo.add_edge(&a, &b, ()).is_ok());
+            assert!(topo.add_edge(&b, &a, ()).is_err());
+            assert_valid_order(&topo);
+        }
+    }
+}
@@ -0,0 +1,1221 @@
+//! Reading and writing graphs in other formats.
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+use crate::adj_list_graph::ALGraph;
+use crate::graph_base::{Edge, EdgeInd, GraphBase, GraphType, GraphView, NodeInd, Weighted};
+
+/// The bytes every graph written by [`to_bytes`] starts with: a tag and then
+/// the version of the format.
+const MAGIC: [u8; 4] = *b"GV4\x01";
+
+/// The error for when [`from_bytes`] is given bytes that aren't a valid graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes ended in the middle of something.
+    UnexpectedEnd,
+    /// The bytes don't start with the right header, so they weren't written by
+    /// [`to_bytes`] or were written by a different version of it.
+    BadHeader,
+    /// The graph was directed and an undirected one was asked for, or the
+    /// other way around.
+    WrongGraphType,
+    /// The bytes have the right layout, but something in them is invalid.
+    Invalid(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            DecodeError::BadHeader => write!(f, "not a graph in this format"),
+            DecodeError::WrongGraphType => write!(f, "graph has the wrong direction type"),
+            DecodeError::Invalid(msg) => write!(f, "invalid graph: {msg}"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// The error for when a graph in a text format can't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the problem is on, counting from 1.
+    pub line: usize,
+    /// What the problem is.
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// The error for when a graph that should be a tree or DAG has a cycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HasCycle;
+
+impl fmt::Display for HasCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "graph has a cycle")
+    }
+}
+
+impl Error for HasCycle {}
+
+/// Node or edge data that can be written by [`to_bytes`] and read back by
+/// [`from_bytes`]. Numbers are little-endian, with `usize` and `isize` always
+/// taking 8 bytes, and strings and vectors have their length first.
+pub trait BinaryData: Sized {
+    /// Writes the data to the end of `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Reads the data from the start of `input`, moving it past what was read.
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError>;
+}
+
+/// Takes the first `n` bytes off the front of `input`.
+fn take<'a>(input: &mut &'a [u8], n: usize) -> Result<&'a [u8], DecodeError> {
+    if input.len() < n {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    let (front, rest) = input.split_at(n);
+    *input = rest;
+    Ok(front)
+}
+
+macro_rules! impl_binary_data {
+    ($($t:ty),*) => {
+        $(
+            impl BinaryData for $t {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+                    let bytes = take(input, std::mem::size_of::<$t>())?;
+                    Ok(<$t>::from_le_bytes(bytes.try_into().unwrap()))
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_data!(f64, f32, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl BinaryData for usize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        usize::try_from(u64::decode(input)?)
+            .map_err(|_| DecodeError::Invalid("integer too large for usize".to_string()))
+    }
+}
+
+impl BinaryData for isize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as i64).encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        isize::try_from(i64::decode(input)?)
+            .map_err(|_| DecodeError::Invalid("integer too large for isize".to_string()))
+    }
+}
+
+impl BinaryData for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        match u8::decode(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(DecodeError::Invalid(format!("{b} is not a bool"))),
+        }
+    }
+}
+
+impl BinaryData for () {
+    fn encode(&self, _out: &mut Vec<u8>) {}
+
+    fn decode(_input: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(())
+    }
+}
+
+impl BinaryData for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let len = usize::decode(input)?;
+        let bytes = take(input, len)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| DecodeError::Invalid("string is not UTF-8".to_string()))
+    }
+}
+
+impl<T: BinaryData> BinaryData for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for item in self {
+            item.encode(out);
+        }
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let len = usize::decode(input)?;
+        // don't trust the length for the allocation: it could be garbage
+        let mut items = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            items.push(T::decode(input)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: BinaryData> BinaryData for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.is_some().encode(out);
+        if let Some(item) = self {
+            item.encode(out);
+        }
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        if bool::decode(input)? {
+            Ok(Some(T::decode(input)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<A: BinaryData, B: BinaryData> BinaryData for (A, B) {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+        self.1.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok((A::decode(input)?, B::decode(input)?))
+    }
+}
+
+/// Writes a graph in a compact binary format that [`from_bytes`] can read.
+/// Node and edge indices are kept, along with the indices the next node and
+/// edge added will get, so reading the graph back gives one that behaves
+/// exactly the same, even if nodes or edges had been removed.
+///
+/// The layout is a 4-byte header, a byte that's 1 for directed graphs and 0
+/// for undirected ones, the next node and edge indices, and then the number of
+/// nodes followed by each node's index and data, and the number of edges
+/// followed by each edge's index, start, end, and data. Nodes and edges are in
+/// ascending order of index, so the same graph always gives the same bytes.
+pub fn to_bytes<N: BinaryData, E: BinaryData + Clone, Ty: GraphType>(
+    graph: &ALGraph<N, E, Ty>,
+) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    Ty::is_directed().encode(&mut out);
+    let (next_node, next_edge) = graph.next_indices();
+    next_node.encode(&mut out);
+    next_edge.encode(&mut out);
+
+    let nodes = graph.nodes_sorted();
+    nodes.len().encode(&mut out);
+    for n in nodes {
+        n.encode(&mut out);
+        graph.node(&n).encode(&mut out);
+    }
+    let edges = graph.edges_sorted();
+    edges.len().encode(&mut out);
+    for e in edges {
+        let edge = graph.edge(&e);
+        edge.index.encode(&mut out);
+        edge.start.encode(&mut out);
+        edge.end.encode(&mut out);
+        edge.data.encode(&mut out);
+    }
+    out
+}
+
+/// Reads a graph written by [`to_bytes`]. The graph has to have been written
+/// with the same node and edge data types and the same direction type, and
+/// there can't be anything after it. Since the graph keeps room for every node
+/// index up to the next one, a next node index too large to make room for is
+/// invalid, rather than running out of memory.
+pub fn from_bytes<N: BinaryData, E: BinaryData, Ty: GraphType>(
+    bytes: &[u8],
+) -> Result<ALGraph<N, E, Ty>, DecodeError> {
+    let mut input = bytes;
+    if take(&mut input, MAGIC.len()).map_err(|_| DecodeError::BadHeader)? != MAGIC {
+        return Err(DecodeError::BadHeader);
+    }
+    if bool::decode(&mut input)? != Ty::is_directed() {
+        return Err(DecodeError::WrongGraphType);
+    }
+    let next_node = usize::decode(&mut input)?;
+    let next_edge = usize::decode(&mut input)?;
+    let invalid = |msg: String| Err(DecodeError::Invalid(msg));
+
+    let mut graph = ALGraph::new();
+    // the adjacency lists have a slot for every node index up to the next
+    // one, so a bad header could ask for far more memory than there is
+    if graph.try_reserve_node_indices(next_node).is_err() {
+        return invalid(format!("next node index {next_node} is too large"));
+    }
+    let num_nodes = usize::decode(&mut input)?;
+    let mut nodes: HashSet<NodeInd> = HashSet::new();
+    let mut prev: Option<NodeInd> = None;
+    for _ in 0..num_nodes {
+        let n = usize::decode(&mut input)?;
+        if n >= next_node || prev.is_some_and(|p| p >= n) {
+            return invalid(format!("node {n} is out of order or past the next index"));
+        }
+        let data = N::decode(&mut input)?;
+        graph.insert_node_at(n, data);
+        nodes.insert(n);
+        prev = Some(n);
+    }
+    graph.skip_indices(next_node, 0);
+
+    let num_edges = usize::decode(&mut input)?;
+    let mut prev: Option<EdgeInd> = None;
+    for _ in 0..num_edges {
+        let index = usize::decode(&mut input)?;
+        let start = usize::decode(&mut input)?;
+        let end = usize::decode(&mut input)?;
+        if index >= next_edge || prev.is_some_and(|p| p >= index) {
+            return invalid(format!(
+                "edge {index} is out of order or past the next index"
+            ));
+        }
+        if !nodes.contains(&start) || !nodes.contains(&end) {
+            return invalid(format!("edge {index} goes between missing nodes"));
+        }
+        let data = E::decode(&mut input)?;
+        graph.insert_edge_at(Edge {
+            start,
+            end,
+            index,
+            data,
+        });
+        prev = Some(index);
+    }
+    graph.skip_indices(next_node, next_edge);
+
+    if !input.is_empty() {
+        return invalid(format!("{} bytes left over", input.len()));
+    }
+    Ok(graph)
+}
+
+/// Splits a line of CSV into its fields. Fields can be quoted with `"`, in
+/// which case they can have commas in them, and `""` stands for a single `"`.
+fn csv_fields(line: &str, line_num: usize) -> Result<Vec<String>, ParseError> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(ParseError::new(line_num, "unclosed quote"));
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// The fields of a row of a table, by column name.
+pub type Attributes = HashMap<String, String>;
+
+/// A CSV table: the names of the columns, and each row with its line number.
+struct CsvTable {
+    header: Vec<String>,
+    rows: Vec<(usize, Attributes)>,
+}
+
+/// Reads a CSV table with a header and at least `min_columns` columns. Blank
+/// lines are skipped.
+fn parse_csv(table: &str, min_columns: usize) -> Result<CsvTable, ParseError> {
+    let mut lines = table
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty());
+    let Some((header_num, header)) = lines.next() else {
+        return Err(ParseError::new(1, "missing header"));
+    };
+    let header = csv_fields(header, header_num)?;
+    if header.len() < min_columns {
+        return Err(ParseError::new(
+            header_num,
+            format!("expected at least {min_columns} columns"),
+        ));
+    }
+
+    let rows = lines
+        .map(|(num, line)| {
+            let fields = csv_fields(line, num)?;
+            if fields.len() != header.len() {
+                return Err(ParseError::new(
+                    num,
+                    format!("expected {} fields, found {}", header.len(), fields.len()),
+                ));
+            }
+            Ok((num, header.iter().cloned().zip(fields).collect()))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(CsvTable { header, rows })
+}
+
+/// Reads a graph from a table of nodes and a table of edges in CSV, as
+/// exported by Gephi or pandas. The node table's first column is the id of
+/// each node, and the edge table's first two columns are the ids of the source
+/// and target of each edge: the other columns can be anything. Every table
+/// needs a header, which gives the names of the columns.
+///
+/// Each node's data maps the column names of the node table to its fields,
+/// including its id, and the same goes for each edge and the edge table. Nodes
+/// are added in the order of the node table, so the first row gets index 0,
+/// and so on. Ids in the edge table that aren't in the node table get new
+/// nodes with only the id column, named after the node table's first column,
+/// after all of the others. Node ids have to be unique.
+///
+/// Fields can be quoted with `"`, with `""` standing for a `"` inside quotes,
+/// but can't have line breaks in them.
+pub fn from_csv<Ty: GraphType>(
+    nodes_csv: &str,
+    edges_csv: &str,
+) -> Result<ALGraph<Attributes, Attributes, Ty>, ParseError> {
+    let nodes = parse_csv(nodes_csv, 1)?;
+    let edges = parse_csv(edges_csv, 2)?;
+    let id_col = &nodes.header[0];
+    let (source_col, target_col) = (&edges.header[0], &edges.header[1]);
+
+    let mut graph = ALGraph::new();
+    let mut ids: HashMap<String, NodeInd> = HashMap::new();
+    for (num, row) in nodes.rows {
+        let id = row[id_col].clone();
+        if ids.contains_key(&id) {
+            return Err(ParseError::new(num, format!("duplicate node id {id:?}")));
+        }
+        ids.insert(id, graph.add_node(row));
+    }
+
+    for (_, row) in edges.rows {
+        let mut endpoint = |col: &String| {
+            let id = &row[col];
+            *ids.entry(id.clone())
+                .or_insert_with(|| graph.add_node(HashMap::from([(id_col.clone(), id.clone())])))
+        };
+        let (source, target) = (endpoint(source_col), endpoint(target_col));
+        graph.add_edge(&source, &target, row);
+    }
+    Ok(graph)
+}
+
+/// Writes a graph as an adjacency list that networkx's `read_adjlist` can
+/// read: a line for each node, in ascending order of index, with the node and
+/// then its neighbors, separated by spaces. For directed graphs, the neighbors
+/// are the ends of the node's outgoing edges. For undirected graphs, each edge
+/// is only written once, on the line of whichever end comes first, so later
+/// nodes might have only themselves on their lines, like isolated nodes do.
+/// Parallel edges repeat the neighbor, in ascending order of edge index.
+pub fn to_adjlist<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> String {
+    let mut out = String::new();
+    for n in graph.nodes_sorted() {
+        let mut edges: Vec<(EdgeInd, NodeInd)> = graph
+            .neighbors_with_edges(&n)
+            .map(|(m, e)| (e, m))
+            .filter(|&(_, m)| graph.is_directed() || m >= n)
+            .collect();
+        edges.sort_unstable();
+        // undirected self-loops show up twice
+        edges.dedup();
+        out.push_str(&n.to_string());
+        for (_, m) in edges {
+            out.push(' ');
+            out.push_str(&m.to_string());
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Reads a graph from an adjacency list, like the ones networkx's
+/// `write_adjlist` and [`to_adjlist`] write. Each line has a node, and then
+/// the nodes it has edges to, separated by whitespace. Anything after a `#` is
+/// a comment, and blank lines are skipped. Nodes can be named anything without
+/// whitespace in it: they get indices in the order their lines come in, and
+/// then nodes that only show up as neighbors get indices in the order they're
+/// found. For undirected graphs, every neighbor on a line is one edge, so an
+/// edge written on both of its ends' lines becomes two parallel edges.
+pub fn from_adjlist<Ty: GraphType>(adjlist: &str) -> ALGraph<(), (), Ty> {
+    let lines: Vec<Vec<&str>> = adjlist
+        .lines()
+        .map(|line| line.split('#').next().unwrap().split_whitespace().collect())
+        .filter(|tokens: &Vec<&str>| !tokens.is_empty())
+        .collect();
+
+    let mut graph = ALGraph::new();
+    let mut ids: HashMap<&str, NodeInd> = HashMap::new();
+    for line in &lines {
+        ids.entry(line[0]).or_insert_with(|| graph.add_node(()));
+    }
+    for line in &lines {
+        let start = ids[line[0]];
+        for name in &line[1..] {
+            let end = *ids.entry(name).or_insert_with(|| graph.add_node(()));
+            graph.add_edge(&start, &end, ());
+        }
+    }
+    graph
+}
+
+/// The (name, value) pairs of attributes of a node or edge in GEXF.
+type GexfAttrs = Vec<(String, String)>;
+
+/// Escapes text for use in XML attribute values.
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Writes a graph in GEXF, the XML format Gephi uses, with no attributes.
+/// Nodes and edges are identified and labeled by their indices.
+pub fn to_gexf<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> String {
+    to_gexf_with(graph, |_| vec![], |_| vec![])
+}
+
+/// Like [`to_gexf`], but with attributes for the nodes and edges, given as
+/// (name, value) pairs by `node_attrs` and `edge_attrs`. Every attribute is
+/// declared as a string, in the order the names are first found, and nodes and
+/// edges without a value for an attribute just leave it out.
+pub fn to_gexf_with<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, FN, FE>(
+    graph: &G,
+    node_attrs: FN,
+    edge_attrs: FE,
+) -> String
+where
+    FN: Fn(&N) -> Vec<(String, String)>,
+    FE: Fn(&E) -> Vec<(String, String)>,
+{
+    let nodes: Vec<(NodeInd, GexfAttrs)> = graph
+        .nodes_sorted()
+        .into_iter()
+        .map(|n| (n, node_attrs(graph.node(&n))))
+        .collect();
+    let edges: Vec<(&Edge<E>, GexfAttrs)> = graph
+        .edges_sorted()
+        .iter()
+        .map(|e| {
+            let edge = graph.edge(e);
+            (edge, edge_attrs(&edge.data))
+        })
+        .collect();
+
+    let edge_type = if graph.is_directed() {
+        "directed"
+    } else {
+        "undirected"
+    };
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+    out.push_str(&format!(
+        "  <graph mode=\"static\" defaultedgetype=\"{edge_type}\">\n"
+    ));
+    let node_ids = declare_gexf_attributes(&mut out, "node", nodes.iter().map(|(_, a)| a));
+    let edge_ids = declare_gexf_attributes(&mut out, "edge", edges.iter().map(|(_, a)| a));
+
+    out.push_str("    <nodes>\n");
+    for (n, attrs) in &nodes {
+        let tag = format!("<node id=\"{n}\" label=\"{n}\"");
+        write_gexf_element(&mut out, &tag, "node", attrs, &node_ids);
+    }
+    out.push_str("    </nodes>\n");
+    out.push_str("    <edges>\n");
+    for (edge, attrs) in &edges {
+        let tag = format!(
+            "<edge id=\"{}\" source=\"{}\" target=\"{}\"",
+            edge.index, edge.start, edge.end
+        );
+        write_gexf_element(&mut out, &tag, "edge", attrs, &edge_ids);
+    }
+    out.push_str("    </edges>\n");
+    out.push_str("  </graph>\n");
+    out.push_str("</gexf>\n");
+    out
+}
+
+/// Writes the `<attributes>` declaring every attribute name in `attrs` for the
+/// given class, if there are any, and returns the id given to each name.
+fn declare_gexf_attributes<'a>(
+    out: &mut String,
+    class: &str,
+    attrs: impl Iterator<Item = &'a GexfAttrs>,
+) -> HashMap<&'a str, usize> {
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut names = vec![];
+    for (name, _) in attrs.flatten() {
+        if !ids.contains_key(name.as_str()) {
+            ids.insert(name, names.len());
+            names.push(name);
+        }
+    }
+    if !names.is_empty() {
+        out.push_str(&format!("    <attributes class=\"{class}\">\n"));
+        for (id, name) in names.iter().enumerate() {
+            out.push_str(&format!(
+                "      <attribute id=\"{id}\" title=\"{}\" type=\"string\"/>\n",
+                xml_escape(name)
+            ));
+        }
+        out.push_str("    </attributes>\n");
+    }
+    ids
+}
+
+/// Writes a node or edge element starting with `open_tag`, with its
+/// attribute values inside it if it has any.
+fn write_gexf_element(
+    out: &mut String,
+    open_tag: &str,
+    name: &str,
+    attrs: &[(String, String)],
+    ids: &HashMap<&str, usize>,
+) {
+    if attrs.is_empty() {
+        out.push_str(&format!("      {open_tag}/>\n"));
+        return;
+    }
+    out.push_str(&format!("      {open_tag}>\n"));
+    out.push_str("        <attvalues>\n");
+    for (attr, value) in attrs {
+        out.push_str(&format!(
+            "          <attvalue for=\"{}\" value=\"{}\"/>\n",
+            ids[attr.as_str()],
+            xml_escape(value)
+        ));
+    }
+    out.push_str("        </attvalues>\n");
+    out.push_str(&format!("      </{name}>\n"));
+}
+
+/// Writes a graph as a sparse matrix in the Matrix Market coordinate format,
+/// used by SuiteSparse and many other collections of scientific data. The
+/// rows and columns are the nodes in ascending order of index, counting from 1,
+/// and each edge is an entry with its weight as the value. Undirected graphs
+/// are written as symmetric matrices, with every entry in the lower triangle,
+/// and directed graphs as general ones, with the start as the row. Parallel
+/// edges are separate entries, which most readers add together.
+pub fn to_matrix_market<N, E: Weighted, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+) -> String {
+    let nodes = graph.nodes_sorted();
+    let pos: HashMap<NodeInd, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i + 1)).collect();
+    let edges = graph.edges_sorted();
+
+    let symmetry = if graph.is_directed() {
+        "general"
+    } else {
+        "symmetric"
+    };
+    let mut out = format!("%%MatrixMarket matrix coordinate real {symmetry}\n");
+    out.push_str(&format!(
+        "{} {} {}\n",
+        nodes.len(),
+        nodes.len(),
+        edges.len()
+    ));
+    for e in edges {
+        let edge = graph.edge(&e);
+        let (mut row, mut col) = (pos[&edge.start], pos[&edge.end]);
+        if !graph.is_directed() && row < col {
+            (row, col) = (col, row);
+        }
+        out.push_str(&format!("{row} {col} {}\n", edge.data.weight()));
+    }
+    out
+}
+
+/// Reads a graph from a square sparse matrix in the Matrix Market coordinate
+/// format. Row and column i are node i - 1, and every entry is an edge from
+/// its row to its column, with its value as the data. Real, integer and
+/// pattern matrices can be read, with pattern entries getting a weight of 1,
+/// and so can general and symmetric ones. Each entry of a symmetric matrix
+/// stands for itself and its mirror image, so it's one edge in an undirected
+/// graph, and two in a directed one unless it's on the diagonal. Entries that
+/// are explicitly zero still become edges.
+pub fn from_matrix_market<Ty: GraphType>(mtx: &str) -> Result<ALGraph<(), f64, Ty>, ParseError> {
+    let mut lines = mtx
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()));
+
+    let header = lines.next().map_or("", |(_, line)| line).to_lowercase();
+    let header: Vec<&str> = header.split_whitespace().collect();
+    let [banner, "matrix", "coordinate", field, symmetry] = header.as_slice() else {
+        return Err(ParseError::new(1, "expected a coordinate matrix header"));
+    };
+    if *banner != "%%matrixmarket" {
+        return Err(ParseError::new(1, "missing %%MatrixMarket header"));
+    }
+    let pattern = match *field {
+        "real" | "integer" => false,
+        "pattern" => true,
+        _ => {
+            return Err(ParseError::new(
+                1,
+                format!("unsupported field type {field}"),
+            ))
+        }
+    };
+    let symmetric = match *symmetry {
+        "general" => false,
+        "symmetric" => true,
+        _ => {
+            return Err(ParseError::new(
+                1,
+                format!("unsupported symmetry {symmetry}"),
+            ))
+        }
+    };
+
+    let mut lines = lines.filter(|(_, line)| !line.is_empty() && !line.starts_with('%'));
+    let parse_index = |num: usize, token: &str, n: usize| match token.parse::<usize>() {
+        Ok(i) if (1..=n).contains(&i) => Ok(i - 1),
+        _ => Err(ParseError::new(num, format!("invalid index {token}"))),
+    };
+    let Some((size_num, size)) = lines.next() else {
+        return Err(ParseError::new(1, "missing size line"));
+    };
+    let size: Vec<usize> = size
+        .split_whitespace()
+        .map(|t| t.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| ParseError::new(size_num, "invalid size line"))?;
+    let &[rows, cols, entries] = size.as_slice() else {
+        return Err(ParseError::new(
+            size_num,
+            "expected rows, columns and entries",
+        ));
+    };
+    if rows != cols {
+        return Err(ParseError::new(size_num, "matrix isn't square"));
+    }
+
+    let mut graph = ALGraph::new();
+    for _ in 0..rows {
+        graph.add_node(());
+    }
+    let mut found = 0;
+    for (num, line) in lines {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let expected = if pattern { 2 } else { 3 };
+        if tokens.len() != expected {
+            return Err(ParseError::new(num, format!("expected {expected} fields")));
+        }
+        let row = parse_index(num, tokens[0], rows)?;
+        let col = parse_index(num, tokens[1], cols)?;
+        let value = if pattern {
+            1.0
+        } else {
+            tokens[2]
+                .parse()
+                .map_err(|_| ParseError::new(num, format!("invalid value {}", tokens[2])))?
+        };
+        graph.add_edge(&row, &col, value);
+        if symmetric && Ty::is_directed() && row != col {
+            graph.add_edge(&col, &row, value);
+        }
+        found += 1;
+    }
+    if found != entries {
+        return Err(ParseError::new(
+            size_num,
+            format!("expected {entries} entries, found {found}"),
+        ));
+    }
+    Ok(graph)
+}
+
+/// Writes the part of a tree or DAG reachable from `root` as nested JSON, the
+/// input D3's tree layouts take: each node is `{"id": n, "children": [...]}`,
+/// with its children in ascending order of edge index. For directed graphs, a
+/// node's children are the ends of its outgoing edges, and for undirected ones
+/// they're its neighbors other than the one it was reached from. A node that
+/// can be reached more than one way, which can happen in a DAG, is written
+/// again each time. Gives [`HasCycle`] if there's a cycle reachable from the
+/// root, since the nesting would never end.
+pub fn to_tree_json<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    root: &NodeInd,
+) -> Result<String, HasCycle> {
+    let mut out = String::new();
+    // the nodes from the root down to the top of the stack
+    let mut path = HashSet::new();
+    let mut stack = vec![];
+    enter_tree_json(graph, *root, None, &mut path, &mut stack, &mut out)?;
+
+    while let Some(frame) = stack.last_mut() {
+        if let Some(&(e, child)) = frame.children.get(frame.next) {
+            frame.next += 1;
+            if Some(e) == frame.via {
+                continue;
+            }
+            if !out.ends_with('[') {
+                out.push(',');
+            }
+            enter_tree_json(graph, child, Some(e), &mut path, &mut stack, &mut out)?;
+        } else {
+            out.push_str("]}");
+            path.remove(&frame.node);
+            stack.pop();
+        }
+    }
+    Ok(out)
+}
+
+/// A node on the DFS stack in [`to_tree_json`].
+struct TreeJsonFrame {
+    node: NodeInd,
+    via: Option<EdgeInd>,
+    children: Vec<(EdgeInd, NodeInd)>,
+    next: usize,
+}
+
+/// Starts writing the subtree at `n` for [`to_tree_json`], where `via` is the
+/// edge it was reached by, and pushes it onto the stack so its children get
+/// written next.
+fn enter_tree_json<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    n: NodeInd,
+    via: Option<EdgeInd>,
+    path: &mut HashSet<NodeInd>,
+    stack: &mut Vec<TreeJsonFrame>,
+    out: &mut String,
+) -> Result<(), HasCycle> {
+    if !path.insert(n) {
+        return Err(HasCycle);
+    }
+    let mut children: Vec<(EdgeInd, NodeInd)> = graph
+        .edges_from(&n)
+        .map(|e| (e.index, e.opposite(&n)))
+        .collect();
+    children.sort_unstable();
+    // undirected self-loops show up twice
+    children.dedup();
+
+    out.push_str(&format!("{{\"id\":{n},\"children\":["));
+    stack.push(TreeJsonFrame {
+        node: n,
+        via,
+        children,
+        next: 0,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::{Directed, Undirected};
+    use crate::test_util::{graph_from_edges, weighted_graph_from_edges};
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let mut g: ALGraph<String, (f64, Option<u8>), Directed> = ALGraph::new();
+        for name in ["a", "b", "c", "d"] {
+            g.add_node(name.to_string());
+        }
+        g.add_edge(&0, &1, (1.5, None));
+        g.add_edge(&1, &2, (-2.0, Some(7)));
+        g.add_edge(&2, &2, (0.0, Some(0)));
+        g.add_edge(&3, &0, (4.0, None));
+        g.remove_edge(&1);
+        // the last node and edge are removed, so the counters are past them
+        g.remove_node(&3);
+
+        let bytes = to_bytes(&g);
+        let mut decoded: ALGraph<String, (f64, Option<u8>), Directed> = from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, g);
+        assert_eq!(decoded.edges_sorted(), vec![0, 2]);
+        assert_eq!(decoded.edge(&2).data, (0.0, Some(0)));
+        assert_eq!(to_bytes(&decoded), bytes);
+
+        assert_eq!(
+            decoded.add_node("e".to_string()),
+            g.add_node("e".to_string())
+        );
+        assert_eq!(
+            decoded.add_edge(&0, &4, (0.0, None)),
+            g.add_edge(&0, &4, (0.0, None))
+        );
+    }
+
+    #[test]
+    fn test_bytes_errors() {
+        let mut g: ALGraph<u32, (), Undirected> = ALGraph::new();
+        g.add_node(1);
+        g.add_node(2);
+        g.add_edge(&0, &1, ());
+        let bytes = to_bytes(&g);
+
+        assert_eq!(
+            from_bytes::<u32, (), Directed>(&bytes),
+            Err(DecodeError::WrongGraphType)
+        );
+        assert_eq!(
+            from_bytes::<u32, (), Undirected>(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            from_bytes::<u32, (), Undirected>(b"GV4"),
+            Err(DecodeError::BadHeader)
+        );
+        // reading the node data as u64 takes too many bytes
+        assert!(from_bytes::<u64, (), Undirected>(&bytes).is_err());
+
+        // a forged header with a next node index far too large to allocate for
+        let mut forged = bytes.clone();
+        forged[5..13].copy_from_slice(&(1usize << 60).to_le_bytes());
+        assert!(matches!(
+            from_bytes::<u32, (), Undirected>(&forged),
+            Err(DecodeError::Invalid(_))
+        ));
+        forged[5..13].copy_from_slice(&usize::MAX.to_le_bytes());
+        assert!(matches!(
+            from_bytes::<u32, (), Undirected>(&forged),
+            Err(DecodeError::Invalid(_))
+        ));
+
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert!(matches!(
+            from_bytes::<u32, (), Undirected>(&extra),
+            Err(DecodeError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let nodes = "Id,Label,group\n\
+                     a,Alice,1\n\
+                     b,\"Bob, Jr.\",2\n\
+                     c,\"says \"\"hi\"\"\",1\n";
+        let edges = "Source,Target,weight\r\na,b,0.5\r\nb,c,2\r\n\r\nc,d,\r\n";
+        let g = from_csv::<Directed>(nodes, edges).unwrap();
+
+        assert_eq!(g.nodes_sorted(), vec![0, 1, 2, 3]);
+        assert_eq!(g.node(&1)["Label"], "Bob, Jr.");
+        assert_eq!(g.node(&2)["Label"], "says \"hi\"");
+        assert_eq!(g.node(&0)["group"], "1");
+        // d is only in the edge table
+        assert_eq!(
+            g.node(&3),
+            &HashMap::from([("Id".to_string(), "d".to_string())])
+        );
+
+        let ends: Vec<(NodeInd, NodeInd)> = g
+            .edges_sorted()
+            .iter()
+            .map(|e| g.edge_endpoints(e))
+            .collect();
+        assert_eq!(ends, vec![(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(g.edge(&0).data["weight"], "0.5");
+        assert_eq!(g.edge(&2).data["weight"], "");
+        assert_eq!(g.edge(&2).data["Source"], "c");
+    }
+
+    #[test]
+    fn test_from_csv_errors() {
+        let edges = "source,target\n";
+        let err = from_csv::<Undirected>("id\na\nb\na\n", edges).unwrap_err();
+        assert_eq!(err.line, 4);
+
+        let err = from_csv::<Undirected>("id,x\na,1\nb\n", edges).unwrap_err();
+        assert_eq!(err.line, 3);
+
+        let err = from_csv::<Undirected>("id\na\n", "source\na\n").unwrap_err();
+        assert_eq!(err.line, 1);
+
+        let err = from_csv::<Undirected>("id\n\"a\n", edges).unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn test_adjlist_round_trip() {
+        // node 4 is isolated
+        let g =
+            graph_from_edges::<Undirected>(5, &[(0, 1), (1, 2), (2, 0), (3, 1), (2, 2), (0, 1)]);
+        let text = to_adjlist(&g);
+        assert_eq!(text, "0 1 2 1\n1 2 3\n2 2\n3\n4\n");
+        assert_eq!(from_adjlist::<Undirected>(&text), g);
+
+        let d = graph_from_edges::<Directed>(4, &[(0, 1), (1, 0), (2, 1), (1, 1)]);
+        let text = to_adjlist(&d);
+        assert_eq!(text, "0 1\n1 0 1\n2 1\n3\n");
+        assert_eq!(from_adjlist::<Directed>(&text), d);
+    }
+
+    #[test]
+    fn test_from_adjlist_names() {
+        let g = from_adjlist::<Directed>("# a comment\nb a c # another\n\na\n");
+        let ends: Vec<(NodeInd, NodeInd)> = g
+            .edges_sorted()
+            .iter()
+            .map(|e| g.edge_endpoints(e))
+            .collect();
+        // b and a have lines, and c only shows up as a neighbor
+        assert_eq!(g.nodes_sorted(), vec![0, 1, 2]);
+        assert_eq!(ends, vec![(0, 1), (0, 2)]);
+    }
+
+    /// Checks that every tag in `xml` is closed in the right order, ignoring
+    /// the declaration.
+    fn assert_tags_balanced(xml: &str) {
+        let mut open: Vec<&str> = vec![];
+        for tag in xml.split('<').skip(1).map(|t| t.split('>').next().unwrap()) {
+            if tag.starts_with('?') || tag.ends_with('/') {
+                continue;
+            }
+            match tag.strip_prefix('/') {
+                Some(name) => assert_eq!(open.pop(), Some(name)),
+                None => open.push(tag.split(' ').next().unwrap()),
+            }
+        }
+        assert!(open.is_empty());
+    }
+
+    #[test]
+    fn test_to_gexf() {
+        let mut g: ALGraph<&str, f64, Directed> = ALGraph::new();
+        g.add_node("a");
+        g.add_node("b & c");
+        g.add_node("");
+        g.add_edge(&0, &1, 1.5);
+        g.add_edge(&1, &2, 2.0);
+        let gexf = to_gexf_with(
+            &g,
+            |name| {
+                if name.is_empty() {
+                    vec![]
+                } else {
+                    vec![("name".to_string(), name.to_string())]
+                }
+            },
+            |w| vec![("weight".to_string(), w.to_string())],
+        );
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gexf xmlns="http://gexf.net/1.3" version="1.3">
+  <graph mode="static" defaultedgetype="directed">
+    <attributes class="node">
+      <attribute id="0" title="name" type="string"/>
+    </attributes>
+    <attributes class="edge">
+      <attribute id="0" title="weight" type="string"/>
+    </attributes>
+    <nodes>
+      <node id="0" label="0">
+        <attvalues>
+          <attvalue for="0" value="a"/>
+        </attvalues>
+      </node>
+      <node id="1" label="1">
+        <attvalues>
+          <attvalue for="0" value="b &amp; c"/>
+        </attvalues>
+      </node>
+      <node id="2" label="2"/>
+    </nodes>
+    <edges>
+      <edge id="0" source="0" target="1">
+        <attvalues>
+          <attvalue for="0" value="1.5"/>
+        </attvalues>
+      </edge>
+      <edge id="1" source="1" target="2">
+        <attvalues>
+          <attvalue for="0" value="2"/>
+        </attvalues>
+      </edge>
+    </edges>
+  </graph>
+</gexf>
+"#;
+        assert_eq!(gexf, expected);
+        assert_tags_balanced(&gexf);
+    }
+
+    #[test]
+    fn test_to_gexf_plain() {
+        let g = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2)]);
+        let gexf = to_gexf(&g);
+        assert!(gexf.contains("defaultedgetype=\"undirected\""));
+        assert!(!gexf.contains("attributes"));
+        assert!(gexf.contains("<edge id=\"1\" source=\"1\" target=\"2\"/>"));
+        assert_tags_balanced(&gexf);
+    }
+
+    #[test]
+    fn test_matrix_market_symmetric() {
+        let mtx = "%%MatrixMarket matrix coordinate real symmetric
+% a 4x4 symmetric matrix, with node 4 isolated
+4 4 4
+1 1 2.5
+2 1 -1
+3 2 0.25
+3 1 4
+";
+        let g = from_matrix_market::<Undirected>(mtx).unwrap();
+        assert_eq!(g.nodes_sorted(), vec![0, 1, 2, 3]);
+        let entries: Vec<(NodeInd, NodeInd, f64)> = g
+            .edges_sorted()
+            .iter()
+            .map(|e| (g.edge(e).start, g.edge(e).end, g.edge(e).data))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![(0, 0, 2.5), (1, 0, -1.0), (2, 1, 0.25), (2, 0, 4.0)]
+        );
+
+        let written = to_matrix_market(&g);
+        assert_eq!(
+            written,
+            "%%MatrixMarket matrix coordinate real symmetric\n4 4 4\n1 1 2.5\n2 1 -1\n3 2 0.25\n3 1 4\n"
+        );
+        assert_eq!(from_matrix_market::<Undirected>(&written).unwrap(), g);
+
+        // as a directed graph, the off-diagonal entries go both ways
+        let d = from_matrix_market::<Directed>(mtx).unwrap();
+        assert_eq!(d.edges().count(), 7);
+    }
+
+    #[test]
+    fn test_matrix_market_general() {
+        let d = weighted_graph_from_edges::<Directed>(3, &[(0, 1, 1.0), (2, 0, 3.5)]);
+        let written = to_matrix_market(&d);
+        assert_eq!(
+            written,
+            "%%MatrixMarket matrix coordinate real general\n3 3 2\n1 2 1\n3 1 3.5\n"
+        );
+        assert_eq!(from_matrix_market::<Directed>(&written).unwrap(), d);
+
+        let pattern = "%%MatrixMarket matrix coordinate pattern general\n2 2 1\n1 2\n";
+        let g = from_matrix_market::<Directed>(pattern).unwrap();
+        assert_eq!(g.edge(&0).data, 1.0);
+    }
+
+    #[test]
+    fn test_matrix_market_errors() {
+        let err = |mtx: &str| from_matrix_market::<Directed>(mtx).unwrap_err().line;
+        assert_eq!(err("%%MatrixMarket matrix array real general\n2 2\n"), 1);
+        assert_eq!(
+            err("%%MatrixMarket matrix coordinate real general\n2 3 0\n"),
+            2
+        );
+        assert_eq!(
+            err("%%MatrixMarket matrix coordinate real general\n2 2 1\n% c\n3 1 1\n"),
+            4
+        );
+        assert_eq!(
+            err("%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1\n"),
+            2
+        );
+    }
+
+    #[test]
+    fn test_to_tree_json() {
+        //     0
+        //    / \
+        //   1   2
+        //  / \
+        // 3   4
+        let tree = graph_from_edges::<Undirected>(5, &[(0, 1), (0, 2), (1, 3), (1, 4)]);
+        let leaf = |n: usize| format!("{{\"id\":{n},\"children\":[]}}");
+        let one = format!("{{\"id\":1,\"children\":[{},{}]}}", leaf(3), leaf(4));
+        let expected = format!("{{\"id\":0,\"children\":[{one},{}]}}", leaf(2));
+        assert_eq!(to_tree_json(&tree, &0), Ok(expected));
+        // rooted somewhere else, the parent becomes a child
+        let zero = format!("{{\"id\":0,\"children\":[{}]}}", leaf(2));
+        let one = format!("{{\"id\":1,\"children\":[{zero},{}]}}", leaf(4));
+        let expected = format!("{{\"id\":3,\"children\":[{one}]}}");
+        assert_eq!(to_tree_json(&tree, &3), Ok(expected));
+
+        // in a DAG, a node reached two ways is written twice
+        let dag = graph_from_edges::<Directed>(3, &[(0, 1), (0, 2), (1, 2)]);
+        let two = leaf(2);
+        assert_eq!(
+            to_tree_json(&dag, &0),
+            Ok(format!(
+                "{{\"id\":0,\"children\":[{{\"id\":1,\"children\":[{two}]}},{two}]}}"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_to_tree_json_cycle() {
+        let triangle = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(to_tree_json(&triangle, &0), Err(HasCycle));
+        let directed = graph_from_edges::<Directed>(3, &[(0, 1), (1, 2), (2, 1)]);
+        assert_eq!(to_tree_json(&directed, &0), Err(HasCycle));
+        let self_loop = graph_from_edges::<Undirected>(2, &[(0, 1), (1, 1)]);
+        assert_eq!(to_tree_json(&self_loop, &0), Err(HasCycle));
+        // a cycle that can't be reached from the root doesn't matter
+        let apart = graph_from_edges::<Directed>(4, &[(0, 1), (2, 3), (3, 2)]);
+        assert!(to_tree_json(&apart, &0).is_ok());
+    }
+
+    #[test]
+    fn test_to_tree_json_deep() {
+        // far deeper than the call stack could go one level per call
+        let n = 200_000;
+        let edges: Vec<(NodeInd, NodeInd)> = (1..n).map(|i| (i - 1, i)).collect();
+        let path = graph_from_edges::<Undirected>(n, &edges);
+        let json = to_tree_json(&path, &0).unwrap();
+        assert!(json.starts_with("{\"id\":0,\"children\":[{\"id\":1,"));
+        assert!(json.ends_with(&"]}".repeat(n)));
+        assert_eq!(json.matches("\"id\"").count(), n);
+    }
+}
@@ -0,0 +1,206 @@
+//! Searching for copies of one graph inside another.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::graph_base::{GraphType, GraphView, NodeInd};
+
+/// The arcs of a graph as (start, end) pairs, with both directions for
+/// undirected edges.
+fn arc_set<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> HashSet<(NodeInd, NodeInd)> {
+    let mut arcs = HashSet::new();
+    for e in graph.edges() {
+        let (u, v) = graph.edge_endpoints(&e);
+        arcs.insert((u, v));
+        if !graph.is_directed() {
+            arcs.insert((v, u));
+        }
+    }
+    arcs
+}
+
+/// The fixed parts of a subgraph isomorphism search.
+struct Search<'a> {
+    /// The pattern nodes, in the order they're mapped.
+    order: Vec<NodeInd>,
+    pattern_arcs: HashSet<(NodeInd, NodeInd)>,
+    target_arcs: HashSet<(NodeInd, NodeInd)>,
+    /// The neighbors of each node in either direction, without duplicates.
+    pattern_adj: HashMap<NodeInd, Vec<NodeInd>>,
+    target_adj: HashMap<NodeInd, Vec<NodeInd>>,
+    target_nodes: &'a [NodeInd],
+}
+
+impl Search<'_> {
+    /// Whether pattern node `p` can go to target node `t`, given the mapping so
+    /// far: every arc between `p` and an already mapped node, or a self-loop on
+    /// `p`, has to have a matching arc in the target.
+    fn feasible(&self, mapping: &HashMap<NodeInd, NodeInd>, p: NodeInd, t: NodeInd) -> bool {
+        if self.target_adj[&t].len() < self.pattern_adj[&p].len() {
+            return false;
+        }
+        let has = |arcs: &HashSet<(NodeInd, NodeInd)>, u, v| arcs.contains(&(u, v));
+        if has(&self.pattern_arcs, p, p) && !has(&self.target_arcs, t, t) {
+            return false;
+        }
+        self.pattern_adj[&p].iter().all(|q| {
+            let Some(&s) = mapping.get(q) else {
+                return true;
+            };
+            (!has(&self.pattern_arcs, p, *q) || has(&self.target_arcs, t, s))
+                && (!has(&self.pattern_arcs, *q, p) || has(&self.target_arcs, s, t))
+        })
+    }
+
+    /// Extends the mapping with the `depth`-th pattern node in every way that
+    /// works, recording each complete mapping.
+    fn extend(
+        &self,
+        depth: usize,
+        mapping: &mut HashMap<NodeInd, NodeInd>,
+        used: &mut HashSet<NodeInd>,
+        found: &mut Vec<HashMap<NodeInd, NodeInd>>,
+    ) {
+        let Some(&p) = self.order.get(depth) else {
+            found.push(mapping.clone());
+            return;
+        };
+        // if a neighbor of p is already mapped, p has to go next to its image
+        let anchor = self.pattern_adj[&p].iter().find_map(|q| mapping.get(q));
+        let candidates: &[NodeInd] = match anchor {
+            Some(s) => &self.target_adj[s],
+            None => self.target_nodes,
+        };
+        for &t in candidates {
+            if used.contains(&t) || !self.feasible(mapping, p, t) {
+                continue;
+            }
+            mapping.insert(p, t);
+            used.insert(t);
+            self.extend(depth + 1, mapping, used, found);
+            used.remove(&t);
+            mapping.remove(&p);
+        }
+    }
+}
+
+/// Gets the neighbors of every node in either direction, sorted and without
+/// duplicates or the node itself.
+fn undirected_adjacency<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+) -> HashMap<NodeInd, Vec<NodeInd>> {
+    let mut adj: HashMap<NodeInd, Vec<NodeInd>> = graph.nodes().map(|n| (n, vec![])).collect();
+    for (u, v) in arc_set(graph) {
+        if u != v {
+            adj.get_mut(&u).unwrap().push(v);
+            adj.get_mut(&v).unwrap().push(u);
+        }
+    }
+    for nbrs in adj.values_mut() {
+        nbrs.sort_unstable();
+        nbrs.dedup();
+    }
+    adj
+}
+
+/// Finds every way to embed `pattern` in `target`: every one-to-one map from
+/// pattern nodes to target nodes that takes each edge of the pattern to an
+/// edge of the target, respecting direction. Extra edges between the images
+/// are allowed, so these are monomorphisms, which is what's wanted for motif
+/// search. Parallel edges count once.
+///
+/// This is a VF2-style backtracking search: pattern nodes are mapped one at a
+/// time, in an order where each one is next to an already mapped node if
+/// possible, so its candidates can be limited to the neighbors of that node's
+/// image, and partial maps that break an edge or need more neighbors than a
+/// target node has are abandoned right away. The number of embeddings can
+/// still be exponential in the size of the pattern.
+pub fn find_subgraph_isomorphisms<N1, E1, N2, E2, Ty: GraphType, G1, G2>(
+    pattern: &G1,
+    target: &G2,
+) -> Vec<HashMap<NodeInd, NodeInd>>
+where
+    G1: GraphView<N1, E1, Ty>,
+    G2: GraphView<N2, E2, Ty>,
+{
+    let pattern_adj = undirected_adjacency(pattern);
+
+    // visit pattern nodes component by component, starting each at its
+    // highest-degree node, since those have the fewest candidates
+    let mut order = vec![];
+    let mut placed = HashSet::new();
+    let mut by_degree = pattern.nodes_sorted();
+    by_degree.sort_by_key(|n| Reverse(pattern_adj[n].len()));
+    for start in by_degree {
+        if !placed.insert(start) {
+            continue;
+        }
+        let first = order.len();
+        order.push(start);
+        let mut i = first;
+        while i < order.len() {
+            for &q in &pattern_adj[&order[i]] {
+                if placed.insert(q) {
+                    order.push(q);
+                }
+            }
+            i += 1;
+        }
+    }
+
+    let target_nodes = target.nodes_sorted();
+    let search = Search {
+        order,
+        pattern_arcs: arc_set(pattern),
+        target_arcs: arc_set(target),
+        pattern_adj,
+        target_adj: undirected_adjacency(target),
+        target_nodes: &target_nodes,
+    };
+    let mut found = vec![];
+    search.extend(0, &mut HashMap::new(), &mut HashSet::new(), &mut found);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::{Directed, Undirected};
+    use crate::test_util::graph_from_edges;
+
+    #[test]
+    fn test_triangles_in_k4() {
+        let triangle = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0)]);
+        let k4 =
+            graph_from_edges::<Undirected>(4, &[(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+        // 4 triangles, each hit in 3! ways
+        let found = find_subgraph_isomorphisms(&triangle, &k4);
+        assert_eq!(found.len(), 24);
+        for mapping in &found {
+            let images: HashSet<NodeInd> = mapping.values().copied().collect();
+            assert_eq!(images.len(), 3);
+        }
+
+        // a path maps into the triangle even though the ends are adjacent
+        let path = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2)]);
+        assert_eq!(find_subgraph_isomorphisms(&path, &triangle).len(), 6);
+        assert!(find_subgraph_isomorphisms(&k4, &triangle).is_empty());
+    }
+
+    #[test]
+    fn test_directed_pattern() {
+        // a directed 3-cycle has 3 rotations, and a transitive triangle has
+        // none of them
+        let cycle = graph_from_edges::<Directed>(3, &[(0, 1), (1, 2), (2, 0)]);
+        let transitive = graph_from_edges::<Directed>(3, &[(0, 1), (1, 2), (0, 2)]);
+        assert_eq!(find_subgraph_isomorphisms(&cycle, &cycle).len(), 3);
+        assert!(find_subgraph_isomorphisms(&cycle, &transitive).is_empty());
+
+        // an edge can go to any of the three, and an isolated node goes to
+        // whichever node is left
+        let pattern = graph_from_edges::<Directed>(3, &[(0, 1)]);
+        let found = find_subgraph_isomorphisms(&pattern, &transitive);
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().all(|m| m[&0] != m[&2] && m[&1] != m[&2]));
+    }
+}
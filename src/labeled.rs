@@ -0,0 +1,167 @@
+//! Graphs whose nodes can be looked up by a label.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::adj_list_graph::ALGraph;
+use crate::graph_base::{Edge, EdgeInd, GraphBase, GraphType, GraphView, NodeInd};
+
+/// A graph where nodes can have a unique label of type `L`, kept in an index
+/// so that the node with a label can be found without searching. Nodes don't
+/// need a label: ones added with [`add_node`](GraphBase::add_node) don't have
+/// one. Removing a node removes its label too, so the label can be used again.
+#[derive(Clone, Debug)]
+pub struct LabeledGraph<L, N, E, Ty: GraphType> {
+    graph: ALGraph<N, E, Ty>,
+    by_label: HashMap<L, NodeInd>,
+    labels: HashMap<NodeInd, L>,
+}
+
+impl<L, N, E, Ty: GraphType> LabeledGraph<L, N, E, Ty> {
+    /// Makes a new, empty graph.
+    pub fn new() -> Self {
+        Self {
+            graph: ALGraph::new(),
+            by_label: HashMap::new(),
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Gets the underlying graph.
+    pub fn graph(&self) -> &ALGraph<N, E, Ty> {
+        &self.graph
+    }
+}
+
+impl<L, N, E, Ty: GraphType> Default for LabeledGraph<L, N, E, Ty> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: Hash + Eq + Clone, N, E: Clone, Ty: GraphType> LabeledGraph<L, N, E, Ty> {
+    /// Adds a node with the given label and data, returning its index. Panics
+    /// if another node already has the label.
+    pub fn add_labeled_node(&mut self, label: L, data: N) -> NodeInd {
+        assert!(
+            !self.by_label.contains_key(&label),
+            "a node already has this label"
+        );
+        let n = self.graph.add_node(data);
+        self.by_label.insert(label.clone(), n);
+        self.labels.insert(n, label);
+        n
+    }
+
+    /// Gets the node with the given label, if there is one.
+    pub fn node_by_label(&self, label: &L) -> Option<NodeInd> {
+        self.by_label.get(label).copied()
+    }
+
+    /// Gets the label of a node, if it has one.
+    pub fn label(&self, n: &NodeInd) -> Option<&L> {
+        self.labels.get(n)
+    }
+}
+
+impl<L: Hash + Eq, N, E: Clone, Ty: GraphType> GraphView<N, E, Ty> for LabeledGraph<L, N, E, Ty> {
+    fn node(&self, n: &NodeInd) -> &N {
+        self.graph.node(n)
+    }
+
+    fn edge(&self, e: &EdgeInd) -> &Edge<E> {
+        self.graph.edge(e)
+    }
+
+    fn nodes(&self) -> Box<dyn Iterator<Item = NodeInd>> {
+        self.graph.nodes()
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = EdgeInd>> {
+        self.graph.edges()
+    }
+
+    fn edges_from(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        self.graph.edges_from(n)
+    }
+
+    fn edges_to(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        self.graph.edges_to(n)
+    }
+
+    fn edges_at(&self, n: &NodeInd) -> Box<dyn Iterator<Item = Edge<E>> + '_> {
+        self.graph.edges_at(n)
+    }
+
+    fn edge_endpoints(&self, e: &EdgeInd) -> (NodeInd, NodeInd) {
+        self.graph.edge_endpoints(e)
+    }
+}
+
+impl<L: Hash + Eq, N, E: Clone, Ty: GraphType> GraphBase<N, E, Ty> for LabeledGraph<L, N, E, Ty> {
+    fn node_mut(&mut self, n: &NodeInd) -> &mut N {
+        self.graph.node_mut(n)
+    }
+
+    fn edge_mut(&mut self, e: &EdgeInd) -> &mut Edge<E> {
+        self.graph.edge_mut(e)
+    }
+
+    fn add_node(&mut self, data: N) -> NodeInd {
+        self.graph.add_node(data)
+    }
+
+    fn add_edge(&mut self, start: &NodeInd, end: &NodeInd, data: E) -> EdgeInd {
+        self.graph.add_edge(start, end, data)
+    }
+
+    fn remove_edge(&mut self, e: &EdgeInd) -> Edge<E> {
+        self.graph.remove_edge(e)
+    }
+
+    fn remove_node(&mut self, n: &NodeInd) -> N {
+        if let Some(label) = self.labels.remove(n) {
+            self.by_label.remove(&label);
+        }
+        self.graph.remove_node(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::Undirected;
+    use crate::traversal::bfs_distances;
+
+    #[test]
+    fn test_labels() {
+        let mut g: LabeledGraph<&str, u32, (), Undirected> = LabeledGraph::new();
+        let a = g.add_labeled_node("a", 1);
+        let b = g.add_labeled_node("b", 2);
+        let unlabeled = g.add_node(3);
+        g.add_edge(&a, &unlabeled, ());
+        g.add_edge(&unlabeled, &b, ());
+
+        assert_eq!(g.node_by_label(&"a"), Some(a));
+        assert_eq!(g.node_by_label(&"b"), Some(b));
+        assert_eq!(g.node_by_label(&"c"), None);
+        assert_eq!(g.label(&b), Some(&"b"));
+        assert_eq!(g.label(&unlabeled), None);
+        assert_eq!(*g.node(&g.node_by_label(&"b").unwrap()), 2);
+        assert_eq!(bfs_distances(&g, &a)[&b], 2);
+
+        assert_eq!(g.remove_node(&a), 1);
+        assert_eq!(g.node_by_label(&"a"), None);
+        assert_eq!(g.label(&a), None);
+        let again = g.add_labeled_node("a", 4);
+        assert_eq!(g.node_by_label(&"a"), Some(again));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_duplicate_label() {
+        let mut g: LabeledGraph<char, (), (), Undirected> = LabeledGraph::new();
+        g.add_labeled_node('x', ());
+        g.add_labeled_node('x', ());
+    }
+}
@@ -1,5 +1,38 @@
 pub mod adj_list_graph;
+pub mod attributes;
+pub mod centrality;
+pub mod cliques;
+pub mod coloring;
+pub mod community;
+pub mod connectivity;
+pub mod covering;
+pub mod cuts;
+pub mod cycles;
+pub mod diff;
+pub mod euler;
+pub mod flow;
+pub mod frozen;
+pub mod generators;
 pub mod graph_base;
+pub mod incremental;
+pub mod io;
+pub mod isomorphism;
+pub mod labeled;
+pub mod matching;
+pub mod matrices;
+pub mod motifs;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod properties;
+pub mod shortest_paths;
+pub mod steiner;
+pub mod traversal;
+pub mod trees;
+pub mod union_find;
+pub mod walks;
+
+#[cfg(test)]
+mod test_util;
 
 #[cfg(test)]
 mod tests {
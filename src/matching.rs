@@ -0,0 +1,1156 @@
+//! Matchings: sets of edges where no two share an endpoint.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::flow::FlowNetwork;
+use crate::graph_base::{Directed, Edge, EdgeInd, GraphView, NodeInd, Undirected};
+
+/// The state of Edmonds' blossom algorithm, on nodes numbered by position.
+struct Blossom {
+    adj: Vec<Vec<usize>>,
+    mate: Vec<Option<usize>>,
+    /// The node each node was reached from in the current search's tree.
+    pred: Vec<Option<usize>>,
+    /// The base of the blossom each node has been shrunk into, or itself.
+    base: Vec<usize>,
+    /// Whether each node is an even node of the current search's tree.
+    used: Vec<bool>,
+    blossom: Vec<bool>,
+}
+
+impl Blossom {
+    /// Gets the base of the lowest blossom containing both `a` and `b` in the
+    /// search tree.
+    fn lca(&self, mut a: usize, mut b: usize) -> usize {
+        let mut seen = vec![false; self.adj.len()];
+        loop {
+            a = self.base[a];
+            seen[a] = true;
+            match self.mate[a] {
+                Some(m) => a = self.pred[m].unwrap(),
+                None => break,
+            }
+        }
+        loop {
+            b = self.base[b];
+            if seen[b] {
+                return b;
+            }
+            b = self.pred[self.mate[b].unwrap()].unwrap();
+        }
+    }
+
+    /// Marks the blossoms on the path from `v` up to the blossom base `b`,
+    /// pointing odd nodes back down towards `child` so that augmenting paths
+    /// can go around the blossom either way.
+    fn mark_path(&mut self, mut v: usize, b: usize, mut child: usize) {
+        while self.base[v] != b {
+            let m = self.mate[v].unwrap();
+            self.blossom[self.base[v]] = true;
+            self.blossom[self.base[m]] = true;
+            self.pred[v] = Some(child);
+            child = m;
+            v = self.pred[m].unwrap();
+        }
+    }
+
+    /// Searches for an augmenting path from the unmatched node `root`, growing
+    /// a tree of alternating paths and shrinking blossoms as they're found.
+    /// Returns the unmatched node at the other end of the path, if any.
+    fn find_path(&mut self, root: usize) -> Option<usize> {
+        let n = self.adj.len();
+        self.used = vec![false; n];
+        self.pred = vec![None; n];
+        self.base = (0..n).collect();
+        self.used[root] = true;
+        let mut queue = VecDeque::from([root]);
+        while let Some(v) = queue.pop_front() {
+            for i in 0..self.adj[v].len() {
+                let to = self.adj[v][i];
+                if self.base[v] == self.base[to] || self.mate[v] == Some(to) {
+                    continue;
+                }
+                let odd_cycle = to == root || self.mate[to].is_some_and(|m| self.pred[m].is_some());
+                if odd_cycle {
+                    let b = self.lca(v, to);
+                    self.blossom = vec![false; n];
+                    self.mark_path(v, b, to);
+                    self.mark_path(to, b, v);
+                    for j in 0..n {
+                        if self.blossom[self.base[j]] {
+                            self.base[j] = b;
+                            if !self.used[j] {
+                                self.used[j] = true;
+                                queue.push_back(j);
+                            }
+                        }
+                    }
+                } else if self.pred[to].is_none() {
+                    self.pred[to] = Some(v);
+                    match self.mate[to] {
+                        None => return Some(to),
+                        Some(m) => {
+                            self.used[m] = true;
+                            queue.push_back(m);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Flips the matched and unmatched edges along the augmenting path ending
+    /// at `v`.
+    fn augment(&mut self, mut v: usize) {
+        loop {
+            let pv = self.pred[v].unwrap();
+            let next = self.mate[pv];
+            self.mate[v] = Some(pv);
+            self.mate[pv] = Some(v);
+            match next {
+                Some(next) => v = next,
+                None => break,
+            }
+        }
+    }
+}
+
+/// Gets a maximum matching of an undirected graph: as many edges as possible,
+/// no two of which share an endpoint. This is Edmonds' blossom algorithm, which
+/// works for any graph, not just bipartite ones: odd cycles found while looking
+/// for augmenting paths are shrunk into a single node. It takes O(V³) time.
+/// Self-loops are never used, and among parallel edges the one with the lowest
+/// index is. The edges are in ascending order of index.
+pub fn maximum_matching<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> Vec<EdgeInd> {
+    let nodes = graph.nodes_sorted();
+    let pos: HashMap<NodeInd, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+
+    let mut edge_between: HashMap<(usize, usize), EdgeInd> = HashMap::new();
+    for e in graph.edges() {
+        let (u, v) = graph.edge_endpoints(&e);
+        let (u, v) = (pos[&u], pos[&v]);
+        if u != v {
+            let best = edge_between.entry((u.min(v), u.max(v))).or_insert(e);
+            *best = e.min(*best);
+        }
+    }
+    let mut adj = vec![vec![]; nodes.len()];
+    let mut pairs: Vec<&(usize, usize)> = edge_between.keys().collect();
+    pairs.sort_unstable();
+    for &(u, v) in pairs {
+        adj[u].push(v);
+        adj[v].push(u);
+    }
+
+    let n = nodes.len();
+    let mut state = Blossom {
+        adj,
+        mate: vec![None; n],
+        pred: vec![None; n],
+        base: (0..n).collect(),
+        used: vec![false; n],
+        blossom: vec![false; n],
+    };
+    for root in 0..n {
+        if state.mate[root].is_none() {
+            if let Some(end) = state.find_path(root) {
+                state.augment(end);
+            }
+        }
+    }
+
+    let mut matching: Vec<EdgeInd> = (0..n)
+        .filter_map(|u| {
+            let v = state.mate[u]?;
+            (u < v).then(|| edge_between[&(u, v)])
+        })
+        .collect();
+    matching.sort_unstable();
+    matching
+}
+
+/// Splits the nodes into two sides with every edge going between them, giving
+/// whether each node is on the second side, or `None` if the graph isn't
+/// bipartite.
+fn two_color<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> Option<HashMap<NodeInd, bool>> {
+    let mut side: HashMap<NodeInd, bool> = HashMap::new();
+    for root in graph.nodes_sorted() {
+        if side.contains_key(&root) {
+            continue;
+        }
+        side.insert(root, false);
+        let mut queue = VecDeque::from([root]);
+        while let Some(u) = queue.pop_front() {
+            for e in graph.edges_at(&u) {
+                let v = e.opposite(&u);
+                match side.get(&v) {
+                    Some(&s) if s == side[&u] => return None,
+                    Some(_) => {}
+                    None => {
+                        side.insert(v, !side[&u]);
+                        queue.push_back(v);
+                    }
+                }
+            }
+        }
+    }
+    Some(side)
+}
+
+/// What to do after changing the duals in [`WeightedBlossom::solve`].
+enum DualStep {
+    /// Some node's dual has reached 0, so no better matching can be found.
+    Stop,
+    /// The edge has become tight and can be used from its labeled end.
+    Allow(usize),
+    /// The odd blossom's dual has reached 0, so it has to be taken apart.
+    Expand(usize),
+}
+
+/// The state of the primal-dual weighted blossom algorithm, on nodes numbered
+/// by position. Blossoms are numbered from `n` on, and edge `k` has the
+/// endpoints `2 * k` and `2 * k + 1`, so `p ^ 1` is the other end of `p`.
+struct WeightedBlossom {
+    n: usize,
+    edges: Vec<(usize, usize, f64)>,
+    /// The node at each endpoint.
+    endpoint: Vec<usize>,
+    /// The far endpoints of the edges at each node.
+    neighbor_ends: Vec<Vec<usize>>,
+    /// The far endpoint of each node's matched edge.
+    mate: Vec<Option<usize>>,
+    /// The label of each node and top-level blossom: 0 for none, 1 for an
+    /// even (S) one and 2 for an odd (T) one, with 5 marking the S-blossoms
+    /// passed while looking for a new blossom's base.
+    label: Vec<u8>,
+    /// The endpoint each labeled node or blossom was reached through.
+    label_end: Vec<Option<usize>>,
+    /// The top-level blossom containing each node, or itself.
+    in_blossom: Vec<usize>,
+    blossom_parent: Vec<Option<usize>>,
+    /// The sub-blossoms of each blossom, going around it from its base.
+    blossom_children: Vec<Vec<usize>>,
+    blossom_base: Vec<Option<usize>>,
+    /// The endpoints of the edges joining the sub-blossoms, in the same order.
+    blossom_ends: Vec<Vec<usize>>,
+    /// The least-slack edge from each node or S-blossom to an S-blossom.
+    best_edge: Vec<Option<usize>>,
+    /// The least-slack edge from each S-blossom to each other S-blossom.
+    blossom_best_edges: Vec<Option<Vec<usize>>>,
+    unused_blossoms: Vec<usize>,
+    dual: Vec<f64>,
+    /// Whether each edge is known to be tight.
+    allow_edge: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+impl WeightedBlossom {
+    fn new(n: usize, edges: Vec<(usize, usize, f64)>) -> Self {
+        let max_weight = edges.iter().map(|e| e.2).fold(0.0, f64::max);
+        let mut neighbor_ends = vec![vec![]; n];
+        let mut endpoint = vec![];
+        for (k, &(i, j, _)) in edges.iter().enumerate() {
+            endpoint.push(i);
+            endpoint.push(j);
+            neighbor_ends[i].push(2 * k + 1);
+            neighbor_ends[j].push(2 * k);
+        }
+        let mut dual = vec![max_weight; n];
+        dual.resize(2 * n, 0.0);
+        Self {
+            n,
+            endpoint,
+            neighbor_ends,
+            mate: vec![None; n],
+            label: vec![0; 2 * n],
+            label_end: vec![None; 2 * n],
+            in_blossom: (0..n).collect(),
+            blossom_parent: vec![None; 2 * n],
+            blossom_children: vec![vec![]; 2 * n],
+            blossom_base: (0..n).map(Some).chain((0..n).map(|_| None)).collect(),
+            blossom_ends: vec![vec![]; 2 * n],
+            best_edge: vec![None; 2 * n],
+            blossom_best_edges: vec![None; 2 * n],
+            unused_blossoms: (n..2 * n).collect(),
+            dual,
+            allow_edge: vec![false; edges.len()],
+            queue: vec![],
+            edges,
+        }
+    }
+
+    /// Gets twice the amount by which the duals of an edge's ends exceed its
+    /// weight, which is never negative, and 0 for edges that can be matched.
+    fn slack(&self, k: usize) -> f64 {
+        let (i, j, w) = self.edges[k];
+        self.dual[i] + self.dual[j] - 2.0 * w
+    }
+
+    /// Gets the nodes inside a blossom, however deeply nested.
+    fn leaves(&self, b: usize) -> Vec<usize> {
+        let mut leaves = vec![];
+        let mut stack = vec![b];
+        while let Some(t) = stack.pop() {
+            if t < self.n {
+                leaves.push(t);
+            } else {
+                stack.extend(self.blossom_children[t].iter().copied());
+            }
+        }
+        leaves
+    }
+
+    /// Gets `list[j]`, counting negative `j` from the end.
+    fn at(list: &[usize], j: isize) -> usize {
+        list[j.rem_euclid(list.len() as isize) as usize]
+    }
+
+    /// Labels the node `w` and its top-level blossom, reached through the
+    /// endpoint `p`. An odd label also labels the node its base is matched to
+    /// even, and even nodes are queued to be scanned.
+    fn assign_label(&mut self, w: usize, label: u8, p: Option<usize>) {
+        let b = self.in_blossom[w];
+        self.label[w] = label;
+        self.label[b] = label;
+        self.label_end[w] = p;
+        self.label_end[b] = p;
+        self.best_edge[w] = None;
+        self.best_edge[b] = None;
+        if label == 1 {
+            let leaves = self.leaves(b);
+            self.queue.extend(leaves);
+        } else {
+            let base = self.blossom_base[b].unwrap();
+            let m = self.mate[base].unwrap();
+            self.assign_label(self.endpoint[m], 1, Some(m ^ 1));
+        }
+    }
+
+    /// Traces back from the even nodes `v` and `w` towards the roots of their
+    /// trees, and gets the base of the new blossom if the paths meet, or
+    /// `None` if they reach different roots, so there's an augmenting path.
+    fn scan_blossom(&mut self, v: usize, w: usize) -> Option<usize> {
+        let mut path = vec![];
+        let mut base = None;
+        let (mut v, mut w) = (Some(v), Some(w));
+        while let Some(x) = v {
+            let b = self.in_blossom[x];
+            if self.label[b] & 4 != 0 {
+                base = self.blossom_base[b];
+                break;
+            }
+            path.push(b);
+            self.label[b] = 5;
+            v = self.label_end[b].map(|p| {
+                let t = self.in_blossom[self.endpoint[p]];
+                self.endpoint[self.label_end[t].unwrap()]
+            });
+            if w.is_some() {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for b in path {
+            self.label[b] = 1;
+        }
+        base
+    }
+
+    /// Makes a new blossom with the given base out of the cycle closed by the
+    /// edge `k` between two even nodes.
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (v, w, _) = self.edges[k];
+        let bb = self.in_blossom[base];
+        let mut bv = self.in_blossom[v];
+        let mut bw = self.in_blossom[w];
+        let b = self.unused_blossoms.pop().unwrap();
+        self.blossom_base[b] = Some(base);
+        self.blossom_parent[b] = None;
+        self.blossom_parent[bb] = Some(b);
+
+        let mut path = vec![];
+        let mut ends = vec![];
+        while bv != bb {
+            self.blossom_parent[bv] = Some(b);
+            path.push(bv);
+            let p = self.label_end[bv].unwrap();
+            ends.push(p);
+            bv = self.in_blossom[self.endpoint[p]];
+        }
+        path.push(bb);
+        path.reverse();
+        ends.reverse();
+        ends.push(2 * k);
+        while bw != bb {
+            self.blossom_parent[bw] = Some(b);
+            path.push(bw);
+            let p = self.label_end[bw].unwrap();
+            ends.push(p ^ 1);
+            bw = self.in_blossom[self.endpoint[p]];
+        }
+        self.blossom_children[b] = path.clone();
+        self.blossom_ends[b] = ends;
+        self.label[b] = 1;
+        self.label_end[b] = self.label_end[bb];
+        self.dual[b] = 0.0;
+        for v in self.leaves(b) {
+            // odd nodes become even inside the blossom
+            if self.label[self.in_blossom[v]] == 2 {
+                self.queue.push(v);
+            }
+            self.in_blossom[v] = b;
+        }
+
+        let mut best_edge_to: Vec<Option<usize>> = vec![None; 2 * self.n];
+        for &bv in &path {
+            let candidates: Vec<usize> = match self.blossom_best_edges[bv].take() {
+                Some(list) => list,
+                None => self
+                    .leaves(bv)
+                    .into_iter()
+                    .flat_map(|v| self.neighbor_ends[v].iter().map(|p| p / 2))
+                    .collect(),
+            };
+            for k in candidates {
+                let (i, j, _) = self.edges[k];
+                let j = if self.in_blossom[j] == b { i } else { j };
+                let bj = self.in_blossom[j];
+                if bj != b
+                    && self.label[bj] == 1
+                    && best_edge_to[bj].is_none_or(|best| self.slack(k) < self.slack(best))
+                {
+                    best_edge_to[bj] = Some(k);
+                }
+            }
+            self.best_edge[bv] = None;
+        }
+        let best: Vec<usize> = best_edge_to.into_iter().flatten().collect();
+        self.best_edge[b] = best
+            .iter()
+            .copied()
+            .min_by(|&x, &y| self.slack(x).total_cmp(&self.slack(y)));
+        self.blossom_best_edges[b] = Some(best);
+    }
+
+    /// Takes apart a top-level blossom. In the middle of a stage, an odd
+    /// blossom's sub-blossoms get the labels they'd have had on their own; at
+    /// the end of a stage, sub-blossoms whose duals are 0 are taken apart too.
+    fn expand_blossom(&mut self, b: usize, end_stage: bool) {
+        let children = self.blossom_children[b].clone();
+        for &s in &children {
+            self.blossom_parent[s] = None;
+            if s < self.n {
+                self.in_blossom[s] = s;
+            } else if end_stage && self.dual[s] == 0.0 {
+                self.expand_blossom(s, end_stage);
+            } else {
+                for v in self.leaves(s) {
+                    self.in_blossom[v] = s;
+                }
+            }
+        }
+
+        if !end_stage && self.label[b] == 2 {
+            // relabel the even-length path around the blossom from the child
+            // it was entered through to its base
+            let entry = self.label_end[b].unwrap();
+            let entry_child = self.in_blossom[self.endpoint[entry ^ 1]];
+            let len = children.len() as isize;
+            let mut j = children.iter().position(|&c| c == entry_child).unwrap() as isize;
+            let (step, trick) = if j & 1 == 1 {
+                j -= len;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+            let mut p = entry;
+            while j != 0 {
+                let q = Self::at(&self.blossom_ends[b], j - trick as isize);
+                self.label[self.endpoint[p ^ 1]] = 0;
+                self.label[self.endpoint[q ^ trick ^ 1]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, Some(p));
+                self.allow_edge[q / 2] = true;
+                j += step;
+                p = Self::at(&self.blossom_ends[b], j - trick as isize) ^ trick;
+                self.allow_edge[p / 2] = true;
+                j += step;
+            }
+            let bv = Self::at(&children, j);
+            let v = self.endpoint[p ^ 1];
+            self.label[v] = 2;
+            self.label[bv] = 2;
+            self.label_end[v] = Some(p);
+            self.label_end[bv] = Some(p);
+            self.best_edge[bv] = None;
+            j += step;
+            // the rest of the children aren't on the path, but some of their
+            // nodes may have been reached from outside
+            while Self::at(&children, j) != entry_child {
+                let bv = Self::at(&children, j);
+                j += step;
+                if self.label[bv] == 1 {
+                    continue;
+                }
+                if let Some(v) = self.leaves(bv).into_iter().find(|&v| self.label[v] != 0) {
+                    let base = self.blossom_base[bv].unwrap();
+                    self.label[v] = 0;
+                    self.label[self.endpoint[self.mate[base].unwrap()]] = 0;
+                    self.assign_label(v, 2, self.label_end[v]);
+                }
+            }
+        }
+
+        self.label[b] = 0;
+        self.label_end[b] = None;
+        self.blossom_children[b].clear();
+        self.blossom_ends[b].clear();
+        self.blossom_base[b] = None;
+        self.blossom_best_edges[b] = None;
+        self.best_edge[b] = None;
+        self.unused_blossoms.push(b);
+    }
+
+    /// Flips the matched and unmatched edges on the even-length path through
+    /// the blossom `b` from the node `v` to its base, making `v` the new base.
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossom_parent[t] != Some(b) {
+            t = self.blossom_parent[t].unwrap();
+        }
+        if t >= self.n {
+            self.augment_blossom(t, v);
+        }
+        let len = self.blossom_children[b].len() as isize;
+        let i = self.blossom_children[b]
+            .iter()
+            .position(|&c| c == t)
+            .unwrap();
+        let mut j = i as isize;
+        let (step, trick) = if i & 1 == 1 {
+            j -= len;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+        while j != 0 {
+            j += step;
+            let t = Self::at(&self.blossom_children[b], j);
+            let p = Self::at(&self.blossom_ends[b], j - trick as isize) ^ trick;
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+            j += step;
+            let t = Self::at(&self.blossom_children[b], j);
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+            self.mate[self.endpoint[p]] = Some(p ^ 1);
+            self.mate[self.endpoint[p ^ 1]] = Some(p);
+        }
+        self.blossom_children[b].rotate_left(i);
+        self.blossom_ends[b].rotate_left(i);
+        self.blossom_base[b] = self.blossom_base[self.blossom_children[b][0]];
+    }
+
+    /// Flips the matched and unmatched edges along the augmenting path through
+    /// the edge `k`, which joins the trees of two unmatched nodes.
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            loop {
+                let bs = self.in_blossom[s];
+                if bs >= self.n {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = Some(p);
+                let Some(q) = self.label_end[bs] else {
+                    break;
+                };
+                let bt = self.in_blossom[self.endpoint[q]];
+                let r = self.label_end[bt].unwrap();
+                s = self.endpoint[r];
+                let j = self.endpoint[r ^ 1];
+                if bt >= self.n {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = Some(r);
+                p = r ^ 1;
+            }
+        }
+    }
+
+    /// Runs stages of growing alternating trees from the unmatched nodes,
+    /// changing the duals whenever they get stuck, until no augmenting path
+    /// would make the matching heavier.
+    fn solve(&mut self) {
+        let n = self.n;
+        for _ in 0..n {
+            self.label.fill(0);
+            self.best_edge.fill(None);
+            self.blossom_best_edges[n..].fill(None);
+            self.allow_edge.fill(false);
+            self.queue.clear();
+            for v in 0..n {
+                if self.mate[v].is_none() && self.label[self.in_blossom[v]] == 0 {
+                    self.assign_label(v, 1, None);
+                }
+            }
+
+            let mut augmented = false;
+            loop {
+                while let Some(v) = self.queue.pop() {
+                    for i in 0..self.neighbor_ends[v].len() {
+                        let p = self.neighbor_ends[v][i];
+                        let (k, w) = (p / 2, self.endpoint[p]);
+                        if self.in_blossom[v] == self.in_blossom[w] {
+                            continue;
+                        }
+                        let mut slack = 0.0;
+                        if !self.allow_edge[k] {
+                            slack = self.slack(k);
+                            self.allow_edge[k] = slack <= 0.0;
+                        }
+                        let bw = self.in_blossom[w];
+                        if self.allow_edge[k] {
+                            if self.label[bw] == 0 {
+                                self.assign_label(w, 2, Some(p ^ 1));
+                            } else if self.label[bw] == 1 {
+                                match self.scan_blossom(v, w) {
+                                    Some(base) => self.add_blossom(base, k),
+                                    None => {
+                                        self.augment_matching(k);
+                                        augmented = true;
+                                        break;
+                                    }
+                                }
+                            } else if self.label[w] == 0 {
+                                self.label[w] = 2;
+                                self.label_end[w] = Some(p ^ 1);
+                            }
+                        } else if self.label[bw] == 1 {
+                            let b = self.in_blossom[v];
+                            if self.best_edge[b].is_none_or(|best| slack < self.slack(best)) {
+                                self.best_edge[b] = Some(k);
+                            }
+                        } else if self.label[w] == 0
+                            && self.best_edge[w].is_none_or(|best| slack < self.slack(best))
+                        {
+                            self.best_edge[w] = Some(k);
+                        }
+                    }
+                    if augmented {
+                        break;
+                    }
+                }
+                if augmented {
+                    break;
+                }
+
+                let mut delta = self.dual[..n].iter().copied().fold(f64::INFINITY, f64::min);
+                let mut dual_step = DualStep::Stop;
+                for v in 0..n {
+                    if let (0, Some(k)) = (self.label[self.in_blossom[v]], self.best_edge[v]) {
+                        if self.slack(k) < delta {
+                            delta = self.slack(k);
+                            dual_step = DualStep::Allow(k);
+                        }
+                    }
+                }
+                for b in 0..2 * n {
+                    if let (None, 1, Some(k)) =
+                        (self.blossom_parent[b], self.label[b], self.best_edge[b])
+                    {
+                        if self.slack(k) / 2.0 < delta {
+                            delta = self.slack(k) / 2.0;
+                            dual_step = DualStep::Allow(k);
+                        }
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossom_base[b].is_some()
+                        && self.blossom_parent[b].is_none()
+                        && self.label[b] == 2
+                        && self.dual[b] < delta
+                    {
+                        delta = self.dual[b];
+                        dual_step = DualStep::Expand(b);
+                    }
+                }
+
+                for v in 0..n {
+                    match self.label[self.in_blossom[v]] {
+                        1 => self.dual[v] -= delta,
+                        2 => self.dual[v] += delta,
+                        _ => {}
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossom_base[b].is_some() && self.blossom_parent[b].is_none() {
+                        match self.label[b] {
+                            1 => self.dual[b] += delta,
+                            2 => self.dual[b] -= delta,
+                            _ => {}
+                        }
+                    }
+                }
+
+                match dual_step {
+                    DualStep::Stop => break,
+                    DualStep::Allow(k) => {
+                        self.allow_edge[k] = true;
+                        let (i, j, _) = self.edges[k];
+                        let even = if self.label[self.in_blossom[i]] == 1 {
+                            i
+                        } else {
+                            j
+                        };
+                        self.queue.push(even);
+                    }
+                    DualStep::Expand(b) => self.expand_blossom(b, false),
+                }
+            }
+            if !augmented {
+                break;
+            }
+
+            for b in n..2 * n {
+                if self.blossom_parent[b].is_none()
+                    && self.blossom_base[b].is_some()
+                    && self.label[b] == 1
+                    && self.dual[b] == 0.0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+    }
+}
+
+/// Gets a maximum-weight matching of the graph on nodes `0..n` with the given
+/// weighted edges, as each node's mate. This is the O(V³) primal-dual version
+/// of Edmonds' blossom algorithm, after van Rantwijk's implementation.
+fn max_weight_matching(n: usize, edges: Vec<(usize, usize, f64)>) -> Vec<Option<usize>> {
+    let mut state = WeightedBlossom::new(n, edges);
+    state.solve();
+    state
+        .mate
+        .iter()
+        .map(|p| p.map(|p| state.endpoint[p]))
+        .collect()
+}
+
+/// Finds a maximum-weight b-matching: a set of edges where each node is the
+/// endpoint of at most `b[node]` of them, with the largest total weight. Nodes
+/// missing from `b` can't be matched at all. With every `b` 1 this is a
+/// maximum-weight matching, and with every weight 1 as well it's a maximum
+/// matching. Edges with weight 0 or less are never chosen, and neither are
+/// self-loops. The edges are in ascending order of index.
+///
+/// Bipartite graphs reduce to min-cost flow: the source feeds each node on one
+/// side up to its `b`, each edge carries one unit across at a cost of minus its
+/// weight, and each node on the other side drains up to its `b` to the sink.
+/// Other graphs use the usual gadget reduction to a maximum-weight matching:
+/// each node is split into `b` copies, and each edge `uv` becomes a path from
+/// every copy of `u`, through two new nodes, to every copy of `v`, with all
+/// three steps having the edge's weight. Matching the middle step alone is
+/// worth as much as leaving the edge out, and matching both outer steps, which
+/// uses up a copy at each end, is worth twice that: choosing the edge.
+pub fn b_matching<N, E, G: GraphView<N, E, Undirected>, F: Fn(&Edge<E>) -> f64>(
+    graph: &G,
+    b: &HashMap<NodeInd, usize>,
+    weight: F,
+) -> Vec<EdgeInd> {
+    match two_color(graph) {
+        Some(side) => bipartite_b_matching(graph, &side, b, weight),
+        None => gadget_b_matching(graph, b, weight),
+    }
+}
+
+/// Does [`b_matching`] on a bipartite graph split into the given sides, with
+/// min-cost flow.
+fn bipartite_b_matching<N, E, G: GraphView<N, E, Undirected>, F: Fn(&Edge<E>) -> f64>(
+    graph: &G,
+    side: &HashMap<NodeInd, bool>,
+    b: &HashMap<NodeInd, usize>,
+    weight: F,
+) -> Vec<EdgeInd> {
+    let nodes = graph.nodes_sorted();
+    let pos: HashMap<NodeInd, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let (source, sink) = (nodes.len(), nodes.len() + 1);
+
+    let mut network = FlowNetwork::new(nodes.len() + 2);
+    for &u in &nodes {
+        let cap = b.get(&u).copied().unwrap_or(0) as f64;
+        if side[&u] {
+            network.add_arc(pos[&u], sink, cap);
+        } else {
+            network.add_arc(source, pos[&u], cap);
+        }
+    }
+    let mut arcs = vec![];
+    for e in graph.edges() {
+        let (mut u, mut v) = graph.edge_endpoints(&e);
+        if side[&u] {
+            std::mem::swap(&mut u, &mut v);
+        }
+        let w = weight(graph.edge(&e));
+        arcs.push((e, network.add_costed_arc(pos[&u], pos[&v], 1.0, -w)));
+    }
+    network.cheapest_flow(source, sink);
+
+    let mut matching: Vec<EdgeInd> = arcs
+        .into_iter()
+        .filter(|&(_, arc)| network.flow(arc) > 0.5)
+        .map(|(e, _)| e)
+        .collect();
+    matching.sort_unstable();
+    matching
+}
+
+/// Does [`b_matching`] on any graph, with the gadget reduction to a
+/// maximum-weight matching.
+fn gadget_b_matching<N, E, G: GraphView<N, E, Undirected>, F: Fn(&Edge<E>) -> f64>(
+    graph: &G,
+    b: &HashMap<NodeInd, usize>,
+    weight: F,
+) -> Vec<EdgeInd> {
+    let nodes = graph.nodes_sorted();
+    let pos: HashMap<NodeInd, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let edges: Vec<(EdgeInd, usize, usize, f64)> = graph
+        .edges_sorted()
+        .into_iter()
+        .filter_map(|e| {
+            let (u, v) = graph.edge_endpoints(&e);
+            let w = weight(graph.edge(&e));
+            (u != v && w > 0.0).then(|| (e, pos[&u], pos[&v], w))
+        })
+        .collect();
+
+    // a node never needs more copies than it has edges that could be chosen
+    let mut degree = vec![0; nodes.len()];
+    for &(_, u, v, _) in &edges {
+        degree[u] += 1;
+        degree[v] += 1;
+    }
+    let mut copies = vec![];
+    let mut num_copies = 0;
+    for (i, n) in nodes.iter().enumerate() {
+        let count = b.get(n).copied().unwrap_or(0).min(degree[i]);
+        copies.push(num_copies..num_copies + count);
+        num_copies += count;
+    }
+
+    // the two middle nodes of edge k's path are num_copies + 2k and one after
+    let mut gadget = vec![];
+    for (k, &(_, u, v, w)) in edges.iter().enumerate() {
+        let (near_u, near_v) = (num_copies + 2 * k, num_copies + 2 * k + 1);
+        gadget.push((near_u, near_v, w));
+        gadget.extend(copies[u].clone().map(|c| (c, near_u, w)));
+        gadget.extend(copies[v].clone().map(|c| (near_v, c, w)));
+    }
+    let mate = max_weight_matching(num_copies + 2 * edges.len(), gadget);
+
+    let is_copy = |m: Option<usize>| m.is_some_and(|m| m < num_copies);
+    edges
+        .iter()
+        .enumerate()
+        .filter(|&(k, _)| {
+            is_copy(mate[num_copies + 2 * k]) && is_copy(mate[num_copies + 2 * k + 1])
+        })
+        .map(|(_, &(e, ..))| e)
+        .collect()
+}
+
+/// Covers a DAG with as few paths as possible, with every node on exactly one
+/// path, and gets the paths, each from its first node to its last, in order of
+/// first node. A lone node is a path on its own.
+///
+/// Each edge used by the paths joins the end of one to the start of the next,
+/// so no two leave the same node or enter the same node: they're a matching in
+/// the bipartite graph with the nodes on both sides, found here with max flow,
+/// and every matched edge leaves one fewer path. If the DAG is transitively
+/// closed, Dilworth's theorem says the number of paths is the size of its
+/// largest antichain, the largest set of nodes with no path between any two.
+/// The graph should be acyclic: nodes on a cycle can be left out otherwise.
+pub fn min_path_cover<N, E, G: GraphView<N, E, Directed>>(dag: &G) -> Vec<Vec<NodeInd>> {
+    let nodes = dag.nodes_sorted();
+    let n = nodes.len();
+    let pos: HashMap<NodeInd, usize> = nodes.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    // node i leaves from i and is entered at n + i
+    let (source, sink) = (2 * n, 2 * n + 1);
+
+    let mut network = FlowNetwork::new(2 * n + 2);
+    for i in 0..n {
+        network.add_arc(source, i, 1.0);
+        network.add_arc(n + i, sink, 1.0);
+    }
+    let mut arcs = vec![];
+    for e in dag.edges() {
+        let (u, v) = dag.edge_endpoints(&e);
+        if u != v {
+            arcs.push((pos[&u], pos[&v], network.add_arc(pos[&u], n + pos[&v], 1.0)));
+        }
+    }
+    network.max_flow(source, sink);
+
+    let mut next: Vec<Option<usize>> = vec![None; n];
+    let mut has_prev = vec![false; n];
+    for (u, v, arc) in arcs {
+        if network.flow(arc) > 0.5 {
+            next[u] = Some(v);
+            has_prev[v] = true;
+        }
+    }
+    (0..n)
+        .filter(|&i| !has_prev[i])
+        .map(|start| {
+            let mut path = vec![nodes[start]];
+            let mut curr = start;
+            while let Some(v) = next[curr] {
+                path.push(nodes[v]);
+                curr = v;
+            }
+            path
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adj_list_graph::ALGraph;
+    use crate::test_util::{graph_from_edges, weighted_graph_from_edges};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashSet;
+
+    /// Checks that no two edges share an endpoint.
+    fn assert_matching(g: &ALGraph<(), (), Undirected>, matching: &[EdgeInd]) {
+        let mut covered = HashSet::new();
+        for e in matching {
+            let (u, v) = g.edge_endpoints(e);
+            assert!(u != v && covered.insert(u) && covered.insert(v));
+        }
+    }
+
+    /// Gets the size of a maximum matching by trying everything.
+    fn brute_force(edges: &[(NodeInd, NodeInd)], used: &mut Vec<bool>) -> usize {
+        let Some((&(u, v), rest)) = edges.split_first() else {
+            return 0;
+        };
+        let mut best = brute_force(rest, used);
+        if u != v && !used[u] && !used[v] {
+            used[u] = true;
+            used[v] = true;
+            best = best.max(1 + brute_force(rest, used));
+            used[u] = false;
+            used[v] = false;
+        }
+        best
+    }
+
+    #[test]
+    fn test_matching_odd_cycle() {
+        let c5 = graph_from_edges::<Undirected>(5, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)]);
+        let matching = maximum_matching(&c5);
+        assert_eq!(matching.len(), 2);
+        assert_matching(&c5, &matching);
+    }
+
+    #[test]
+    fn test_matching_perfect() {
+        // a triangle with a tail at each corner: a greedy matching can take a
+        // triangle edge and get stuck, but the tails give a perfect matching
+        let g =
+            graph_from_edges::<Undirected>(6, &[(0, 1), (1, 2), (2, 0), (0, 3), (1, 4), (2, 5)]);
+        let matching = maximum_matching(&g);
+        assert_eq!(matching, vec![3, 4, 5]);
+
+        // the Petersen graph isn't bipartite, but has a perfect matching
+        let mut edges = vec![];
+        for i in 0..5 {
+            edges.push((i, (i + 1) % 5));
+            edges.push((i, i + 5));
+            edges.push((i + 5, (i + 2) % 5 + 5));
+        }
+        let petersen = graph_from_edges::<Undirected>(10, &edges);
+        let matching = maximum_matching(&petersen);
+        assert_eq!(matching.len(), 5);
+        assert_matching(&petersen, &matching);
+    }
+
+    #[test]
+    fn test_matching_random() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..200 {
+            let n = rng.gen_range(1..9);
+            let m = rng.gen_range(0..13);
+            let edges: Vec<(NodeInd, NodeInd)> = (0..m)
+                .map(|_| (rng.gen_range(0..n), rng.gen_range(0..n)))
+                .collect();
+            let g = graph_from_edges::<Undirected>(n, &edges);
+            let matching = maximum_matching(&g);
+            assert_matching(&g, &matching);
+            assert_eq!(matching.len(), brute_force(&edges, &mut vec![false; n]));
+        }
+    }
+
+    #[test]
+    fn test_b_matching_unit() {
+        // with every b and weight 1, it's a maximum matching, whether or not
+        // the graph is bipartite
+        let mut rng = StdRng::seed_from_u64(12);
+        for _ in 0..100 {
+            let n = rng.gen_range(1..9);
+            let edges: Vec<(NodeInd, NodeInd)> = (0..rng.gen_range(0..14))
+                .map(|_| (rng.gen_range(0..n), rng.gen_range(0..n)))
+                .collect();
+            let g = graph_from_edges::<Undirected>(n, &edges);
+            let b: HashMap<NodeInd, usize> = (0..n).map(|n| (n, 1)).collect();
+            let matching = b_matching(&g, &b, |_| 1.0);
+            assert_matching(&g, &matching);
+            assert_eq!(matching.len(), maximum_matching(&g).len());
+        }
+    }
+
+    #[test]
+    fn test_b_matching_weighted() {
+        // hub 0 can take two of its three edges, and should take the heaviest
+        // two, which leaves 4 to take its edge to 5
+        let g = weighted_graph_from_edges::<Undirected>(
+            6,
+            &[
+                (0, 1, 5.0),
+                (0, 2, 1.0),
+                (0, 3, 4.0),
+                (4, 3, 2.0),
+                (4, 5, 3.0),
+            ],
+        );
+        let b: HashMap<NodeInd, usize> = [(0, 2), (1, 1), (2, 1), (3, 1), (4, 1), (5, 1)]
+            .into_iter()
+            .collect();
+        assert_eq!(b_matching(&g, &b, |e| e.data), vec![0, 2, 4]);
+
+        // 4 may now take two edges, but its other edge goes to 3, which 0 already uses
+        let mut b = b;
+        b.insert(4, 2);
+        assert_eq!(b_matching(&g, &b, |e| e.data), vec![0, 2, 4]);
+        // and with 3 allowed two as well, everything but the lightest edge fits
+        b.insert(3, 2);
+        assert_eq!(b_matching(&g, &b, |e| e.data), vec![0, 2, 3, 4]);
+        // nodes missing from b are never matched
+        b.remove(&0);
+        assert_eq!(b_matching(&g, &b, |e| e.data), vec![3, 4]);
+    }
+
+    #[test]
+    fn test_b_matching_odd_cycle() {
+        let triangle = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0)]);
+        let b: HashMap<NodeInd, usize> = (0..3).map(|n| (n, 1)).collect();
+        assert_eq!(b_matching(&triangle, &b, |_| 1.0).len(), 1);
+        // with two each, the whole triangle fits, which no flow can give
+        let b: HashMap<NodeInd, usize> = (0..3).map(|n| (n, 2)).collect();
+        assert_eq!(b_matching(&triangle, &b, |_| 1.0), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_b_matching_random() {
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..300 {
+            let n = rng.gen_range(1..7);
+            let edges: Vec<(NodeInd, NodeInd, f64)> = (0..rng.gen_range(0..10))
+                .map(|_| {
+                    let w = rng.gen_range(-2..10) as f64;
+                    (rng.gen_range(0..n), rng.gen_range(0..n), w)
+                })
+                .collect();
+            let g = weighted_graph_from_edges::<Undirected>(n, &edges);
+            let b: HashMap<NodeInd, usize> = (0..n).map(|n| (n, rng.gen_range(0..3))).collect();
+
+            let matching = b_matching(&g, &b, |e| e.data);
+            let mut used = vec![0; n];
+            for e in &matching {
+                let (u, v) = g.edge_endpoints(e);
+                assert_ne!(u, v);
+                used[u] += 1;
+                used[v] += 1;
+            }
+            assert!((0..n).all(|u| used[u] <= b[&u]));
+
+            // try every set of edges
+            let mut best = 0.0;
+            for set in 0..1usize << edges.len() {
+                let mut used = vec![0; n];
+                let mut total = 0.0;
+                for (k, &(u, v, w)) in edges.iter().enumerate() {
+                    if set >> k & 1 == 1 && u != v {
+                        used[u] += 1;
+                        used[v] += 1;
+                        total += w;
+                    }
+                }
+                if (0..n).all(|u| used[u] <= b[&u]) && total > best {
+                    best = total;
+                }
+            }
+            let total: f64 = matching.iter().map(|e| g.edge(e).data).sum();
+            assert_eq!(total, best);
+        }
+    }
+
+    #[test]
+    fn test_min_path_cover() {
+        // two chains with a shortcut from one to the other: 2 and 5 can't
+        // reach each other, so no fewer than two paths will do
+        let g = graph_from_edges::<Directed>(6, &[(0, 1), (1, 2), (3, 4), (4, 5), (0, 4)]);
+        assert_eq!(min_path_cover(&g), vec![vec![0, 1, 2], vec![3, 4, 5]]);
+
+        let lone = graph_from_edges::<Directed>(2, &[]);
+        assert_eq!(min_path_cover(&lone), vec![vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn test_min_path_cover_dilworth() {
+        // on transitively closed DAGs, the number of paths is the size of the
+        // largest antichain, found by trying every set of nodes
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..20 {
+            let n = rng.gen_range(1..8);
+            let mut below: Vec<Vec<bool>> = (0..n)
+                .map(|i| (0..n).map(|j| i < j && rng.gen_bool(0.3)).collect())
+                .collect();
+            for k in 0..n {
+                for i in 0..n {
+                    for j in 0..n {
+                        below[i][j] |= below[i][k] && below[k][j];
+                    }
+                }
+            }
+            let edges: Vec<(NodeInd, NodeInd)> = (0..n)
+                .flat_map(|i| (0..n).map(move |j| (i, j)))
+                .filter(|&(i, j)| below[i][j])
+                .collect();
+            let g = graph_from_edges::<Directed>(n, &edges);
+
+            let paths = min_path_cover(&g);
+            let mut covered: Vec<NodeInd> = paths.concat();
+            covered.sort_unstable();
+            assert_eq!(covered, (0..n).collect::<Vec<_>>());
+            for path in &paths {
+                assert!(path.windows(2).all(|w| below[w[0]][w[1]]));
+            }
+
+            let antichain = (0..1usize << n)
+                .filter(|set| {
+                    (0..n).all(|i| {
+                        (0..n).all(|j| set & (1 << i) == 0 || set & (1 << j) == 0 || !below[i][j])
+                    })
+                })
+                .map(|set| set.count_ones() as usize)
+                .max()
+                .unwrap();
+            assert_eq!(paths.len(), antichain);
+        }
+    }
+}
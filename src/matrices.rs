@@ -0,0 +1,240 @@
+//! Exporting graphs as matrices. Rows and columns for nodes are in ascending
+//! order of node index, and those for edges are in ascending order of edge
+//! index, so removed nodes and edges don't leave gaps.
+
+use std::collections::HashMap;
+
+use crate::graph_base::{Edge, GraphType, GraphView, NodeInd, Undirected};
+
+/// Gets the position of each node in the compacted ordering.
+fn node_positions(nodes: &[NodeInd]) -> HashMap<NodeInd, usize> {
+    nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect()
+}
+
+/// Gets the nodes × edges incidence matrix. For directed graphs, each edge's
+/// column has -1 at its start and 1 at its end, so a self-loop's column is all
+/// zeros. For undirected graphs, there's a 1 at both ends, or a 2 for a
+/// self-loop.
+pub fn incidence_matrix<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> Vec<Vec<i8>> {
+    let nodes = graph.nodes_sorted();
+    let pos = node_positions(&nodes);
+    let edges = graph.edges_sorted();
+
+    let mut matrix = vec![vec![0; edges.len()]; nodes.len()];
+    for (j, e) in edges.iter().enumerate() {
+        let (start, end) = graph.edge_endpoints(e);
+        if graph.is_directed() {
+            matrix[pos[&start]][j] -= 1;
+            matrix[pos[&end]][j] += 1;
+        } else {
+            matrix[pos[&start]][j] += 1;
+            matrix[pos[&end]][j] += 1;
+        }
+    }
+    matrix
+}
+
+/// Gets the adjacency matrix in the sparse coordinate (COO) format that ML
+/// libraries take, as the rows, columns, and values of its entries, with every
+/// value 1. Each edge is an entry from its start to its end, and for
+/// undirected graphs, another from its end to its start, unless it's a
+/// self-loop. Entries are in ascending order of edge index, and parallel
+/// edges are separate entries, which most libraries add together.
+pub fn to_sparse_triplets<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+) -> (Vec<usize>, Vec<usize>, Vec<f64>) {
+    to_sparse_triplets_weighted(graph, |_| 1.0)
+}
+
+/// Like [`to_sparse_triplets`], but with the value of each edge's entries
+/// given by `weight`.
+pub fn to_sparse_triplets_weighted<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    weight: F,
+) -> (Vec<usize>, Vec<usize>, Vec<f64>)
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let pos = node_positions(&graph.nodes_sorted());
+    let edges = graph.edges_sorted();
+
+    let (mut rows, mut cols, mut values) = (vec![], vec![], vec![]);
+    for e in edges {
+        let edge = graph.edge(&e);
+        let (start, end) = (pos[&edge.start], pos[&edge.end]);
+        let w = weight(edge);
+        rows.push(start);
+        cols.push(end);
+        values.push(w);
+        if !graph.is_directed() && start != end {
+            rows.push(end);
+            cols.push(start);
+            values.push(w);
+        }
+    }
+    (rows, cols, values)
+}
+
+/// Gets the Laplacian matrix D - A of an undirected graph, where D is the
+/// diagonal matrix of node degrees and A is the adjacency matrix. Parallel
+/// edges count once each, and self-loops are ignored, because they'd add the
+/// same amount to both D and A.
+pub fn laplacian_matrix<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> Vec<Vec<f64>> {
+    integer_laplacian(graph)
+        .into_iter()
+        .map(|row| row.into_iter().map(|x| x as f64).collect())
+        .collect()
+}
+
+/// Gets the Laplacian with exact integer entries.
+fn integer_laplacian<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> Vec<Vec<i128>> {
+    let nodes = graph.nodes_sorted();
+    let pos = node_positions(&nodes);
+
+    let mut matrix = vec![vec![0; nodes.len()]; nodes.len()];
+    for e in graph.edges() {
+        let (start, end) = graph.edge_endpoints(&e);
+        if start == end {
+            continue;
+        }
+        let (i, j) = (pos[&start], pos[&end]);
+        matrix[i][i] += 1;
+        matrix[j][j] += 1;
+        matrix[i][j] -= 1;
+        matrix[j][i] -= 1;
+    }
+    matrix
+}
+
+/// Gets the number of spanning trees of an undirected graph, using Kirchhoff's
+/// matrix-tree theorem: it's the determinant of the Laplacian with any one row
+/// and the matching column removed. Parallel edges give different spanning
+/// trees. The determinant is computed exactly, so this is only limited by
+/// overflow, not by rounding. The empty graph is counted as having no
+/// spanning trees.
+pub fn spanning_tree_count<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> u128 {
+    let laplacian = integer_laplacian(graph);
+    if laplacian.is_empty() {
+        return 0;
+    }
+    let minor: Vec<Vec<i128>> = laplacian[1..].iter().map(|row| row[1..].to_vec()).collect();
+    // the Laplacian is positive semidefinite, so its minors are non-negative
+    determinant(minor).unsigned_abs()
+}
+
+/// Gets the determinant of a square integer matrix using the Bareiss
+/// algorithm, a fraction-free version of Gaussian elimination where every
+/// division is exact.
+fn determinant(mut m: Vec<Vec<i128>>) -> i128 {
+    let n = m.len();
+    let mut sign = 1;
+    let mut prev_pivot = 1;
+    for k in 0..n {
+        if m[k][k] == 0 {
+            match (k + 1..n).find(|&i| m[i][k] != 0) {
+                Some(i) => {
+                    m.swap(i, k);
+                    sign = -sign;
+                }
+                None => return 0,
+            }
+        }
+        for i in (k + 1)..n {
+            for j in (k + 1)..n {
+                m[i][j] = (m[i][j] * m[k][k] - m[i][k] * m[k][j]) / prev_pivot;
+            }
+        }
+        prev_pivot = m[k][k];
+    }
+    if n == 0 {
+        1
+    } else {
+        sign * m[n - 1][n - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::{Directed, GraphBase, Undirected};
+    use crate::test_util::graph_from_edges;
+
+    fn column_sums(matrix: &[Vec<i8>]) -> Vec<i8> {
+        (0..matrix[0].len())
+            .map(|j| matrix.iter().map(|row| row[j]).sum())
+            .collect()
+    }
+
+    #[test]
+    fn test_incidence_matrix() {
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3)];
+        let directed = incidence_matrix(&graph_from_edges::<Directed>(4, &edges));
+        assert_eq!(directed[0], vec![-1, 0, 1, 0]);
+        assert_eq!(directed[2], vec![0, 1, -1, -1]);
+        assert_eq!(column_sums(&directed), vec![0; 4]);
+
+        let undirected = incidence_matrix(&graph_from_edges::<Undirected>(4, &edges));
+        assert_eq!(undirected[2], vec![0, 1, 1, 1]);
+        assert_eq!(column_sums(&undirected), vec![2; 4]);
+    }
+
+    #[test]
+    fn test_laplacian_matrix() {
+        let g = graph_from_edges::<Undirected>(4, &[(0, 1), (1, 2), (2, 0), (2, 3), (2, 3)]);
+        let laplacian = laplacian_matrix(&g);
+        assert_eq!(laplacian[2], vec![-1.0, -1.0, 4.0, -2.0]);
+        for (i, row) in laplacian.iter().enumerate() {
+            assert_eq!(row.iter().sum::<f64>(), 0.0);
+            assert_eq!(row[i], g.edges_at(&i).count() as f64);
+        }
+    }
+
+    #[test]
+    fn test_spanning_tree_count() {
+        for n in 3..8 {
+            let cycle: Vec<(NodeInd, NodeInd)> = (0..n).map(|i| (i, (i + 1) % n)).collect();
+            let g = graph_from_edges::<Undirected>(n, &cycle);
+            assert_eq!(spanning_tree_count(&g), n as u128);
+        }
+
+        let k4 =
+            graph_from_edges::<Undirected>(4, &[(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+        assert_eq!(spanning_tree_count(&k4), 16);
+
+        // Cayley's formula: n^(n - 2) labeled trees on n nodes
+        let mut k8 = vec![];
+        for i in 0..8 {
+            for j in (i + 1)..8 {
+                k8.push((i, j));
+            }
+        }
+        let g = graph_from_edges::<Undirected>(8, &k8);
+        assert_eq!(spanning_tree_count(&g), 8u128.pow(6));
+
+        let disconnected = graph_from_edges::<Undirected>(4, &[(0, 1), (2, 3)]);
+        assert_eq!(spanning_tree_count(&disconnected), 0);
+    }
+
+    #[test]
+    fn test_sparse_triplets() {
+        let edges = [(0, 1), (1, 2), (2, 0), (3, 1)];
+        let directed = graph_from_edges::<Directed>(5, &edges);
+        let (rows, cols, values) = to_sparse_triplets(&directed);
+        assert_eq!(rows.len(), edges.len());
+        assert_eq!(rows, vec![0, 1, 2, 3]);
+        assert_eq!(cols, vec![1, 2, 0, 1]);
+        assert_eq!(values, vec![1.0; 4]);
+
+        let mut undirected = graph_from_edges::<Undirected>(5, &edges);
+        // with a gap in the indices, which gets compacted away
+        undirected.remove_node(&0);
+        let (rows, cols, values) =
+            to_sparse_triplets_weighted(&undirected, |e| (e.start + e.end) as f64);
+        assert_eq!(rows.len(), 2 * undirected.edges().count());
+        assert_eq!(cols.len(), rows.len());
+        assert!(rows.iter().chain(&cols).all(|&i| i < 4));
+        assert_eq!(rows, vec![0, 1, 2, 0]);
+        assert_eq!(cols, vec![1, 0, 0, 2]);
+        assert_eq!(values, vec![3.0, 3.0, 4.0, 4.0]);
+    }
+}
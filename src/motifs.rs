@@ -0,0 +1,153 @@
+//! Counts of small patterns in graphs.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::graph_base::{Directed, GraphView, NodeInd};
+
+/// The names of the 16 types of directed triad, in the order that
+/// [`triad_census`] counts them. Each name gives the number of mutual,
+/// asymmetric, and null pairs among the three nodes, and a letter to tell
+/// apart types with the same numbers: D for down, U for up, C for cyclic, and
+/// T for transitive.
+pub const TRIAD_NAMES: [&str; 16] = [
+    "003", "012", "102", "021D", "021U", "021C", "111D", "111U", "030T", "030C", "201", "120D",
+    "120U", "120C", "210", "300",
+];
+
+/// The type of triad, as an index into [`TRIAD_NAMES`], for each of the 64
+/// ways the six possible edges between three nodes can be present or not. The
+/// edges are given by bits as in [`tricode`].
+const TRICODES: [usize; 64] = [
+    0, 1, 1, 2, 1, 3, 5, 7, 1, 5, 4, 6, 2, 7, 6, 10, 1, 5, 3, 7, 4, 8, 8, 12, 5, 9, 8, 13, 6, 13,
+    11, 14, 1, 4, 5, 6, 5, 8, 9, 13, 3, 8, 8, 11, 7, 12, 13, 14, 2, 6, 7, 10, 6, 11, 13, 14, 7, 13,
+    12, 14, 10, 14, 14, 15,
+];
+
+/// Gets the code for the edges between `v`, `u` and `w`, with a bit for each
+/// of v → u, u → v, v → w, w → v, u → w, and w → u, from lowest to highest.
+fn tricode(arcs: &HashSet<(NodeInd, NodeInd)>, v: NodeInd, u: NodeInd, w: NodeInd) -> usize {
+    [(v, u), (u, v), (v, w), (w, v), (u, w), (w, u)]
+        .iter()
+        .enumerate()
+        .filter(|(_, arc)| arcs.contains(arc))
+        .map(|(i, _)| 1 << i)
+        .sum()
+}
+
+/// Counts the triads of a directed graph: every set of three nodes has one of
+/// 16 types, depending on the edges between them, and this gets how many sets
+/// of each type there are, in the order of [`TRIAD_NAMES`]. Self-loops are
+/// ignored, and parallel edges count once.
+///
+/// This is the algorithm of Batagelj and Mrvar, which only looks at sets
+/// that have an edge in them, so it's fast for sparse graphs: the number of
+/// empty sets is worked out from the rest.
+pub fn triad_census<N, E, G: GraphView<N, E, Directed>>(graph: &G) -> [usize; 16] {
+    let nodes = graph.nodes_sorted();
+    let n = nodes.len();
+    let rank: HashMap<NodeInd, usize> = nodes.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+
+    let mut arcs: HashSet<(NodeInd, NodeInd)> = HashSet::new();
+    let mut nbrs: HashMap<NodeInd, HashSet<NodeInd>> =
+        nodes.iter().map(|&v| (v, HashSet::new())).collect();
+    for e in graph.edges() {
+        let (u, v) = graph.edge_endpoints(&e);
+        if u != v {
+            arcs.insert((u, v));
+            nbrs.get_mut(&u).unwrap().insert(v);
+            nbrs.get_mut(&v).unwrap().insert(u);
+        }
+    }
+
+    let mut census = [0; 16];
+    for &v in &nodes {
+        for &u in &nbrs[&v] {
+            if rank[&u] <= rank[&v] {
+                continue;
+            }
+            // the nodes next to v or u, which are the ones that make a triad
+            // with more than just the edges between v and u
+            let joined: HashSet<NodeInd> = nbrs[&u]
+                .union(&nbrs[&v])
+                .copied()
+                .filter(|&w| w != u && w != v)
+                .collect();
+            let dyad = if arcs.contains(&(v, u)) && arcs.contains(&(u, v)) {
+                2
+            } else {
+                1
+            };
+            census[dyad] += n - joined.len() - 2;
+            for &w in &joined {
+                // count each triad once, from its lowest connected pair
+                if rank[&u] < rank[&w]
+                    || (rank[&v] < rank[&w] && rank[&w] < rank[&u] && !nbrs[&v].contains(&w))
+                {
+                    census[TRICODES[tricode(&arcs, v, u, w)]] += 1;
+                }
+            }
+        }
+    }
+
+    let total = if n < 3 { 0 } else { n * (n - 1) * (n - 2) / 6 };
+    census[0] = total - census[1..].iter().sum::<usize>();
+    census
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::graph_from_edges;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Gets the index of the named triad type.
+    fn triad(name: &str) -> usize {
+        TRIAD_NAMES.iter().position(|&t| t == name).unwrap()
+    }
+
+    #[test]
+    fn test_triad_census_known() {
+        // 0 <-> 1, 1 -> 2, and 3 -> 2, with 4 on its own and a self-loop that
+        // doesn't count
+        let g = graph_from_edges::<Directed>(5, &[(0, 1), (1, 0), (1, 2), (3, 2), (2, 2)]);
+        let mut expected = [0; 16];
+        // {0, 1, 2}
+        expected[triad("111U")] = 1;
+        // {1, 2, 3}
+        expected[triad("021U")] = 1;
+        // {0, 1, 3} and {0, 1, 4}
+        expected[triad("102")] = 2;
+        // {0, 2, 3}, {1, 2, 4} and {2, 3, 4}
+        expected[triad("012")] = 3;
+        // {0, 2, 4}, {0, 3, 4} and {1, 3, 4}
+        expected[triad("003")] = 3;
+        assert_eq!(expected.iter().sum::<usize>(), 10);
+        assert_eq!(triad_census(&g), expected);
+    }
+
+    #[test]
+    fn test_triad_census_random() {
+        // the fast count agrees with checking every set of three nodes
+        let mut rng = StdRng::seed_from_u64(8);
+        for _ in 0..20 {
+            let n = rng.gen_range(1..9);
+            let edges: Vec<(NodeInd, NodeInd)> = (0..rng.gen_range(0..25))
+                .map(|_| (rng.gen_range(0..n), rng.gen_range(0..n)))
+                .collect();
+            let g = graph_from_edges::<Directed>(n, &edges);
+            let arcs: HashSet<(NodeInd, NodeInd)> =
+                edges.iter().copied().filter(|(u, v)| u != v).collect();
+
+            let mut expected = [0; 16];
+            for a in 0..n {
+                for b in a + 1..n {
+                    for c in b + 1..n {
+                        expected[TRICODES[tricode(&arcs, a, b, c)]] += 1;
+                    }
+                }
+            }
+            assert_eq!(triad_census(&g), expected);
+        }
+    }
+}
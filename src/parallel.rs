@@ -0,0 +1,44 @@
+//! Parallel versions of algorithms, using `rayon`. Only available with the
+//! `rayon` feature.
+
+use rayon::prelude::*;
+
+use crate::graph_base::{GraphType, GraphView};
+use crate::traversal::distance_row;
+
+/// Parallel version of
+/// [`all_pairs_shortest_paths`](crate::traversal::all_pairs_shortest_paths),
+/// which runs the BFS from each node on a separate task. This gives the same
+/// result, with the same layout.
+pub fn par_all_pairs_shortest_paths<N, E, Ty: GraphType, G: GraphView<N, E, Ty> + Sync>(
+    graph: &G,
+) -> Vec<Vec<usize>> {
+    let nodes = graph.nodes_sorted();
+    nodes
+        .par_iter()
+        .map(|source| distance_row(graph, source, &nodes))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::Undirected;
+    use crate::test_util::graph_from_edges;
+    use crate::traversal::all_pairs_shortest_paths;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_par_all_pairs_matches_sequential() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let edges: Vec<(usize, usize)> = (0..60)
+            .map(|_| (rng.gen_range(0..40), rng.gen_range(0..40)))
+            .collect();
+        let g = graph_from_edges::<Undirected>(40, &edges);
+        assert_eq!(
+            par_all_pairs_shortest_paths(&g),
+            all_pairs_shortest_paths(&g)
+        );
+    }
+}
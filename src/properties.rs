@@ -0,0 +1,171 @@
+//! Checks for structural properties of graphs.
+
+use std::collections::HashSet;
+
+use crate::graph_base::{EdgeInd, GraphType, GraphView, NodeInd};
+
+/// Whether the graph has any edges going from a node to itself.
+pub fn has_self_loops<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> bool {
+    graph.edges().any(|e| {
+        let (start, end) = graph.edge_endpoints(&e);
+        start == end
+    })
+}
+
+/// Whether the graph has more than one edge connecting the same pair of nodes.
+/// For directed graphs, a -> b and b -> a are not parallel.
+pub fn has_parallel_edges<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> bool {
+    let mut seen: HashSet<(NodeInd, NodeInd)> = HashSet::new();
+    graph.edges().any(|e| !seen.insert(endpoint_key(graph, &e)))
+}
+
+/// Whether the graph is simple: it has no self-loops or parallel edges.
+pub fn is_simple<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> bool {
+    !has_self_loops(graph) && !has_parallel_edges(graph)
+}
+
+/// Gets Newman's degree assortativity coefficient: the Pearson correlation
+/// between the degrees of the nodes at either end of each edge. Positive values
+/// mean high-degree nodes tend to connect to each other, and negative values
+/// mean they tend to connect to low-degree nodes. For directed graphs, this
+/// correlates the out-degree of each edge's start with the in-degree of its
+/// end. Returns `None` when it's undefined: when there are fewer than two
+/// edges, or when every edge has the same degrees at its ends, such as in a
+/// regular graph.
+pub fn degree_assortativity<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(graph: &G) -> Option<f64> {
+    if graph.edges().count() < 2 {
+        return None;
+    }
+
+    let mut pairs: Vec<(f64, f64)> = vec![];
+    for e in graph.edges() {
+        let (start, end) = graph.edge_endpoints(&e);
+        if graph.is_directed() {
+            let out_deg = graph.edges_from(&start).count() as f64;
+            let in_deg = graph.edges_to(&end).count() as f64;
+            pairs.push((out_deg, in_deg));
+        } else {
+            let start_deg = graph.edges_at(&start).count() as f64;
+            let end_deg = graph.edges_at(&end).count() as f64;
+            pairs.push((start_deg, end_deg));
+            pairs.push((end_deg, start_deg));
+        }
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+    let cov: f64 = pairs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let var_x: f64 = pairs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let var_y: f64 = pairs.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some(cov / (var_x * var_y).sqrt())
+}
+
+/// Gets the endpoints of an edge in a form that's equal for parallel edges:
+/// for undirected graphs, the order of the endpoints doesn't matter, so the
+/// smaller one goes first.
+pub(crate) fn endpoint_key<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    e: &EdgeInd,
+) -> (NodeInd, NodeInd) {
+    let (start, end) = graph.edge_endpoints(e);
+    if graph.is_directed() {
+        (start, end)
+    } else {
+        (start.min(end), start.max(end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::{Directed, GraphBase, Undirected};
+    use crate::test_util::{graph_from_edges, weighted_graph_from_edges};
+
+    #[test]
+    fn test_self_loops() {
+        let g = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2)]);
+        assert!(!has_self_loops(&g));
+        assert!(is_simple(&g));
+
+        let g = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 1)]);
+        assert!(has_self_loops(&g));
+        assert!(!has_parallel_edges(&g));
+        assert!(!is_simple(&g));
+    }
+
+    #[test]
+    fn test_parallel_edges() {
+        let g = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 0)]);
+        assert!(has_parallel_edges(&g));
+        assert!(!is_simple(&g));
+
+        // antiparallel edges are fine for directed graphs, but parallel ones aren't
+        let g = graph_from_edges::<Directed>(3, &[(0, 1), (1, 0)]);
+        assert!(!has_parallel_edges(&g));
+        assert!(is_simple(&g));
+
+        let g = graph_from_edges::<Directed>(3, &[(0, 1), (1, 2), (0, 1)]);
+        assert!(has_parallel_edges(&g));
+    }
+
+    #[test]
+    fn test_degree_assortativity() {
+        let star: Vec<(NodeInd, NodeInd)> = (1..6).map(|i| (0, i)).collect();
+        let r = degree_assortativity(&graph_from_edges::<Undirected>(6, &star)).unwrap();
+        assert!((r + 1.0).abs() < 1e-12);
+
+        let cycle: Vec<(NodeInd, NodeInd)> = (0..6).map(|i| (i, (i + 1) % 6)).collect();
+        assert_eq!(
+            degree_assortativity(&graph_from_edges::<Undirected>(6, &cycle)),
+            None
+        );
+
+        // a triangle and a separate edge: every edge joins equal degrees
+        let g = graph_from_edges::<Undirected>(5, &[(0, 1), (1, 2), (2, 0), (3, 4)]);
+        let r = degree_assortativity(&g).unwrap();
+        assert!((r - 1.0).abs() < 1e-12);
+
+        assert_eq!(
+            degree_assortativity(&graph_from_edges::<Undirected>(2, &[(0, 1)])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_merge_parallel_edges() {
+        let mut g = weighted_graph_from_edges::<Directed>(
+            3,
+            &[(0, 1, 2.0), (0, 1, 3.0), (1, 0, 4.0), (1, 2, 1.0)],
+        );
+        g.merge_parallel_edges(|a, b| a + b);
+        assert!(!has_parallel_edges(&g));
+        assert_eq!(g.edges().count(), 3);
+        assert_eq!(g.edge(&0).data, 5.0);
+        assert_eq!(g.edge(&2).data, 4.0);
+    }
+
+    #[test]
+    fn test_simplify() {
+        let mut g = weighted_graph_from_edges::<Undirected>(
+            3,
+            &[
+                (0, 1, 1.0),
+                (1, 0, 2.0),
+                (0, 1, 3.0),
+                (1, 2, 1.0),
+                (2, 2, 1.0),
+            ],
+        );
+        assert!(!is_simple(&g));
+
+        let report = g.simplify(|a, b| a + b);
+        assert_eq!(report.self_loops, 1);
+        assert_eq!(report.parallel_edges, 2);
+        assert!(is_simple(&g));
+        assert_eq!(g.edges().count(), 2);
+        assert_eq!(g.edge(&0).data, 6.0);
+    }
+}
@@ -0,0 +1,1343 @@
+//! Weighted shortest path algorithms. Edge weights are given by a closure that
+//! takes each edge, so the same graph can be used with different weightings.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::graph_base::{Edge, EdgeInd, GraphType, GraphView, NodeInd, Weighted};
+
+/// A node with a priority, ordered so that `BinaryHeap` pops the smallest
+/// score first.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct MinScored(pub f64, pub NodeInd);
+
+impl PartialEq for MinScored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MinScored {}
+
+impl PartialOrd for MinScored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MinScored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .0
+            .total_cmp(&self.0)
+            .then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+/// The result of a single-source search: the distance to each reached node,
+/// and the node and edge that each one was reached from.
+pub(crate) type SearchResult = (HashMap<NodeInd, f64>, HashMap<NodeInd, (NodeInd, EdgeInd)>);
+
+/// Runs Dijkstra's algorithm from `source`, stopping early once `target` is
+/// settled if it's given.
+pub(crate) fn dijkstra_search<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    target: Option<&NodeInd>,
+    weight: F,
+) -> SearchResult
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    dijkstra_search_dir(graph, source, target, weight, false)
+}
+
+/// Like [`dijkstra_search`], but following edges backwards if `backwards` is
+/// set, which gets the distances to `source` instead of from it.
+fn dijkstra_search_dir<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    target: Option<&NodeInd>,
+    weight: F,
+    backwards: bool,
+) -> SearchResult
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let mut dist = HashMap::from([(*source, 0.0)]);
+    let mut pred = HashMap::new();
+    let mut heap = BinaryHeap::from([MinScored(0.0, *source)]);
+    while let Some(MinScored(d, curr)) = heap.pop() {
+        if d > dist[&curr] {
+            // stale entry
+            continue;
+        }
+        if Some(&curr) == target {
+            break;
+        }
+        let edges = if backwards {
+            graph.edges_to(&curr)
+        } else {
+            graph.edges_from(&curr)
+        };
+        for edge in edges {
+            let w = weight(&edge);
+            if w == f64::INFINITY {
+                continue;
+            }
+            let next = edge.opposite(&curr);
+            let next_d = d + w;
+            if dist.get(&next).is_none_or(|&old| next_d < old) {
+                dist.insert(next, next_d);
+                pred.insert(next, (curr, edge.index));
+                heap.push(MinScored(next_d, next));
+            }
+        }
+    }
+    (dist, pred)
+}
+
+/// Follows the predecessors back from `target` to get the path to it, starting
+/// at the search's source.
+pub(crate) fn backtrack(
+    pred: &HashMap<NodeInd, (NodeInd, EdgeInd)>,
+    target: &NodeInd,
+) -> Vec<NodeInd> {
+    backtrack_with_edges(pred, target).0
+}
+
+/// Like [`backtrack`], but also gets the edges along the path.
+pub(crate) fn backtrack_with_edges(
+    pred: &HashMap<NodeInd, (NodeInd, EdgeInd)>,
+    target: &NodeInd,
+) -> (Vec<NodeInd>, Vec<EdgeInd>) {
+    let mut nodes = vec![*target];
+    let mut edges = vec![];
+    let mut curr = *target;
+    while let Some(&(prev, e)) = pred.get(&curr) {
+        nodes.push(prev);
+        edges.push(e);
+        curr = prev;
+    }
+    nodes.reverse();
+    edges.reverse();
+    (nodes, edges)
+}
+
+/// The shortest paths from one source to every node reachable from it, as the
+/// tree of the edge each node was reached by. This is what [`dijkstra_tree`]
+/// and [`bfs_tree`](crate::traversal::bfs_tree) return, so that paths can be
+/// read off for any number of targets without searching again.
+#[derive(Clone, Debug)]
+pub struct ShortestPathTree {
+    source: NodeInd,
+    dist: HashMap<NodeInd, f64>,
+    pred: HashMap<NodeInd, (NodeInd, EdgeInd)>,
+}
+
+impl ShortestPathTree {
+    /// Makes the tree from the results of a search from `source`.
+    pub(crate) fn new(source: NodeInd, (dist, pred): SearchResult) -> Self {
+        Self { source, dist, pred }
+    }
+
+    /// Gets the node the paths start from.
+    pub fn source(&self) -> NodeInd {
+        self.source
+    }
+
+    /// Gets the length of the shortest path to `target`, or `None` if it isn't
+    /// reachable.
+    pub fn distance_to(&self, target: &NodeInd) -> Option<f64> {
+        self.dist.get(target).copied()
+    }
+
+    /// Gets the nodes on the shortest path to `target`, starting at the source,
+    /// or `None` if it isn't reachable.
+    pub fn path_to(&self, target: &NodeInd) -> Option<Vec<NodeInd>> {
+        self.dist
+            .contains_key(target)
+            .then(|| backtrack(&self.pred, target))
+    }
+
+    /// Gets the edges on the shortest path to `target`, in order, or `None` if
+    /// it isn't reachable.
+    pub fn edges_to(&self, target: &NodeInd) -> Option<Vec<EdgeInd>> {
+        self.dist
+            .contains_key(target)
+            .then(|| backtrack_with_edges(&self.pred, target).1)
+    }
+
+    /// Gets the node before `n` on the shortest path to it, or `None` if `n`
+    /// is the source or isn't reachable.
+    pub fn predecessor(&self, n: &NodeInd) -> Option<NodeInd> {
+        self.pred.get(n).map(|(prev, _)| *prev)
+    }
+}
+
+/// Gets the length of the shortest path from `source` to every node reachable
+/// from it, using Dijkstra's algorithm. Weights must be non-negative: use
+/// [`bellman_ford`] otherwise. Edges with infinite weight are never used.
+pub fn dijkstra<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    weight: F,
+) -> HashMap<NodeInd, f64>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    dijkstra_search(graph, source, None, weight).0
+}
+
+/// Like [`dijkstra`], but gets the whole shortest path tree, so paths as well
+/// as distances can be read off it.
+pub fn dijkstra_tree<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    weight: F,
+) -> ShortestPathTree
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    ShortestPathTree::new(*source, dijkstra_search(graph, source, None, weight))
+}
+
+/// Like [`dijkstra`], but using the weight of the edge data.
+pub fn dijkstra_weighted<N, E: Weighted, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    source: &NodeInd,
+) -> HashMap<NodeInd, f64> {
+    dijkstra(graph, source, |e| e.data.weight())
+}
+
+/// Gets the shortest path from `source` to `target` and its length, or `None`
+/// if `target` isn't reachable. Weights must be non-negative.
+pub fn dijkstra_path<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    target: &NodeInd,
+    weight: F,
+) -> Option<(f64, Vec<NodeInd>)>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let (dist, pred) = dijkstra_search(graph, source, Some(target), weight);
+    let d = *dist.get(target)?;
+    Some((d, backtrack(&pred, target)))
+}
+
+/// Like [`dijkstra_path`], but searching forwards from `source` and backwards
+/// from `target` at the same time, always going on with whichever search has
+/// the closer next node, until the two meet in the middle. Each search only
+/// has to get about halfway, so this usually looks at far fewer nodes.
+/// Weights must be non-negative.
+pub fn bidirectional_dijkstra<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    target: &NodeInd,
+    weight: F,
+) -> Option<(f64, Vec<NodeInd>)>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    // everything is kept twice, with the forward search first
+    let mut dist = [
+        HashMap::from([(*source, 0.0)]),
+        HashMap::from([(*target, 0.0)]),
+    ];
+    let mut pred: [HashMap<NodeInd, NodeInd>; 2] = [HashMap::new(), HashMap::new()];
+    let mut heaps = [
+        BinaryHeap::from([MinScored(0.0, *source)]),
+        BinaryHeap::from([MinScored(0.0, *target)]),
+    ];
+    // the shortest path found so far, and the node where its halves meet
+    let mut best = (source == target).then_some((0.0, *source));
+
+    // once either search has run out, it's seen everything it can reach
+    while let (Some(f), Some(b)) = (heaps[0].peek(), heaps[1].peek()) {
+        // any path not found yet is at least this long
+        if best.is_some_and(|(d, _)| f.0 + b.0 >= d) {
+            break;
+        }
+        let side = if f.0 <= b.0 { 0 } else { 1 };
+        let MinScored(d, curr) = heaps[side].pop().unwrap();
+        if d > dist[side][&curr] {
+            continue;
+        }
+        let edges = if side == 0 {
+            graph.edges_from(&curr)
+        } else {
+            graph.edges_to(&curr)
+        };
+        for edge in edges {
+            let w = weight(&edge);
+            if w == f64::INFINITY {
+                continue;
+            }
+            let next = edge.opposite(&curr);
+            let next_d = d + w;
+            if dist[side].get(&next).is_none_or(|&old| next_d < old) {
+                dist[side].insert(next, next_d);
+                pred[side].insert(next, curr);
+                heaps[side].push(MinScored(next_d, next));
+                if let Some(other) = dist[1 - side].get(&next) {
+                    if best.is_none_or(|(d, _)| next_d + other < d) {
+                        best = Some((next_d + other, next));
+                    }
+                }
+            }
+        }
+    }
+
+    let (d, meet) = best?;
+    let mut path = vec![meet];
+    while let Some(&prev) = pred[0].get(path.last().unwrap()) {
+        path.push(prev);
+    }
+    path.reverse();
+    while let Some(&next) = pred[1].get(path.last().unwrap()) {
+        path.push(next);
+    }
+    Some((d, path))
+}
+
+/// Gets the shortest path from `source` to `target` and its length, or `None`
+/// if `target` isn't reachable, using A* search: like [`dijkstra_path`], but
+/// looking at nodes in order of their distance from `source` plus
+/// `heuristic`'s estimate of their distance to `target`, so nodes that seem to
+/// lead the wrong way are put off. The heuristic must be consistent: it's 0 at
+/// `target`, and never drops by more than an edge's weight along that edge,
+/// which means it never overestimates. A heuristic of infinity means the node
+/// can't reach `target` at all. Weights must be non-negative.
+pub fn astar<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F, H>(
+    graph: &G,
+    source: &NodeInd,
+    target: &NodeInd,
+    weight: F,
+    heuristic: H,
+) -> Option<(f64, Vec<NodeInd>)>
+where
+    F: Fn(&Edge<E>) -> f64,
+    H: Fn(&NodeInd) -> f64,
+{
+    let mut dist = HashMap::from([(*source, 0.0)]);
+    let mut pred = HashMap::new();
+    let mut done = HashSet::new();
+    let mut heap = BinaryHeap::from([MinScored(heuristic(source), *source)]);
+    while let Some(MinScored(_, curr)) = heap.pop() {
+        if curr == *target {
+            return Some((dist[target], backtrack(&pred, target)));
+        }
+        if !done.insert(curr) {
+            continue;
+        }
+        let d = dist[&curr];
+        for edge in graph.edges_from(&curr) {
+            let w = weight(&edge);
+            let next = edge.opposite(&curr);
+            let next_d = d + w;
+            if w == f64::INFINITY || done.contains(&next) {
+                continue;
+            }
+            if dist.get(&next).is_none_or(|&old| next_d < old) {
+                let h = heuristic(&next);
+                if h == f64::INFINITY {
+                    continue;
+                }
+                dist.insert(next, next_d);
+                pred.insert(next, (curr, edge.index));
+                heap.push(MinScored(next_d + h, next));
+            }
+        }
+    }
+    None
+}
+
+/// The ALT heuristic for [`astar`], named for A*, landmarks, and the triangle
+/// inequality. It keeps the distances from and to a few landmark nodes, and
+/// since a path can't be shorter than the difference between its ends'
+/// distances from a landmark, the largest of those differences is a lower
+/// bound on the distance between any two nodes. This works on any graph, with
+/// no need for coordinates, and it's consistent, so A* finds shortest paths
+/// with it. It's best with landmarks on the edge of the graph, behind the
+/// nodes being searched between, which [`select_landmarks`] looks for.
+///
+/// [`select_landmarks`]: AltHeuristic::select_landmarks
+#[derive(Clone, Debug)]
+pub struct AltHeuristic {
+    landmarks: Vec<NodeInd>,
+    /// The distances from each landmark to every node it can reach.
+    from: Vec<HashMap<NodeInd, f64>>,
+    /// The distances to each landmark from every node that can reach it.
+    to: Vec<HashMap<NodeInd, f64>>,
+}
+
+impl AltHeuristic {
+    /// Makes the heuristic with the given landmarks, finding the distances
+    /// from and to each of them. Weights must be non-negative.
+    pub fn new<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+        graph: &G,
+        landmarks: &[NodeInd],
+        weight: F,
+    ) -> Self
+    where
+        F: Fn(&Edge<E>) -> f64,
+    {
+        let mut alt = Self {
+            landmarks: vec![],
+            from: vec![],
+            to: vec![],
+        };
+        for l in landmarks {
+            alt.add_landmark(graph, l, &weight);
+        }
+        alt
+    }
+
+    /// Picks up to `count` landmarks spread out over the graph and makes the
+    /// heuristic with them. The first is the node with the lowest index, and
+    /// each after that is the node furthest from all the ones picked so far,
+    /// preferring nodes none of them can reach. Weights must be non-negative.
+    pub fn select_landmarks<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+        graph: &G,
+        count: usize,
+        weight: F,
+    ) -> Self
+    where
+        F: Fn(&Edge<E>) -> f64,
+    {
+        let nodes = graph.nodes_sorted();
+        let mut alt = Self::new(graph, &[], &weight);
+        let Some(&first) = nodes.first() else {
+            return alt;
+        };
+        let mut next = first;
+        while alt.landmarks.len() < count.min(nodes.len()) {
+            alt.add_landmark(graph, &next, &weight);
+            let closest = |n: &NodeInd| {
+                alt.from
+                    .iter()
+                    .map(|dist| dist.get(n).copied().unwrap_or(f64::INFINITY))
+                    .fold(f64::INFINITY, f64::min)
+            };
+            // max_by keeps the last of equals, so go backwards to get the
+            // lowest index
+            next = *nodes
+                .iter()
+                .rev()
+                .filter(|n| !alt.landmarks.contains(n))
+                .max_by(|a, b| closest(a).total_cmp(&closest(b)))
+                .unwrap_or(&first);
+        }
+        alt
+    }
+
+    /// Finds the distances from and to a new landmark.
+    fn add_landmark<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+        &mut self,
+        graph: &G,
+        landmark: &NodeInd,
+        weight: &F,
+    ) where
+        F: Fn(&Edge<E>) -> f64,
+    {
+        self.landmarks.push(*landmark);
+        self.from
+            .push(dijkstra_search_dir(graph, landmark, None, weight, false).0);
+        self.to
+            .push(dijkstra_search_dir(graph, landmark, None, weight, true).0);
+    }
+
+    /// Gets the landmarks, in the order they were added.
+    pub fn landmarks(&self) -> &[NodeInd] {
+        &self.landmarks
+    }
+
+    /// Gets a lower bound on the distance from `n` to `target`, which is
+    /// infinity if the landmarks show that there's no path.
+    pub fn estimate(&self, n: &NodeInd, target: &NodeInd) -> f64 {
+        let get = |dist: &HashMap<NodeInd, f64>, v: &NodeInd| {
+            dist.get(v).copied().unwrap_or(f64::INFINITY)
+        };
+        // differences of two infinities are NaN, which max skips
+        self.from
+            .iter()
+            .map(|from| get(from, target) - get(from, n))
+            .chain(self.to.iter().map(|to| get(to, n) - get(to, target)))
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Gets the path from `source` to `target` whose largest edge weight, its
+/// bottleneck, is as small as possible, and that bottleneck, or `None` if
+/// `target` isn't reachable. This is the same as Dijkstra's algorithm, but
+/// taking the maximum of the weights along the path instead of the sum, and it
+/// allows negative weights. The path from `source` to itself has no edges, so
+/// its bottleneck is negative infinity.
+pub fn min_bottleneck_path<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    target: &NodeInd,
+    weight: F,
+) -> Option<(f64, Vec<NodeInd>)>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let mut bottleneck = HashMap::from([(*source, f64::NEG_INFINITY)]);
+    let mut pred = HashMap::new();
+    let mut heap = BinaryHeap::from([MinScored(f64::NEG_INFINITY, *source)]);
+    while let Some(MinScored(b, curr)) = heap.pop() {
+        if b > bottleneck[&curr] {
+            continue;
+        }
+        if &curr == target {
+            return Some((b, backtrack(&pred, target)));
+        }
+        for edge in graph.edges_from(&curr) {
+            let next = edge.opposite(&curr);
+            let next_b = b.max(weight(&edge));
+            if bottleneck.get(&next).is_none_or(|&old| next_b < old) {
+                bottleneck.insert(next, next_b);
+                pred.insert(next, (curr, edge.index));
+                heap.push(MinScored(next_b, next));
+            }
+        }
+    }
+    None
+}
+
+/// Gets the path from `source` to `target` whose smallest edge capacity is as
+/// large as possible, and that capacity, or `None` if `target` isn't reachable.
+/// This is the maximum-capacity route problem. Maximizing the smallest
+/// capacity is minimizing the largest negated capacity, so this is
+/// [`min_bottleneck_path`] on negated capacities. The path from `source` to
+/// itself has no edges, so its width is infinite.
+pub fn widest_path<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    target: &NodeInd,
+    capacity: F,
+) -> Option<(f64, Vec<NodeInd>)>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    min_bottleneck_path(graph, source, target, |e| -capacity(e)).map(|(b, path)| (-b, path))
+}
+
+/// Gets every edge as a directed (start, end, weight) arc: undirected edges
+/// give an arc in each direction.
+fn arcs<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    weight: &F,
+) -> Vec<(NodeInd, NodeInd, f64)>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let mut arcs = vec![];
+    for e in graph.edges() {
+        let edge = graph.edge(&e);
+        let w = weight(edge);
+        arcs.push((edge.start, edge.end, w));
+        if !graph.is_directed() {
+            arcs.push((edge.end, edge.start, w));
+        }
+    }
+    arcs
+}
+
+/// Relaxes every arc until nothing changes, starting from the given distances.
+/// Returns `false` if there's a negative cycle reachable from the nodes with a
+/// starting distance.
+fn relax_all(
+    dist: &mut HashMap<NodeInd, f64>,
+    arcs: &[(NodeInd, NodeInd, f64)],
+    rounds: usize,
+) -> bool {
+    for _ in 0..=rounds {
+        let mut changed = false;
+        for &(start, end, w) in arcs {
+            if let Some(&d) = dist.get(&start) {
+                if dist.get(&end).is_none_or(|&old| d + w < old) {
+                    dist.insert(end, d + w);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return true;
+        }
+    }
+    false
+}
+
+/// Gets the length of the shortest path from `source` to every node reachable
+/// from it, using the Bellman-Ford algorithm. Unlike [`dijkstra`], this allows
+/// negative weights, but returns `None` if there's a negative cycle reachable
+/// from `source`. Note that an undirected edge with a negative weight is
+/// itself a negative cycle.
+pub fn bellman_ford<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    weight: F,
+) -> Option<HashMap<NodeInd, f64>>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let arcs = arcs(graph, &weight);
+    let mut dist = HashMap::from([(*source, 0.0)]);
+    let n = graph.nodes().count();
+    relax_all(&mut dist, &arcs, n).then_some(dist)
+}
+
+/// Gets the length of the shortest path between every pair of nodes, using
+/// the Floyd-Warshall algorithm, in O(V^3) time. Rows and columns are in
+/// ascending order of node index like
+/// [`all_pairs_shortest_paths`](crate::traversal::all_pairs_shortest_paths),
+/// with infinity for unreachable pairs. Returns `None` if there's a negative
+/// cycle.
+pub fn floyd_warshall<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    weight: F,
+) -> Option<Vec<Vec<f64>>>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let nodes = graph.nodes_sorted();
+    let pos: HashMap<NodeInd, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let n = nodes.len();
+
+    let mut dist = vec![vec![f64::INFINITY; n]; n];
+    for (i, row) in dist.iter_mut().enumerate() {
+        row[i] = 0.0;
+    }
+    for (start, end, w) in arcs(graph, &weight) {
+        let (i, j) = (pos[&start], pos[&end]);
+        dist[i][j] = dist[i][j].min(w);
+    }
+
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
+                let through = dist[i][k] + dist[k][j];
+                if through < dist[i][j] {
+                    dist[i][j] = through;
+                }
+            }
+        }
+    }
+
+    if (0..n).any(|i| dist[i][i] < 0.0) {
+        None
+    } else {
+        Some(dist)
+    }
+}
+
+/// Gets the length of the shortest path between every pair of nodes, using
+/// Johnson's algorithm: Bellman-Ford finds a potential for each node that makes
+/// every weight non-negative, and then Dijkstra runs from every node. This is
+/// O(VE log V), so it's faster than [`floyd_warshall`] for sparse graphs, and
+/// like it handles negative weights. The result has the same layout, and is
+/// `None` if there's a negative cycle.
+pub fn johnson<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    weight: F,
+) -> Option<Vec<Vec<f64>>>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let nodes = graph.nodes_sorted();
+
+    // equivalent to running Bellman-Ford from a new node with a zero-weight
+    // edge to every other node
+    let arcs = arcs(graph, &weight);
+    let mut potential: HashMap<NodeInd, f64> = nodes.iter().map(|&n| (n, 0.0)).collect();
+    if !relax_all(&mut potential, &arcs, nodes.len() + 1) {
+        return None;
+    }
+
+    // negative undirected edges are negative cycles, so for undirected graphs
+    // the potential is zero and it doesn't matter which end is the start
+    let reweighted = |e: &Edge<E>| weight(e) + potential[&e.start] - potential[&e.end];
+    let dists = nodes
+        .iter()
+        .map(|source| {
+            let dist = dijkstra(graph, source, reweighted);
+            nodes
+                .iter()
+                .map(|target| match dist.get(target) {
+                    Some(d) => d - potential[source] + potential[target],
+                    None => f64::INFINITY,
+                })
+                .collect()
+        })
+        .collect();
+    Some(dists)
+}
+
+/// A path found by [`k_shortest_paths`], keeping track of the edges used so
+/// parallel edges give different paths.
+struct YenPath {
+    cost: f64,
+    nodes: Vec<NodeInd>,
+    edges: Vec<EdgeInd>,
+}
+
+/// Gets up to `k` of the shortest loopless paths from `source` to `target`, in
+/// increasing order of cost, using Yen's algorithm. Each path is given with its
+/// cost. Weights must be non-negative. Paths going through different parallel
+/// edges count as different, even though their nodes are the same.
+///
+/// This runs Dijkstra O(kV) times, once from every node along each path found.
+pub fn k_shortest_paths<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(
+    graph: &G,
+    source: &NodeInd,
+    target: &NodeInd,
+    k: usize,
+    weight: F,
+) -> Vec<(f64, Vec<NodeInd>)>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let mut found: Vec<YenPath> = vec![];
+    let (dist, pred) = dijkstra_search(graph, source, Some(target), &weight);
+    match dist.get(target) {
+        Some(&cost) if k > 0 => {
+            let (nodes, edges) = backtrack_with_edges(&pred, target);
+            found.push(YenPath { cost, nodes, edges });
+        }
+        _ => return vec![],
+    }
+
+    let mut candidates: Vec<YenPath> = vec![];
+    while found.len() < k {
+        let prev = found.last().unwrap();
+        let mut root_cost = 0.0;
+        for i in 0..(prev.nodes.len() - 1) {
+            let spur = prev.nodes[i];
+            let root_nodes = &prev.nodes[..i];
+            let root_edges = &prev.edges[..i];
+            if i > 0 {
+                root_cost += weight(graph.edge(&prev.edges[i - 1]));
+            }
+
+            // the spur path can't reuse the root path's nodes, and it can't
+            // take the same next step as any path with the same root
+            let banned_edges: Vec<EdgeInd> = found
+                .iter()
+                .filter(|p| p.edges.len() > i && p.edges[..i] == *root_edges)
+                .map(|p| p.edges[i])
+                .collect();
+            let spur_weight = |e: &Edge<E>| {
+                if banned_edges.contains(&e.index)
+                    || root_nodes.contains(&e.start)
+                    || root_nodes.contains(&e.end)
+                {
+                    f64::INFINITY
+                } else {
+                    weight(e)
+                }
+            };
+
+            let (dist, pred) = dijkstra_search(graph, &spur, Some(target), spur_weight);
+            let spur_cost = match dist.get(target) {
+                Some(&d) => d,
+                None => continue,
+            };
+            let (spur_nodes, spur_edges) = backtrack_with_edges(&pred, target);
+            let edges: Vec<EdgeInd> = root_edges.iter().chain(&spur_edges).copied().collect();
+            if candidates.iter().any(|p| p.edges == edges) {
+                continue;
+            }
+            let nodes = root_nodes.iter().chain(&spur_nodes).copied().collect();
+            candidates.push(YenPath {
+                cost: root_cost + spur_cost,
+                nodes,
+                edges,
+            });
+        }
+
+        let best = candidates
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.cost.total_cmp(&b.cost))
+            .map(|(i, _)| i);
+        match best {
+            Some(i) => found.push(candidates.swap_remove(i)),
+            None => break,
+        }
+    }
+
+    found.into_iter().map(|p| (p.cost, p.nodes)).collect()
+}
+
+/// How many nodes a witness search in [`ContractionHierarchy::new`] settles
+/// before giving up. Giving up early only adds shortcuts that aren't needed, so
+/// this trades preprocessing time against query time.
+const WITNESS_SETTLE_LIMIT: usize = 64;
+
+/// A contraction hierarchy: a graph preprocessed so that shortest path
+/// distances can be found much faster than with [`dijkstra`], for when there
+/// are many queries on the same graph, like routing on a road network.
+///
+/// Building it contracts the nodes one at a time, in order of how many
+/// shortcuts each would need, which is the number of shortest paths through it
+/// between nodes not yet contracted. Those paths are kept as shortcut arcs
+/// from one neighbor to the other, so every shortest path has a version that
+/// only goes up the order and then down it. A query then searches upwards from
+/// the source and downwards into the target, each of which only sees a small
+/// part of the graph. Weights must be non-negative, and as for [`dijkstra`],
+/// edges with infinite weight are never used.
+#[derive(Clone, Debug)]
+pub struct ContractionHierarchy {
+    pos: HashMap<NodeInd, usize>,
+    /// The arcs from each node to nodes contracted after it, with their
+    /// lengths.
+    up: Vec<Vec<(usize, f64)>>,
+    /// The arcs into each node from nodes contracted after it, backwards.
+    down: Vec<Vec<(usize, f64)>>,
+}
+
+impl ContractionHierarchy {
+    /// Builds the hierarchy for a graph, with the given weights. Undirected
+    /// edges can be used either way.
+    pub fn new<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, F>(graph: &G, weight: F) -> Self
+    where
+        F: Fn(&Edge<E>) -> f64,
+    {
+        let nodes = graph.nodes_sorted();
+        let n = nodes.len();
+        let pos: HashMap<NodeInd, usize> = nodes.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+
+        // the shortest arc each way between each pair of nodes not yet
+        // contracted
+        let mut out: Vec<HashMap<usize, f64>> = vec![HashMap::new(); n];
+        let mut inn: Vec<HashMap<usize, f64>> = vec![HashMap::new(); n];
+        for (start, end, w) in arcs(graph, &weight) {
+            let (u, v) = (pos[&start], pos[&end]);
+            if u != v && w < f64::INFINITY {
+                let best = out[u].entry(v).or_insert(w);
+                *best = best.min(w);
+                inn[v].insert(u, *best);
+            }
+        }
+
+        let mut ch = Self {
+            pos,
+            up: vec![vec![]; n],
+            down: vec![vec![]; n],
+        };
+        let mut contracted = vec![false; n];
+        let mut neighbors_contracted = vec![0; n];
+        let priority = |v: usize, out: &[HashMap<usize, f64>], inn: &[HashMap<usize, f64>]| {
+            let shortcuts = shortcuts(out, inn, v).len() as f64;
+            shortcuts - (out[v].len() + inn[v].len()) as f64
+        };
+        let mut heap: BinaryHeap<MinScored> = (0..n)
+            .map(|v| MinScored(priority(v, &out, &inn), v))
+            .collect();
+        while let Some(MinScored(_, v)) = heap.pop() {
+            if contracted[v] {
+                continue;
+            }
+            // priorities go stale as neighbors are contracted, so check it's
+            // still the best before contracting it
+            let p = priority(v, &out, &inn) + neighbors_contracted[v] as f64;
+            if heap.peek().is_some_and(|next| p > next.0) {
+                heap.push(MinScored(p, v));
+                continue;
+            }
+
+            for (u, w, d) in shortcuts(&out, &inn, v) {
+                let best = out[u].entry(w).or_insert(d);
+                *best = best.min(d);
+                inn[w].insert(u, *best);
+            }
+            let ups: Vec<(usize, f64)> = out[v].drain().collect();
+            let downs: Vec<(usize, f64)> = inn[v].drain().collect();
+            for &(w, _) in &ups {
+                inn[w].remove(&v);
+                neighbors_contracted[w] += 1;
+            }
+            for &(u, _) in &downs {
+                out[u].remove(&v);
+                neighbors_contracted[u] += 1;
+            }
+            ch.up[v] = ups;
+            ch.down[v] = downs;
+            contracted[v] = true;
+        }
+        ch
+    }
+
+    /// Gets the length of the shortest path from `source` to `target`, or
+    /// `None` if it isn't reachable or either node isn't in the graph.
+    pub fn query(&self, source: &NodeInd, target: &NodeInd) -> Option<f64> {
+        let (&s, &t) = (self.pos.get(source)?, self.pos.get(target)?);
+        let forward = upward_search(&self.up, s);
+        let backward = upward_search(&self.down, t);
+        forward
+            .iter()
+            .filter_map(|(v, d)| Some(d + backward.get(v)?))
+            .min_by(f64::total_cmp)
+    }
+}
+
+/// Gets the shortcuts needed to contract `v`: for each pair of its neighbors
+/// whose shortest path might go through it, the start and end of the path and
+/// its length.
+fn shortcuts(
+    out: &[HashMap<usize, f64>],
+    inn: &[HashMap<usize, f64>],
+    v: usize,
+) -> Vec<(usize, usize, f64)> {
+    let mut found = vec![];
+    for (&u, &to_v) in &inn[v] {
+        let limit = out[v]
+            .values()
+            .map(|from_v| to_v + from_v)
+            .fold(0.0, f64::max);
+        let witness = witness_search(out, u, v, limit);
+        for (&w, &from_v) in &out[v] {
+            let d = to_v + from_v;
+            if w != u && witness.get(&w).is_none_or(|&other| other > d) {
+                found.push((u, w, d));
+            }
+        }
+    }
+    found
+}
+
+/// Runs Dijkstra from `source` on the nodes not yet contracted, without going
+/// through `skip`, settling at most [`WITNESS_SETTLE_LIMIT`] nodes and none
+/// further than `limit`.
+fn witness_search(
+    out: &[HashMap<usize, f64>],
+    source: usize,
+    skip: usize,
+    limit: f64,
+) -> HashMap<usize, f64> {
+    let mut dist = HashMap::from([(source, 0.0)]);
+    let mut heap = BinaryHeap::from([MinScored(0.0, source)]);
+    let mut settled = 0;
+    while let Some(MinScored(d, curr)) = heap.pop() {
+        if d > dist[&curr] {
+            continue;
+        }
+        settled += 1;
+        if d > limit || settled > WITNESS_SETTLE_LIMIT {
+            break;
+        }
+        for (&next, &w) in &out[curr] {
+            let next_d = d + w;
+            if next != skip && dist.get(&next).is_none_or(|&old| next_d < old) {
+                dist.insert(next, next_d);
+                heap.push(MinScored(next_d, next));
+            }
+        }
+    }
+    dist
+}
+
+/// Runs Dijkstra from `source` along the arcs of one direction of a
+/// [`ContractionHierarchy`], getting the distance to every node it reaches.
+fn upward_search(arcs: &[Vec<(usize, f64)>], source: usize) -> HashMap<usize, f64> {
+    let mut dist = HashMap::from([(source, 0.0)]);
+    let mut heap = BinaryHeap::from([MinScored(0.0, source)]);
+    while let Some(MinScored(d, curr)) = heap.pop() {
+        if d > dist[&curr] {
+            continue;
+        }
+        for &(next, w) in &arcs[curr] {
+            let next_d = d + w;
+            if dist.get(&next).is_none_or(|&old| next_d < old) {
+                dist.insert(next, next_d);
+                heap.push(MinScored(next_d, next));
+            }
+        }
+    }
+    dist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::{Directed, Undirected};
+    use crate::test_util::weighted_graph_from_edges;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::cell::Cell;
+
+    #[test]
+    fn test_dijkstra() {
+        let g = weighted_graph_from_edges::<Undirected>(
+            4,
+            &[(0, 1, 1.0), (1, 2, 1.0), (0, 2, 3.0), (2, 3, 1.0)],
+        );
+        let dist = dijkstra(&g, &0, |e| e.data);
+        assert_eq!(
+            dist,
+            HashMap::from([(0, 0.0), (1, 1.0), (2, 2.0), (3, 3.0)])
+        );
+        assert_eq!(
+            dijkstra_path(&g, &0, &3, |e| e.data),
+            Some((3.0, vec![0, 1, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn test_dijkstra_weighted() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let edges: Vec<(NodeInd, NodeInd, f64)> = (0..40)
+            .map(|_| {
+                (
+                    rng.gen_range(0..15),
+                    rng.gen_range(0..15),
+                    rng.gen_range(0.0..5.0),
+                )
+            })
+            .collect();
+        let g = weighted_graph_from_edges::<Directed>(15, &edges);
+        assert_eq!(dijkstra_weighted(&g, &0), dijkstra(&g, &0, |e| e.data));
+    }
+
+    #[test]
+    fn test_min_bottleneck_path() {
+        // the direct route has one very costly edge, and the long way round
+        // has only cheap ones
+        let g = weighted_graph_from_edges::<Undirected>(
+            5,
+            &[
+                (0, 1, 1.0),
+                (1, 4, 10.0),
+                (1, 2, 4.0),
+                (2, 3, 4.0),
+                (3, 4, 4.0),
+            ],
+        );
+        assert_eq!(
+            min_bottleneck_path(&g, &0, &4, |e| e.data),
+            Some((4.0, vec![0, 1, 2, 3, 4]))
+        );
+        assert_eq!(
+            dijkstra_path(&g, &0, &4, |e| e.data),
+            Some((11.0, vec![0, 1, 4]))
+        );
+        assert_eq!(
+            min_bottleneck_path(&g, &0, &0, |e| e.data),
+            Some((f64::NEG_INFINITY, vec![0]))
+        );
+    }
+
+    #[test]
+    fn test_dijkstra_tree() {
+        let g = weighted_graph_from_edges::<Directed>(
+            5,
+            &[
+                (0, 1, 1.0),
+                (1, 2, 1.0),
+                (0, 2, 5.0),
+                (2, 3, 1.0),
+                (4, 0, 1.0),
+            ],
+        );
+        let tree = dijkstra_tree(&g, &0, |e| e.data);
+        assert_eq!(tree.source(), 0);
+        let path = tree.path_to(&3).unwrap();
+        assert_eq!(path, vec![0, 1, 2, 3]);
+        let edges = tree.edges_to(&3).unwrap();
+        assert_eq!(edges.len(), path.len() - 1);
+        for (i, e) in edges.iter().enumerate() {
+            assert_eq!(g.edge_endpoints(e), (path[i], path[i + 1]));
+        }
+        assert_eq!(tree.distance_to(&3), Some(3.0));
+        assert_eq!(tree.predecessor(&2), Some(1));
+        assert_eq!(tree.path_to(&0), Some(vec![0]));
+        assert_eq!(tree.predecessor(&0), None);
+
+        assert_eq!(tree.path_to(&4), None);
+        assert_eq!(tree.edges_to(&4), None);
+        assert_eq!(tree.distance_to(&4), None);
+    }
+
+    #[test]
+    fn test_widest_path() {
+        // the direct pipe is narrow, and the detour through 2 and 3 is wide
+        // except for a middling first hop
+        let g = weighted_graph_from_edges::<Directed>(
+            5,
+            &[
+                (0, 4, 1.0),
+                (0, 1, 5.0),
+                (1, 4, 2.0),
+                (1, 2, 8.0),
+                (2, 3, 9.0),
+                (3, 4, 7.0),
+            ],
+        );
+        assert_eq!(
+            widest_path(&g, &0, &4, |e| e.data),
+            Some((5.0, vec![0, 1, 2, 3, 4]))
+        );
+        assert_eq!(widest_path(&g, &4, &0, |e| e.data), None);
+        assert_eq!(
+            widest_path(&g, &2, &2, |e| e.data),
+            Some((f64::INFINITY, vec![2]))
+        );
+    }
+
+    #[test]
+    fn test_johnson_matches_floyd_warshall() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for _ in 0..10 {
+            // negative edges only go forwards, and backwards edges are too
+            // heavy and short to make a negative cycle
+            let n: usize = 12;
+            let mut edges = vec![];
+            for _ in 0..30 {
+                let i = rng.gen_range(0..n - 1);
+                let j = rng.gen_range(i + 1..n);
+                edges.push((i, j, rng.gen_range(-2..10) as f64));
+            }
+            for _ in 0..10 {
+                let j = rng.gen_range(1..n);
+                let i = j.saturating_sub(rng.gen_range(1..=3usize));
+                edges.push((j, i, 10.0));
+            }
+            let g = weighted_graph_from_edges::<Directed>(n, &edges);
+            let expected = floyd_warshall(&g, |e| e.data).unwrap();
+            assert_eq!(johnson(&g, |e| e.data), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_k_shortest_paths() {
+        let g = weighted_graph_from_edges::<Directed>(
+            6,
+            &[
+                (0, 1, 3.0),
+                (0, 2, 2.0),
+                (1, 3, 4.0),
+                (2, 1, 1.0),
+                (2, 3, 2.0),
+                (2, 4, 3.0),
+                (3, 4, 2.0),
+                (3, 5, 1.0),
+                (4, 5, 2.0),
+            ],
+        );
+        let paths = k_shortest_paths(&g, &0, &5, 10, |e| e.data);
+        assert_eq!(paths[0], dijkstra_path(&g, &0, &5, |e| e.data).unwrap());
+        assert_eq!(paths[0], (5.0, vec![0, 2, 3, 5]));
+        assert_eq!(paths[1], (7.0, vec![0, 2, 4, 5]));
+
+        // there are 7 simple paths in total
+        assert_eq!(paths.len(), 7);
+        for pair in paths.windows(2) {
+            assert!(pair[0].0 <= pair[1].0);
+        }
+        for (i, (_, p)) in paths.iter().enumerate() {
+            assert!(paths[..i].iter().all(|(_, other)| other != p));
+        }
+        assert_eq!(k_shortest_paths(&g, &0, &5, 2, |e| e.data).len(), 2);
+    }
+
+    #[test]
+    fn test_negative_cycle() {
+        let g = weighted_graph_from_edges::<Directed>(3, &[(0, 1, 1.0), (1, 2, -3.0), (2, 0, 1.0)]);
+        assert_eq!(johnson(&g, |e| e.data), None);
+        assert_eq!(floyd_warshall(&g, |e| e.data), None);
+        assert_eq!(bellman_ford(&g, &0, |e| e.data), None);
+    }
+
+    #[test]
+    fn test_contraction_hierarchy() {
+        // whole-number weights, so that adding them up in a different order
+        // for shortcuts gives exactly the same lengths
+        let mut rng = StdRng::seed_from_u64(23);
+        for _ in 0..10 {
+            let n = rng.gen_range(1..30);
+            let edges: Vec<(NodeInd, NodeInd, f64)> = (0..rng.gen_range(0..4 * n))
+                .map(|_| {
+                    let w = rng.gen_range(0..10) as f64;
+                    (rng.gen_range(0..n), rng.gen_range(0..n), w)
+                })
+                .collect();
+            let g = weighted_graph_from_edges::<Directed>(n, &edges);
+            let ch = ContractionHierarchy::new(&g, |e| e.data);
+            for s in 0..n {
+                let dist = dijkstra(&g, &s, |e| e.data);
+                for t in 0..n {
+                    assert_eq!(ch.query(&s, &t), dist.get(&t).copied());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_contraction_hierarchy_grid() {
+        // a road-like grid, with undirected edges
+        let mut rng = StdRng::seed_from_u64(24);
+        let side = 8;
+        let mut edges = vec![];
+        for i in 0..side {
+            for j in 0..side {
+                let v = i * side + j;
+                if j + 1 < side {
+                    edges.push((v, v + 1, rng.gen_range(1..20) as f64));
+                }
+                if i + 1 < side {
+                    edges.push((v, v + side, rng.gen_range(1..20) as f64));
+                }
+            }
+        }
+        let g = weighted_graph_from_edges::<Undirected>(side * side, &edges);
+        let ch = ContractionHierarchy::new(&g, |e| e.data);
+        for s in 0..side * side {
+            let dist = dijkstra(&g, &s, |e| e.data);
+            for t in 0..side * side {
+                assert_eq!(ch.query(&s, &t), Some(dist[&t]));
+            }
+        }
+        assert_eq!(ch.query(&0, &(side * side)), None);
+    }
+
+    #[test]
+    fn test_bidirectional_dijkstra() {
+        let mut rng = StdRng::seed_from_u64(25);
+        for _ in 0..20 {
+            let n = rng.gen_range(1..15);
+            let edges: Vec<(NodeInd, NodeInd, f64)> = (0..rng.gen_range(0..3 * n))
+                .map(|_| {
+                    let w = rng.gen_range(0..10) as f64;
+                    (rng.gen_range(0..n), rng.gen_range(0..n), w)
+                })
+                .collect();
+            let g = weighted_graph_from_edges::<Directed>(n, &edges);
+            for s in 0..n {
+                let dist = dijkstra(&g, &s, |e| e.data);
+                for t in 0..n {
+                    let found = bidirectional_dijkstra(&g, &s, &t, |e| e.data);
+                    assert_eq!(found.as_ref().map(|f| f.0), dist.get(&t).copied());
+                    if let Some((d, path)) = found {
+                        // the path is really that long
+                        assert_eq!((path[0], *path.last().unwrap()), (s, t));
+                        let length: f64 = path
+                            .windows(2)
+                            .map(|w| {
+                                g.edges_from(&w[0])
+                                    .filter(|e| e.end == w[1])
+                                    .map(|e| e.data)
+                                    .fold(f64::INFINITY, f64::min)
+                            })
+                            .sum();
+                        assert_eq!(length, d);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bidirectional_dijkstra_explores_less() {
+        // on a grid, each search covers a disk about half as wide as the one
+        // search from the source does, so it looks at about half as many
+        // edges, counted by how often the weight is asked for
+        let side = 31;
+        let mut edges = vec![];
+        for i in 0..side {
+            for j in 0..side {
+                let v = i * side + j;
+                if j + 1 < side {
+                    edges.push((v, v + 1, 1.0));
+                }
+                if i + 1 < side {
+                    edges.push((v, v + side, 1.0));
+                }
+            }
+        }
+        let g = weighted_graph_from_edges::<Undirected>(side * side, &edges);
+        let (s, t) = (15 * side + 5, 15 * side + 25);
+
+        let one_way = Cell::new(0);
+        let expected = dijkstra_path(&g, &s, &t, |e| {
+            one_way.set(one_way.get() + 1);
+            e.data
+        });
+        let both_ways = Cell::new(0);
+        let found = bidirectional_dijkstra(&g, &s, &t, |e| {
+            both_ways.set(both_ways.get() + 1);
+            e.data
+        });
+        assert_eq!(found.map(|f| f.0), Some(20.0));
+        assert_eq!(expected.map(|f| f.0), Some(20.0));
+        assert!(both_ways.get() < one_way.get());
+    }
+
+    #[test]
+    fn test_astar_alt() {
+        let mut rng = StdRng::seed_from_u64(26);
+        for _ in 0..20 {
+            let n = rng.gen_range(1..15);
+            let edges: Vec<(NodeInd, NodeInd, f64)> = (0..rng.gen_range(0..3 * n))
+                .map(|_| {
+                    let w = rng.gen_range(0..10) as f64;
+                    (rng.gen_range(0..n), rng.gen_range(0..n), w)
+                })
+                .collect();
+            let g = weighted_graph_from_edges::<Directed>(n, &edges);
+            let alt = AltHeuristic::select_landmarks(&g, 3, |e| e.data);
+            assert_eq!(alt.landmarks().len(), n.min(3));
+            for s in 0..n {
+                let dist = dijkstra(&g, &s, |e| e.data);
+                for t in 0..n {
+                    let found = astar(&g, &s, &t, |e| e.data, |v| alt.estimate(v, &t));
+                    assert_eq!(found.map(|f| f.0), dist.get(&t).copied());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_astar_alt_explores_less() {
+        // on a grid with landmarks in the corners, A* heads more or less
+        // straight for the target, while Dijkstra spreads out in every
+        // direction, so it looks at fewer edges
+        let mut rng = StdRng::seed_from_u64(27);
+        let side = 25;
+        let mut edges = vec![];
+        for i in 0..side {
+            for j in 0..side {
+                let v = i * side + j;
+                if j + 1 < side {
+                    edges.push((v, v + 1, rng.gen_range(1..5) as f64));
+                }
+                if i + 1 < side {
+                    edges.push((v, v + side, rng.gen_range(1..5) as f64));
+                }
+            }
+        }
+        let g = weighted_graph_from_edges::<Undirected>(side * side, &edges);
+        let alt = AltHeuristic::select_landmarks(&g, 4, |e| e.data);
+        let (s, t) = (12 * side + 3, 12 * side + 21);
+
+        let plain = Cell::new(0);
+        let expected = dijkstra_path(&g, &s, &t, |e| {
+            plain.set(plain.get() + 1);
+            e.data
+        });
+        let guided = Cell::new(0);
+        let found = astar(
+            &g,
+            &s,
+            &t,
+            |e| {
+                guided.set(guided.get() + 1);
+                e.data
+            },
+            |v| alt.estimate(v, &t),
+        );
+        assert_eq!(found.map(|f| f.0), expected.map(|f| f.0));
+        assert!(guided.get() < plain.get());
+    }
+}
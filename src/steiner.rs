@@ -0,0 +1,192 @@
+//! Approximate Steiner trees: trees connecting a given set of terminal nodes,
+//! possibly through other nodes, with as little total weight as possible.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::graph_base::{Edge, EdgeInd, GraphView, NodeInd, Undirected};
+use crate::shortest_paths::{backtrack_with_edges, dijkstra_search, SearchResult};
+use crate::union_find::UnionFind;
+
+/// Gets the edges of a tree connecting all of the terminals, with total weight
+/// at most twice the smallest possible. This is the classic approximation: find
+/// a minimum spanning tree of the terminals where each pair is joined by its
+/// shortest path length, replace each of its edges with the shortest path in
+/// the graph, then take a minimum spanning tree of the result and cut off any
+/// branches that don't lead to a terminal. Weights must be non-negative. The
+/// edges are in ascending order of index. Panics if the terminals aren't all
+/// connected.
+pub fn steiner_tree_approx<N, E, G: GraphView<N, E, Undirected>, F>(
+    graph: &G,
+    terminals: &[NodeInd],
+    weight: F,
+) -> Vec<EdgeInd>
+where
+    F: Fn(&Edge<E>) -> f64,
+{
+    let mut terminals = terminals.to_vec();
+    terminals.sort_unstable();
+    terminals.dedup();
+    let k = terminals.len();
+    if k < 2 {
+        return vec![];
+    }
+
+    let searches: Vec<SearchResult> = terminals
+        .iter()
+        .map(|t| dijkstra_search(graph, t, None, &weight))
+        .collect();
+    let dist = |i: usize, j: usize| {
+        searches[i]
+            .0
+            .get(&terminals[j])
+            .copied()
+            .unwrap_or(f64::INFINITY)
+    };
+
+    // Prim's algorithm on the complete graph of terminals, expanding each edge
+    // it picks into the path it stands for
+    let mut in_tree = vec![false; k];
+    in_tree[0] = true;
+    let mut best: Vec<(f64, usize)> = (0..k).map(|j| (dist(0, j), 0)).collect();
+    let mut union: HashSet<EdgeInd> = HashSet::new();
+    for _ in 1..k {
+        let j = (0..k)
+            .filter(|&j| !in_tree[j])
+            .min_by(|&a, &b| best[a].0.total_cmp(&best[b].0))
+            .unwrap();
+        let (d, i) = best[j];
+        assert!(
+            d.is_finite(),
+            "terminals {} and {} aren't connected",
+            terminals[i],
+            terminals[j]
+        );
+        in_tree[j] = true;
+        union.extend(backtrack_with_edges(&searches[i].1, &terminals[j]).1);
+        for l in 0..k {
+            if !in_tree[l] && dist(j, l) < best[l].0 {
+                best[l] = (dist(j, l), j);
+            }
+        }
+    }
+
+    // shortest paths can share nodes, so their union can have cycles: Kruskal's
+    // algorithm breaks them
+    let mut union: Vec<EdgeInd> = union.into_iter().collect();
+    union.sort_by(|a, b| {
+        weight(graph.edge(a))
+            .total_cmp(&weight(graph.edge(b)))
+            .then(a.cmp(b))
+    });
+    let mut sets = UnionFind::new();
+    let mut tree: HashSet<EdgeInd> = HashSet::new();
+    for e in union {
+        let (u, v) = graph.edge_endpoints(&e);
+        if sets.union(u, v) {
+            tree.insert(e);
+        }
+    }
+
+    // then cut off leaves that aren't terminals until there are none left
+    let terminal_set: HashSet<NodeInd> = terminals.into_iter().collect();
+    let mut incident: HashMap<NodeInd, Vec<EdgeInd>> = HashMap::new();
+    for &e in &tree {
+        let (u, v) = graph.edge_endpoints(&e);
+        incident.entry(u).or_default().push(e);
+        incident.entry(v).or_default().push(e);
+    }
+    let mut leaves: Vec<NodeInd> = incident
+        .iter()
+        .filter(|(n, es)| es.len() == 1 && !terminal_set.contains(n))
+        .map(|(n, _)| *n)
+        .collect();
+    while let Some(leaf) = leaves.pop() {
+        let e = incident.get_mut(&leaf).unwrap().pop().unwrap();
+        tree.remove(&e);
+        let other = graph.edge(&e).opposite(&leaf);
+        let rest = incident.get_mut(&other).unwrap();
+        rest.retain(|&f| f != e);
+        if rest.len() == 1 && !terminal_set.contains(&other) {
+            leaves.push(other);
+        }
+    }
+
+    let mut tree: Vec<EdgeInd> = tree.into_iter().collect();
+    tree.sort_unstable();
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adj_list_graph::ALGraph;
+    use crate::graph_base::GraphBase;
+    use crate::test_util::weighted_graph_from_edges;
+    use crate::traversal::reachable_from;
+
+    /// Checks that the edges form a tree connecting the terminals, and gets its
+    /// weight.
+    fn check_tree(
+        g: &ALGraph<(), f64, Undirected>,
+        edges: &[EdgeInd],
+        terminals: &[NodeInd],
+    ) -> f64 {
+        let mut tree: ALGraph<(), f64, Undirected> = ALGraph::new();
+        for _ in g.nodes() {
+            tree.add_node(());
+        }
+        for e in edges {
+            let edge = g.edge(e);
+            tree.add_edge(&edge.start, &edge.end, edge.data);
+        }
+        let reached = reachable_from(&tree, &terminals[0]);
+        assert!(terminals.iter().all(|t| reached.contains(t)));
+        assert_eq!(edges.len() + 1, reached.len());
+        edges.iter().map(|e| g.edge(e).data).sum()
+    }
+
+    #[test]
+    fn test_steiner_star() {
+        // the best tree goes through the center, which isn't a terminal, but
+        // the approximation uses the direct edges between terminals
+        let g = weighted_graph_from_edges::<Undirected>(
+            5,
+            &[
+                (0, 1, 1.0),
+                (0, 2, 1.0),
+                (0, 3, 1.0),
+                (1, 2, 1.9),
+                (2, 3, 1.9),
+                (3, 1, 1.9),
+                (3, 4, 0.1),
+            ],
+        );
+        let terminals = [1, 2, 3];
+        let tree = steiner_tree_approx(&g, &terminals, |e| e.data);
+        let w = check_tree(&g, &tree, &terminals);
+        assert!(w <= 2.0 * 3.0);
+    }
+
+    #[test]
+    fn test_steiner_through_others() {
+        // the short way between the terminals goes through two other nodes, and
+        // the branch to 4 isn't needed
+        let g = weighted_graph_from_edges::<Undirected>(
+            6,
+            &[
+                (0, 1, 1.0),
+                (1, 2, 1.0),
+                (2, 3, 1.0),
+                (2, 4, 1.0),
+                (3, 5, 1.0),
+                (0, 5, 10.0),
+            ],
+        );
+        let terminals = [0, 3];
+        let tree = steiner_tree_approx(&g, &terminals, |e| e.data);
+        assert_eq!(check_tree(&g, &tree, &terminals), 3.0);
+        assert_eq!(tree, vec![0, 1, 2]);
+
+        assert!(steiner_tree_approx(&g, &[2], |e| e.data).is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+//! Helpers for building small graphs in tests.
+
+use crate::adj_list_graph::ALGraph;
+use crate::graph_base::{GraphBase, GraphType, NodeInd};
+
+/// Makes a graph with nodes `0..n` and the given edges.
+pub(crate) fn graph_from_edges<Ty: GraphType>(
+    n: usize,
+    edges: &[(NodeInd, NodeInd)],
+) -> ALGraph<(), (), Ty> {
+    let mut g = ALGraph::new();
+    for _ in 0..n {
+        g.add_node(());
+    }
+    for (start, end) in edges {
+        g.add_edge(start, end, ());
+    }
+    g
+}
+
+/// Makes a graph with nodes `0..n` and the given weighted edges.
+pub(crate) fn weighted_graph_from_edges<Ty: GraphType>(
+    n: usize,
+    edges: &[(NodeInd, NodeInd, f64)],
+) -> ALGraph<(), f64, Ty> {
+    let mut g = ALGraph::new();
+    for _ in 0..n {
+        g.add_node(());
+    }
+    for (start, end, w) in edges {
+        g.add_edge(start, end, *w);
+    }
+    g
+}
@@ -0,0 +1,629 @@
+//! Graph traversals: breadth-first search and friends.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::adj_list_graph::ALGraph;
+use crate::graph_base::{EdgeInd, GraphType, GraphView, NodeInd};
+use crate::shortest_paths::ShortestPathTree;
+
+/// Gets the number of edges on the shortest path from `source` to every node
+/// reachable from it, respecting edge direction. Unreachable nodes are not
+/// included.
+pub fn bfs_distances<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    source: &NodeInd,
+) -> HashMap<NodeInd, usize> {
+    multi_source_bfs(graph, &[*source])
+}
+
+/// Like [`bfs_distances`], but gets the whole tree of shortest paths found by
+/// the search, so paths as well as distances can be read off it. Distances are
+/// numbers of edges, as floats to match weighted searches.
+pub fn bfs_tree<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    source: &NodeInd,
+) -> ShortestPathTree {
+    let mut dist = HashMap::from([(*source, 0.0)]);
+    let mut pred = HashMap::new();
+    let mut queue = VecDeque::from([*source]);
+    while let Some(curr) = queue.pop_front() {
+        let d = dist[&curr] + 1.0;
+        for (next, e) in graph.neighbors_with_edges(&curr) {
+            if let Entry::Vacant(entry) = dist.entry(next) {
+                entry.insert(d);
+                pred.insert(next, (curr, e));
+                queue.push_back(next);
+            }
+        }
+    }
+    ShortestPathTree::new(*source, (dist, pred))
+}
+
+/// Iterates over every edge reachable from `start`, each once, in the order a
+/// BFS comes across them: all of the edges out of `start` first, then all of
+/// the edges out of the nodes they lead to, and so on. This includes edges
+/// back to nodes that have already been found, not just the edges of the BFS
+/// tree. Edge direction is respected.
+pub fn bfs_edges<'a, N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &'a G,
+    start: &NodeInd,
+) -> impl Iterator<Item = EdgeInd> + 'a {
+    let mut found = HashSet::from([*start]);
+    let mut queue = VecDeque::from([*start]);
+    let mut yielded: HashSet<EdgeInd> = HashSet::new();
+    // the edges out of the node at the front of the queue, in reverse order
+    let mut pending: Vec<(NodeInd, EdgeInd)> = vec![];
+
+    std::iter::from_fn(move || loop {
+        if let Some((next, e)) = pending.pop() {
+            if !yielded.insert(e) {
+                continue;
+            }
+            if found.insert(next) {
+                queue.push_back(next);
+            }
+            return Some(e);
+        }
+        let curr = queue.pop_front()?;
+        pending = graph.neighbors_with_edges(&curr).collect();
+        pending.reverse();
+    })
+}
+
+/// Gets every node reachable from `source`, including itself, respecting edge
+/// direction.
+pub fn reachable_from<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    source: &NodeInd,
+) -> HashSet<NodeInd> {
+    let mut seen = HashSet::from([*source]);
+    let mut stack = vec![*source];
+    while let Some(curr) = stack.pop() {
+        for next in graph.neighbors(&curr) {
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    seen
+}
+
+/// Whether there's a path from `source` to `target`, respecting edge
+/// direction. This stops searching as soon as it finds `target`.
+pub fn can_reach<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    source: &NodeInd,
+    target: &NodeInd,
+) -> bool {
+    let mut seen = HashSet::from([*source]);
+    let mut stack = vec![*source];
+    while let Some(curr) = stack.pop() {
+        if &curr == target {
+            return true;
+        }
+        for next in graph.neighbors(&curr) {
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    false
+}
+
+/// Like [`bfs_distances`], but only includes nodes at most `max_dist` edges
+/// away from `source`, and doesn't search any further than that.
+pub fn bfs_distances_within<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    source: &NodeInd,
+    max_dist: usize,
+) -> HashMap<NodeInd, usize> {
+    let mut dist = HashMap::from([(*source, 0)]);
+    let mut queue = VecDeque::from([*source]);
+    while let Some(curr) = queue.pop_front() {
+        let d = dist[&curr];
+        if d == max_dist {
+            continue;
+        }
+        for next in graph.neighbors(&curr) {
+            dist.entry(next).or_insert_with(|| {
+                queue.push_back(next);
+                d + 1
+            });
+        }
+    }
+    dist
+}
+
+/// Gets the nodes whose shortest path from `source` has exactly `k` edges,
+/// respecting edge direction, in ascending order.
+pub fn neighbors_at_distance<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    source: &NodeInd,
+    k: usize,
+) -> Vec<NodeInd> {
+    let mut nodes: Vec<NodeInd> = bfs_distances_within(graph, source, k)
+        .into_iter()
+        .filter(|&(_, d)| d == k)
+        .map(|(n, _)| n)
+        .collect();
+    nodes.sort_unstable();
+    nodes
+}
+
+/// Gets the ego network of `center`: the subgraph induced by every node within
+/// `radius` edges of it, respecting edge direction. The nodes get new indices
+/// as in [`ALGraph::subgraph`], so this also returns the new index of
+/// `center`.
+pub fn ego_graph<N: Clone, E: Clone, Ty: GraphType>(
+    graph: &ALGraph<N, E, Ty>,
+    center: &NodeInd,
+    radius: usize,
+) -> (ALGraph<N, E, Ty>, NodeInd) {
+    let nodes: Vec<NodeInd> = bfs_distances_within(graph, center, radius)
+        .into_keys()
+        .collect();
+    let (ego, new_index) = graph.subgraph(&nodes);
+    (ego, new_index[center])
+}
+
+/// Samples a subgraph around `seeds` for minibatch training, as in GraphSAGE:
+/// starting from the seeds, each hop `h` picks up to `fanout[h]` distinct
+/// neighbors of every node picked in the hop before, uniformly at random,
+/// respecting edge direction. After `hops` hops, this gets the subgraph
+/// induced by every node picked, including the seeds, along with the original
+/// index of each of its nodes, by new index. The nodes are in the same order as
+/// in `graph`, as in [`ALGraph::subgraph`].
+///
+/// Nodes that were already picked aren't expanded again, so with s seeds there
+/// are at most s(1 + f0 + f0 f1 + ...) nodes, where f0, f1, ... are the
+/// fanouts. Panics if `fanout` has fewer than `hops` entries.
+pub fn sample_k_hop<N: Clone, E: Clone, Ty: GraphType, R: Rng + ?Sized>(
+    graph: &ALGraph<N, E, Ty>,
+    seeds: &[NodeInd],
+    hops: usize,
+    fanout: &[usize],
+    rng: &mut R,
+) -> (ALGraph<N, E, Ty>, Vec<NodeInd>) {
+    assert!(
+        fanout.len() >= hops,
+        "need a fanout for each of the {hops} hops"
+    );
+    let mut picked: HashSet<NodeInd> = seeds.iter().copied().collect();
+    let mut frontier: Vec<NodeInd> = seeds.to_vec();
+    frontier.sort_unstable();
+    frontier.dedup();
+
+    for &k in &fanout[..hops] {
+        let mut next_frontier = vec![];
+        for n in &frontier {
+            let mut nbrs: Vec<NodeInd> = graph.distinct_neighbors(n).collect();
+            nbrs.sort_unstable();
+            for &m in nbrs.choose_multiple(rng, k) {
+                if picked.insert(m) {
+                    next_frontier.push(m);
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+
+    let mut nodes: Vec<NodeInd> = picked.into_iter().collect();
+    nodes.sort_unstable();
+    let (sample, _) = graph.subgraph(&nodes);
+    (sample, nodes)
+}
+
+/// Gets the number of edges on the shortest path to every node from whichever
+/// of `sources` is closest to it, by starting a BFS from all of the sources at
+/// once. Edge direction is respected, and unreachable nodes are not included.
+pub fn multi_source_bfs<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    sources: &[NodeInd],
+) -> HashMap<NodeInd, usize> {
+    multi_source_search(graph, sources)
+        .into_iter()
+        .map(|(n, (d, _))| (n, d))
+        .collect()
+}
+
+/// Assigns every node reachable from `sources` to its nearest source, which
+/// partitions the graph into Voronoi cells. Ties are broken in favor of the
+/// source with the lowest index. Edge direction is respected, so nodes are
+/// assigned to the nearest source with a path to them.
+pub fn graph_voronoi<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    sources: &[NodeInd],
+) -> HashMap<NodeInd, NodeInd> {
+    multi_source_search(graph, sources)
+        .into_iter()
+        .map(|(n, (_, source))| (n, source))
+        .collect()
+}
+
+/// Runs a BFS from all of `sources` at once, getting the distance from each
+/// reachable node to the nearest source and the lowest-indexed source at that
+/// distance.
+fn multi_source_search<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    sources: &[NodeInd],
+) -> HashMap<NodeInd, (usize, NodeInd)> {
+    let mut found: HashMap<NodeInd, (usize, NodeInd)> =
+        sources.iter().map(|&s| (s, (0, s))).collect();
+    let mut queue: VecDeque<NodeInd> = sources.iter().copied().collect();
+    while let Some(curr) = queue.pop_front() {
+        let (d, source) = found[&curr];
+        for next in graph.neighbors(&curr) {
+            match found.get_mut(&next) {
+                None => {
+                    found.insert(next, (d + 1, source));
+                    queue.push_back(next);
+                }
+                // the queue goes in order of distance, so this node hasn't
+                // been expanded yet and can still switch sources
+                Some((next_d, next_source)) if *next_d == d + 1 => {
+                    *next_source = source.min(*next_source);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    found
+}
+
+/// Searches for a path from `start` to `target` with at most `max_depth` edges
+/// using iterative deepening: it runs a depth-limited DFS with a limit of 0,
+/// then 1, and so on, so the first path it finds has as few edges as possible.
+/// This only needs memory for the current path, unlike BFS, which can need
+/// memory for the whole graph, at the cost of revisiting nodes. Edge direction
+/// is respected. Returns `None` if there's no path within the limit.
+pub fn iddfs<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    start: &NodeInd,
+    target: &NodeInd,
+    max_depth: usize,
+) -> Option<Vec<NodeInd>> {
+    let mut path = vec![*start];
+    (0..=max_depth).find_map(|limit| {
+        depth_limited_search(graph, target, limit, &mut path).then(|| path.clone())
+    })
+}
+
+/// Tries to extend `path` to reach `target` with at most `limit` more edges,
+/// without repeating nodes. Leaves the found path in `path` if there is one,
+/// and otherwise leaves `path` as it was.
+fn depth_limited_search<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    target: &NodeInd,
+    limit: usize,
+    path: &mut Vec<NodeInd>,
+) -> bool {
+    let curr = *path.last().unwrap();
+    if &curr == target {
+        return true;
+    }
+    if limit == 0 {
+        return false;
+    }
+    for next in graph.neighbors(&curr) {
+        if path.contains(&next) {
+            continue;
+        }
+        path.push(next);
+        if depth_limited_search(graph, target, limit - 1, path) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+/// Iterates over every simple path (one that doesn't repeat any nodes) from
+/// `source` to `target`, respecting edge direction, by backtracking DFS. If
+/// `max_len` is given, only paths with at most that many edges are included.
+/// Parallel edges don't give separate paths, and there are no paths from a node
+/// to itself.
+///
+/// Be careful: the number of simple paths can be exponential in the size of
+/// the graph, even for small graphs, so use `max_len` or only take the first
+/// few paths unless the graph is tiny.
+pub fn all_simple_paths<'a, N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &'a G,
+    source: &NodeInd,
+    target: &NodeInd,
+    max_len: Option<usize>,
+) -> impl Iterator<Item = Vec<NodeInd>> + 'a {
+    let target = *target;
+    // the neighbors left to try from each node on the path, in reverse order
+    let unvisited = move |n: &NodeInd| {
+        let mut seen = HashSet::new();
+        let mut nbrs: Vec<NodeInd> = graph.neighbors(n).filter(|m| seen.insert(*m)).collect();
+        nbrs.reverse();
+        nbrs
+    };
+    let mut path = vec![*source];
+    let mut stack = vec![unvisited(source)];
+
+    std::iter::from_fn(move || {
+        while let Some(nbrs) = stack.last_mut() {
+            let Some(next) = nbrs.pop() else {
+                stack.pop();
+                path.pop();
+                continue;
+            };
+            if path.contains(&next) {
+                continue;
+            }
+            // path.len() is the number of edges with the next node added
+            if next == target {
+                if max_len.is_none_or(|m| path.len() <= m) {
+                    let mut found = path.clone();
+                    found.push(next);
+                    return Some(found);
+                }
+            } else if max_len.is_none_or(|m| path.len() < m) {
+                path.push(next);
+                stack.push(unvisited(&next));
+            }
+        }
+        None
+    })
+}
+
+/// Gets the number of edges on the shortest path between every pair of nodes,
+/// by running a BFS from each node. Rows and columns are in ascending order of
+/// node index, so for graphs where nodes have been removed the positions won't
+/// match the node indices. Entries for unreachable pairs are `usize::MAX`.
+pub fn all_pairs_shortest_paths<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+) -> Vec<Vec<usize>> {
+    let nodes = graph.nodes_sorted();
+    nodes
+        .iter()
+        .map(|source| distance_row(graph, source, &nodes))
+        .collect()
+}
+
+/// Gets the BFS distance from `source` to each of `nodes`, in order, with
+/// `usize::MAX` for unreachable nodes.
+pub(crate) fn distance_row<N, E, Ty: GraphType, G: GraphView<N, E, Ty>>(
+    graph: &G,
+    source: &NodeInd,
+    nodes: &[NodeInd],
+) -> Vec<usize> {
+    let dist = bfs_distances(graph, source);
+    nodes
+        .iter()
+        .map(|n| dist.get(n).copied().unwrap_or(usize::MAX))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::{Directed, Undirected};
+    use crate::test_util::graph_from_edges;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_bfs_distances() {
+        let g = graph_from_edges::<Directed>(5, &[(0, 1), (1, 2), (0, 2), (2, 3), (4, 0)]);
+        let dist = bfs_distances(&g, &0);
+        assert_eq!(dist, HashMap::from([(0, 0), (1, 1), (2, 1), (3, 2)]));
+    }
+
+    #[test]
+    fn test_bfs_edges() {
+        // a square with a diagonal, a tail, a self-loop, a doubled edge, and a
+        // separate edge
+        let g = graph_from_edges::<Undirected>(
+            8,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 3),
+                (3, 0),
+                (0, 2),
+                (3, 4),
+                (4, 4),
+                (4, 5),
+                (4, 5),
+                (6, 7),
+            ],
+        );
+        let edges: Vec<EdgeInd> = bfs_edges(&g, &0).collect();
+        let mut sorted = edges.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..9).collect::<Vec<_>>());
+        // the edges at the start come before anything further away
+        let mut first: Vec<EdgeInd> = edges[..3].to_vec();
+        first.sort_unstable();
+        assert_eq!(first, vec![0, 3, 4]);
+
+        let g = graph_from_edges::<Directed>(3, &[(1, 0), (0, 2), (2, 1)]);
+        assert_eq!(bfs_edges(&g, &0).collect::<Vec<_>>(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_bfs_tree() {
+        let g = graph_from_edges::<Undirected>(6, &[(0, 1), (1, 2), (2, 3), (0, 3), (3, 4)]);
+        let tree = bfs_tree(&g, &1);
+        for target in 0..5 {
+            let path = tree.path_to(&target).unwrap();
+            assert_eq!(path[0], 1);
+            assert_eq!(*path.last().unwrap(), target);
+            assert_eq!(tree.distance_to(&target), Some((path.len() - 1) as f64));
+            for w in path.windows(2) {
+                assert!(g.neighbors(&w[0]).any(|n| n == w[1]));
+            }
+        }
+        assert_eq!(tree.distance_to(&4), Some(3.0));
+        assert_eq!(tree.path_to(&5), None);
+    }
+
+    #[test]
+    fn test_reachable_from() {
+        // 3 and 4 can only be reached by going backwards along an edge
+        let g = graph_from_edges::<Directed>(5, &[(0, 1), (1, 2), (2, 0), (3, 1), (4, 3)]);
+        assert_eq!(reachable_from(&g, &0), HashSet::from([0, 1, 2]));
+        assert_eq!(reachable_from(&g, &4), HashSet::from([0, 1, 2, 3, 4]));
+        assert!(can_reach(&g, &4, &2));
+        assert!(!can_reach(&g, &0, &3));
+        assert!(can_reach(&g, &3, &3));
+    }
+
+    #[test]
+    fn test_neighbors_at_distance() {
+        let g = graph_from_edges::<Undirected>(
+            7,
+            &[
+                (0, 1),
+                (0, 2),
+                (1, 3),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (0, 0),
+                (0, 1),
+            ],
+        );
+        assert_eq!(neighbors_at_distance(&g, &0, 0), vec![0]);
+        assert_eq!(neighbors_at_distance(&g, &0, 1), vec![1, 2]);
+        let mut direct: Vec<NodeInd> = g.neighbors(&0).filter(|&n| n != 0).collect();
+        direct.sort();
+        direct.dedup();
+        assert_eq!(neighbors_at_distance(&g, &0, 1), direct);
+        assert_eq!(neighbors_at_distance(&g, &0, 2), vec![3]);
+        assert_eq!(neighbors_at_distance(&g, &0, 4), vec![5]);
+        assert!(neighbors_at_distance(&g, &0, 5).is_empty());
+        assert_eq!(bfs_distances_within(&g, &0, 2).len(), 4);
+    }
+
+    #[test]
+    fn test_ego_graph() {
+        // 2 is the center, with neighbors 1, 3, and 4, where 3 and 4 are
+        // connected to each other and 1 is connected further out to 0
+        let g =
+            graph_from_edges::<Undirected>(6, &[(0, 1), (1, 2), (2, 3), (2, 4), (3, 4), (4, 5)]);
+        let (ego, center) = ego_graph(&g, &2, 1);
+        assert_eq!(center, 1);
+        assert_eq!(ego.nodes().count(), 4);
+        assert_eq!(ego.edges().count(), 4);
+        assert_eq!(ego.neighbors(&center).count(), 3);
+
+        let (ego, center) = ego_graph(&g, &2, 0);
+        assert_eq!(
+            (ego.nodes().count(), ego.edges().count(), center),
+            (1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_sample_k_hop() {
+        // a complete graph, so every node always has plenty of neighbors
+        let edges: Vec<(NodeInd, NodeInd)> = (0..30)
+            .flat_map(|a| (a + 1..30).map(move |b| (a, b)))
+            .collect();
+        let g = graph_from_edges::<Undirected>(30, &edges);
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let (sample, original) = sample_k_hop(&g, &[4, 17], 2, &[3, 2], &mut rng);
+            let count = sample.nodes().count();
+            assert_eq!(count, original.len());
+            // 2 seeds, up to 6 more after one hop, and up to 12 more after two
+            assert!((3..=20).contains(&count));
+            assert!(original.contains(&4) && original.contains(&17));
+            // the sample is induced, so it's complete too
+            assert_eq!(sample.edges().count(), count * (count - 1) / 2);
+        }
+
+        // a path only has one way to go from its end
+        let path = graph_from_edges::<Directed>(5, &[(0, 1), (1, 2), (2, 3), (3, 4)]);
+        let (sample, original) = sample_k_hop(&path, &[1], 2, &[5, 5], &mut rng);
+        assert_eq!(original, vec![1, 2, 3]);
+        assert_eq!(sample.edges().count(), 2);
+        let (_, original) = sample_k_hop(&path, &[1, 1], 0, &[], &mut rng);
+        assert_eq!(original, vec![1]);
+    }
+
+    #[test]
+    fn test_multi_source_bfs() {
+        // a path 0 - 1 - 2 - 3 - 4 - 5 - 6 with sources at 1 and 5
+        let edges: Vec<(NodeInd, NodeInd)> = (0..6).map(|i| (i, i + 1)).collect();
+        let g = graph_from_edges::<Undirected>(8, &edges);
+        let dist = multi_source_bfs(&g, &[1, 5]);
+        let expected = [1, 0, 1, 2, 1, 0, 1];
+        for (n, d) in expected.iter().enumerate() {
+            assert_eq!(dist[&n], *d);
+        }
+        assert!(!dist.contains_key(&7));
+    }
+
+    #[test]
+    fn test_graph_voronoi() {
+        // a path 0 - 1 - 2 - 3 - 4 - 5 - 6 with sources at 5 and 1, where 3 is
+        // equally close to both
+        let edges: Vec<(NodeInd, NodeInd)> = (0..6).map(|i| (i, i + 1)).collect();
+        let g = graph_from_edges::<Undirected>(8, &edges);
+        let cells = graph_voronoi(&g, &[5, 1]);
+        let expected = [1, 1, 1, 1, 5, 5, 5];
+        for (n, source) in expected.iter().enumerate() {
+            assert_eq!(cells[&n], *source);
+        }
+        assert!(!cells.contains_key(&7));
+    }
+
+    #[test]
+    fn test_iddfs() {
+        // a long way round from 0 to 4, and a shortcut through 5
+        let g = graph_from_edges::<Directed>(
+            7,
+            &[(0, 1), (1, 2), (2, 3), (3, 4), (0, 5), (5, 4), (4, 6)],
+        );
+        assert_eq!(iddfs(&g, &0, &4, 10), Some(vec![0, 5, 4]));
+        assert_eq!(iddfs(&g, &0, &6, 3), Some(vec![0, 5, 4, 6]));
+        assert_eq!(iddfs(&g, &0, &6, 2), None);
+        assert_eq!(iddfs(&g, &0, &0, 0), Some(vec![0]));
+        assert_eq!(iddfs(&g, &4, &0, 10), None);
+    }
+
+    #[test]
+    fn test_all_simple_paths() {
+        let g = graph_from_edges::<Undirected>(
+            5,
+            &[(0, 1), (0, 2), (1, 2), (1, 3), (2, 3), (3, 4), (1, 3)],
+        );
+        let mut paths: Vec<Vec<NodeInd>> = all_simple_paths(&g, &0, &3, None).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                vec![0, 1, 2, 3],
+                vec![0, 1, 3],
+                vec![0, 2, 1, 3],
+                vec![0, 2, 3],
+            ]
+        );
+
+        let mut short: Vec<Vec<NodeInd>> = all_simple_paths(&g, &0, &3, Some(2)).collect();
+        short.sort();
+        assert_eq!(short, vec![vec![0, 1, 3], vec![0, 2, 3]]);
+        assert_eq!(all_simple_paths(&g, &0, &4, Some(2)).count(), 0);
+        assert_eq!(all_simple_paths(&g, &0, &4, Some(3)).count(), 2);
+        assert_eq!(all_simple_paths(&g, &0, &0, None).count(), 0);
+    }
+
+    #[test]
+    fn test_all_pairs_shortest_paths() {
+        let g = graph_from_edges::<Directed>(3, &[(0, 1), (1, 2)]);
+        let inf = usize::MAX;
+        assert_eq!(
+            all_pairs_shortest_paths(&g),
+            vec![vec![0, 1, 2], vec![inf, 0, 1], vec![inf, inf, 0]]
+        );
+    }
+}
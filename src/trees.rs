@@ -0,0 +1,472 @@
+//! Algorithms for trees.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+use crate::adj_list_graph::ALGraph;
+use crate::graph_base::{Edge, EdgeInd, GraphBase, GraphView, NodeInd, Undirected};
+use crate::traversal::bfs_distances;
+use crate::union_find::UnionFind;
+
+/// The error for when an algorithm that needs a tree is given a graph that
+/// isn't one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NotATree;
+
+impl fmt::Display for NotATree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "graph is not a tree")
+    }
+}
+
+impl Error for NotATree {}
+
+/// Whether the undirected graph is a tree: connected, with one fewer edge than
+/// it has nodes. The graph with no nodes isn't a tree.
+pub fn is_tree<N, E, G: GraphView<N, E, Undirected>>(graph: &G) -> bool {
+    let Some(root) = graph.nodes().next() else {
+        return false;
+    };
+    let n = graph.nodes().count();
+    graph.edges().count() == n - 1 && bfs_distances(graph, &root).len() == n
+}
+
+/// Encodes a labeled tree as its Prüfer sequence: repeatedly remove the leaf
+/// with the lowest index and write down its neighbor, until two nodes are
+/// left. A tree with n nodes gets a sequence of n - 2 node indices, and every
+/// such sequence comes from exactly one tree on the same nodes. Returns an
+/// error if the graph isn't a tree with at least two nodes.
+pub fn to_prufer<N, E, G: GraphView<N, E, Undirected>>(tree: &G) -> Result<Vec<NodeInd>, NotATree> {
+    if !is_tree(tree) || tree.nodes().count() < 2 {
+        return Err(NotATree);
+    }
+
+    let mut degree: HashMap<NodeInd, usize> = tree
+        .nodes()
+        .map(|n| (n, tree.edges_at(&n).count()))
+        .collect();
+    let mut leaves: BTreeSet<NodeInd> = degree
+        .iter()
+        .filter(|(_, &d)| d == 1)
+        .map(|(&n, _)| n)
+        .collect();
+    let mut removed: BTreeSet<NodeInd> = BTreeSet::new();
+
+    let mut seq = Vec::with_capacity(degree.len() - 2);
+    while seq.len() < degree.len() - 2 {
+        let leaf = leaves.pop_first().unwrap();
+        removed.insert(leaf);
+        let parent = tree
+            .neighbors(&leaf)
+            .find(|n| !removed.contains(n))
+            .unwrap();
+        seq.push(parent);
+
+        let d = degree.get_mut(&parent).unwrap();
+        *d -= 1;
+        if *d == 1 {
+            leaves.insert(parent);
+        }
+    }
+    Ok(seq)
+}
+
+/// Gets the farthest node from `source`, with the lowest index among ties, and
+/// its distance.
+fn farthest_from<N, E, G: GraphView<N, E, Undirected>>(
+    graph: &G,
+    source: &NodeInd,
+) -> (NodeInd, usize) {
+    bfs_distances(graph, source)
+        .into_iter()
+        .max_by_key(|&(n, d)| (d, Reverse(n)))
+        .unwrap()
+}
+
+/// Gets the two ends of a longest path in a tree and the number of edges on it.
+/// The farthest node from any node is one end of a longest path, so this takes
+/// two BFS passes: one from the lowest node, to find one end, and one from that
+/// end. Returns an error if the graph isn't a tree.
+pub fn tree_diameter<N, E, G: GraphView<N, E, Undirected>>(
+    tree: &G,
+) -> Result<(NodeInd, NodeInd, usize), NotATree> {
+    if !is_tree(tree) {
+        return Err(NotATree);
+    }
+    let root = tree.nodes().min().unwrap();
+    let (start, _) = farthest_from(tree, &root);
+    let (end, diameter) = farthest_from(tree, &start);
+    Ok((start, end, diameter))
+}
+
+/// Gets the lowest common ancestor of each pair of nodes in `queries`, in a
+/// tree rooted at `root`: the deepest node that both are descended from, where
+/// every node is descended from itself. This is Tarjan's offline algorithm,
+/// which answers all of the queries in one DFS, merging each subtree into its
+/// parent's set with union-find once it's done, so it takes nearly linear time
+/// in the size of the tree plus the number of queries. Returns an error if the
+/// graph isn't a tree, and panics if a query has a node that isn't in it.
+pub fn tarjan_offline_lca<N, E, G: GraphView<N, E, Undirected>>(
+    tree: &G,
+    root: &NodeInd,
+    queries: &[(NodeInd, NodeInd)],
+) -> Result<Vec<NodeInd>, NotATree> {
+    if !is_tree(tree) {
+        return Err(NotATree);
+    }
+
+    let mut queries_at: HashMap<NodeInd, Vec<(NodeInd, usize)>> = HashMap::new();
+    for (i, &(u, v)) in queries.iter().enumerate() {
+        queries_at.entry(u).or_default().push((v, i));
+        queries_at.entry(v).or_default().push((u, i));
+    }
+
+    let mut answers: Vec<Option<NodeInd>> = vec![None; queries.len()];
+    let mut sets = UnionFind::new();
+    // the ancestor standing for each set, by its representative
+    let mut ancestor: HashMap<NodeInd, NodeInd> = HashMap::from([(*root, *root)]);
+    let mut done: HashSet<NodeInd> = HashSet::new();
+    // each frame is a node, its parent, and the rest of its children
+    let mut stack = vec![(*root, *root, tree.neighbors(root).collect::<Vec<_>>())];
+    while let Some((curr, parent, children)) = stack.last_mut() {
+        let (curr, parent) = (*curr, *parent);
+        if let Some(child) = children.pop() {
+            if child != parent {
+                ancestor.insert(child, child);
+                let grandchildren = tree.neighbors(&child).collect();
+                stack.push((child, curr, grandchildren));
+            }
+            continue;
+        }
+
+        stack.pop();
+        done.insert(curr);
+        for &(other, i) in queries_at.get(&curr).into_iter().flatten() {
+            if done.contains(&other) {
+                answers[i] = Some(ancestor[&sets.find(other)]);
+            }
+        }
+        if curr != parent {
+            sets.union(parent, curr);
+            ancestor.insert(sets.find(parent), parent);
+        }
+    }
+
+    Ok(answers
+        .into_iter()
+        .map(|a| a.expect("query node isn't in the tree"))
+        .collect())
+}
+
+/// Decodes a Prüfer sequence into the labeled tree it came from, with nodes
+/// `0..seq.len() + 2`. This is the inverse of [`to_prufer`] for trees with
+/// those nodes. Panics if the sequence has a node index that's too large.
+pub fn from_prufer(seq: &[NodeInd]) -> ALGraph<(), (), Undirected> {
+    let n = seq.len() + 2;
+    let mut tree = ALGraph::new();
+    for _ in 0..n {
+        tree.add_node(());
+    }
+
+    let mut degree = vec![1; n];
+    for &x in seq {
+        assert!(
+            x < n,
+            "node {} is out of range for a Prüfer sequence of length {}",
+            x,
+            seq.len()
+        );
+        degree[x] += 1;
+    }
+    let mut leaves: BTreeSet<NodeInd> = (0..n).filter(|&i| degree[i] == 1).collect();
+    for &x in seq {
+        let leaf = leaves.pop_first().unwrap();
+        tree.add_edge(&leaf, &x, ());
+        degree[x] -= 1;
+        if degree[x] == 1 {
+            leaves.insert(x);
+        }
+    }
+    let last = leaves.pop_first().unwrap();
+    let other = leaves.pop_first().unwrap();
+    tree.add_edge(&last, &other, ());
+    tree
+}
+
+/// Iterates over every spanning tree of an undirected graph, each as its edges
+/// in ascending order of index, with the trees in lexicographic order. This
+/// backtracks over the edges, deciding whether each one is in the tree, and
+/// only leaves an edge out if the rest can still connect the graph, so every
+/// branch it tries ends in a tree. Parallel edges give different trees, as in
+/// [`spanning_tree_count`](crate::matrices::spanning_tree_count). Graphs that
+/// aren't connected, including the empty graph, have no spanning trees.
+///
+/// Be careful: the number of spanning trees grows exponentially, and there are
+/// already n^(n - 2) of them for the complete graph on n nodes, so only use
+/// this on small graphs or take the first few trees. Each tree takes O(E²)
+/// time to find.
+pub fn all_spanning_trees<N, E, G: GraphView<N, E, Undirected>>(
+    graph: &G,
+) -> impl Iterator<Item = Vec<EdgeInd>> {
+    let indices = graph.edges_sorted();
+    let ends: Vec<(NodeInd, NodeInd)> = indices.iter().map(|e| graph.edge_endpoints(e)).collect();
+    let num_nodes = graph.nodes().count();
+    // whether the chosen edges and the ones from `next` on connect the graph
+    let connects = move |ends: &[(NodeInd, NodeInd)], chosen: &[usize], next: usize| {
+        let (_, merged) = union_edges(ends, chosen.iter().copied().chain(next..ends.len()));
+        merged + 1 == num_nodes
+    };
+
+    // each state is the next edge to decide on and the positions of the edges
+    // chosen so far, and every state can still finish a tree
+    let mut stack: Vec<(usize, Vec<usize>)> = vec![];
+    if connects(&ends, &[], 0) {
+        stack.push((0, vec![]));
+    }
+    std::iter::from_fn(move || {
+        while let Some((next, chosen)) = stack.pop() {
+            if chosen.len() + 1 == num_nodes {
+                return Some(chosen.iter().map(|&i| indices[i]).collect());
+            }
+            if connects(&ends, &chosen, next + 1) {
+                stack.push((next + 1, chosen.clone()));
+            }
+            let (mut sets, _) = union_edges(&ends, chosen.iter().copied());
+            if !sets.connected(ends[next].0, ends[next].1) {
+                let mut with = chosen;
+                with.push(next);
+                stack.push((next + 1, with));
+            }
+        }
+        None
+    })
+}
+
+/// Gets the edges of a minimum spanning forest of an undirected graph, in
+/// ascending order of index, using Kruskal's algorithm: go through the edges
+/// from lightest to heaviest, keeping each one that joins two trees. Edges with
+/// the same weight are taken in order of `tiebreak`, and then of index, so the
+/// same graph always gives the same forest, however its edges are stored.
+/// Self-loops are never used.
+pub fn kruskal_mst_deterministic<N, E, G: GraphView<N, E, Undirected>, F, T, K: Ord>(
+    graph: &G,
+    weight: F,
+    tiebreak: T,
+) -> Vec<EdgeInd>
+where
+    F: Fn(&Edge<E>) -> f64,
+    T: Fn(&Edge<E>) -> K,
+{
+    let mut edges: Vec<(f64, K, EdgeInd)> = graph
+        .edges()
+        .map(|e| {
+            let edge = graph.edge(&e);
+            (weight(edge), tiebreak(edge), e)
+        })
+        .collect();
+    edges.sort_by(|a, b| {
+        a.0.total_cmp(&b.0)
+            .then_with(|| (&a.1, a.2).cmp(&(&b.1, b.2)))
+    });
+
+    let mut sets = UnionFind::new();
+    let mut forest: Vec<EdgeInd> = edges
+        .into_iter()
+        .map(|(_, _, e)| e)
+        .filter(|e| {
+            let (u, v) = graph.edge_endpoints(e);
+            sets.union(u, v)
+        })
+        .collect();
+    forest.sort_unstable();
+    forest
+}
+
+/// Merges the ends of the edges at the given positions in `ends`, returning
+/// the sets and the number of merges that joined two different sets.
+fn union_edges(
+    ends: &[(NodeInd, NodeInd)],
+    positions: impl Iterator<Item = usize>,
+) -> (UnionFind, usize) {
+    let mut sets = UnionFind::new();
+    let merged = positions
+        .filter(|&i| sets.union(ends[i].0, ends[i].1))
+        .count();
+    (sets, merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrices::spanning_tree_count;
+    use crate::test_util::{graph_from_edges, weighted_graph_from_edges};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_prufer_round_trip() {
+        let mut rng = StdRng::seed_from_u64(9);
+        for n in 2..30 {
+            // attach each node to a random earlier one
+            let edges: Vec<(NodeInd, NodeInd)> = (1..n).map(|i| (rng.gen_range(0..i), i)).collect();
+            let tree = graph_from_edges::<Undirected>(n, &edges);
+            let seq = to_prufer(&tree).unwrap();
+            assert_eq!(seq.len(), n - 2);
+            assert_eq!(from_prufer(&seq), tree);
+
+            let seq: Vec<NodeInd> = (0..n - 2).map(|_| rng.gen_range(0..n)).collect();
+            assert_eq!(to_prufer(&from_prufer(&seq)), Ok(seq));
+        }
+    }
+
+    #[test]
+    fn test_prufer_known() {
+        let tree = graph_from_edges::<Undirected>(6, &[(0, 3), (1, 3), (2, 3), (3, 4), (4, 5)]);
+        assert_eq!(to_prufer(&tree), Ok(vec![3, 3, 3, 4]));
+    }
+
+    #[test]
+    fn test_tree_diameter() {
+        let path = graph_from_edges::<Undirected>(6, &[(2, 0), (0, 4), (4, 1), (1, 5), (5, 3)]);
+        let (start, end, d) = tree_diameter(&path).unwrap();
+        assert_eq!((start.min(end), start.max(end), d), (2, 3, 5));
+
+        // the longest path in this tree avoids the root
+        let tree = graph_from_edges::<Undirected>(
+            8,
+            &[(0, 1), (1, 2), (2, 3), (2, 4), (4, 5), (1, 6), (6, 7)],
+        );
+        assert_eq!(tree_diameter(&tree), Ok((5, 7, 5)));
+
+        let single = graph_from_edges::<Undirected>(1, &[]);
+        assert_eq!(tree_diameter(&single), Ok((0, 0, 0)));
+        let cycle = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(tree_diameter(&cycle), Err(NotATree));
+    }
+
+    #[test]
+    fn test_tarjan_offline_lca() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let n = 60;
+        let parent: Vec<NodeInd> = (0..n)
+            .map(|i| if i == 0 { 0 } else { rng.gen_range(0..i) })
+            .collect();
+        let edges: Vec<(NodeInd, NodeInd)> = (1..n).map(|i| (parent[i], i)).collect();
+        let tree = graph_from_edges::<Undirected>(n, &edges);
+
+        // naive LCA: walk the deeper node up until they meet
+        let depth = bfs_distances(&tree, &0);
+        let naive = |mut u: NodeInd, mut v: NodeInd| {
+            while u != v {
+                if depth[&u] >= depth[&v] {
+                    u = parent[u];
+                } else {
+                    v = parent[v];
+                }
+            }
+            u
+        };
+
+        let mut queries: Vec<(NodeInd, NodeInd)> = (0..200)
+            .map(|_| (rng.gen_range(0..n), rng.gen_range(0..n)))
+            .collect();
+        queries.push((5, 5));
+        queries.push((0, 17));
+        let expected: Vec<NodeInd> = queries.iter().map(|&(u, v)| naive(u, v)).collect();
+        assert_eq!(tarjan_offline_lca(&tree, &0, &queries), Ok(expected));
+
+        let cycle = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(tarjan_offline_lca(&cycle, &0, &[(1, 2)]), Err(NotATree));
+    }
+
+    #[test]
+    fn test_prufer_not_a_tree() {
+        let cycle = graph_from_edges::<Undirected>(3, &[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(to_prufer(&cycle), Err(NotATree));
+        let forest = graph_from_edges::<Undirected>(4, &[(0, 1), (2, 3), (2, 3)]);
+        assert_eq!(to_prufer(&forest), Err(NotATree));
+        let single = graph_from_edges::<Undirected>(1, &[]);
+        assert_eq!(to_prufer(&single), Err(NotATree));
+    }
+
+    #[test]
+    fn test_all_spanning_trees() {
+        let k4_edges: Vec<(NodeInd, NodeInd)> = (0..4)
+            .flat_map(|a| (a + 1..4).map(move |b| (a, b)))
+            .collect();
+        let k4 = graph_from_edges::<Undirected>(4, &k4_edges);
+        let trees: Vec<Vec<EdgeInd>> = all_spanning_trees(&k4).collect();
+        assert_eq!(trees.len(), 16);
+        assert_eq!(trees.len() as u128, spanning_tree_count(&k4));
+        assert_eq!(trees[0], vec![0, 1, 2]);
+        for tree in &trees {
+            let tree_edges: Vec<(NodeInd, NodeInd)> =
+                tree.iter().map(|e| k4.edge_endpoints(e)).collect();
+            assert!(is_tree(&graph_from_edges::<Undirected>(4, &tree_edges)));
+        }
+
+        // a triangle with a doubled edge, a self-loop and a tail
+        let g =
+            graph_from_edges::<Undirected>(4, &[(0, 1), (1, 2), (2, 0), (0, 1), (2, 2), (2, 3)]);
+        assert_eq!(
+            all_spanning_trees(&g).count() as u128,
+            spanning_tree_count(&g)
+        );
+        assert_eq!(all_spanning_trees(&g).count(), 5);
+
+        let split = graph_from_edges::<Undirected>(3, &[(0, 1)]);
+        assert_eq!(all_spanning_trees(&split).count(), 0);
+        let single = graph_from_edges::<Undirected>(1, &[]);
+        assert_eq!(
+            all_spanning_trees(&single).collect::<Vec<_>>(),
+            vec![vec![]]
+        );
+    }
+
+    #[test]
+    fn test_kruskal_mst_deterministic() {
+        // with every weight the same, the tiebreak picks the forest
+        let square = graph_from_edges::<Undirected>(4, &[(0, 1), (1, 2), (2, 3), (3, 0)]);
+        assert_eq!(
+            kruskal_mst_deterministic(&square, |_| 1.0, |e| e.index),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            kruskal_mst_deterministic(&square, |_| 1.0, |e| Reverse(e.index)),
+            vec![1, 2, 3]
+        );
+
+        let mut rng = StdRng::seed_from_u64(28);
+        for _ in 0..20 {
+            // few distinct weights, so there are lots of ties
+            let n = rng.gen_range(1..7);
+            let edges: Vec<(NodeInd, NodeInd, f64)> = (0..rng.gen_range(0..12))
+                .map(|_| {
+                    let w = rng.gen_range(0..3) as f64;
+                    (rng.gen_range(0..n), rng.gen_range(0..n), w)
+                })
+                .collect();
+            // two copies of the graph store their edges in different orders,
+            // but give the same forest
+            let g = weighted_graph_from_edges::<Undirected>(n, &edges);
+            let copy = weighted_graph_from_edges::<Undirected>(n, &edges);
+            let tiebreak = |e: &Edge<f64>| (e.start.max(e.end), e.start.min(e.end));
+            let forest = kruskal_mst_deterministic(&g, |e| e.data, tiebreak);
+            assert_eq!(
+                kruskal_mst_deterministic(&copy, |e| e.data, tiebreak),
+                forest
+            );
+
+            // and it's as light as any spanning tree
+            let total = |tree: &[EdgeInd]| tree.iter().map(|e| g.edge(e).data).sum::<f64>();
+            if let Some(best) = all_spanning_trees(&g)
+                .map(|tree| total(&tree))
+                .min_by(f64::total_cmp)
+            {
+                assert_eq!(total(&forest), best);
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! A disjoint-set forest over node indices.
+
+use std::collections::HashMap;
+
+use crate::graph_base::NodeInd;
+
+/// Keeps track of a partition of nodes into disjoint sets, with union by size
+/// and path compression, so any sequence of operations takes nearly linear
+/// time. Every node starts out in a set of its own, and nodes don't need to be
+/// added before they're used.
+#[derive(Clone, Debug, Default)]
+pub struct UnionFind {
+    parent: HashMap<NodeInd, NodeInd>,
+    size: HashMap<NodeInd, usize>,
+}
+
+impl UnionFind {
+    /// Makes a new partition with every node on its own.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the representative of the set containing `n`: two nodes are in the
+    /// same set exactly when they have the same representative.
+    pub fn find(&mut self, n: NodeInd) -> NodeInd {
+        let mut root = n;
+        while let Some(&p) = self.parent.get(&root) {
+            root = p;
+        }
+        // point everything on the way straight at the root
+        let mut curr = n;
+        while let Some(&p) = self.parent.get(&curr) {
+            if p == root {
+                break;
+            }
+            self.parent.insert(curr, root);
+            curr = p;
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns `false` if they were
+    /// already the same set.
+    pub fn union(&mut self, a: NodeInd, b: NodeInd) -> bool {
+        let (mut ra, mut rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return false;
+        }
+        let size_a = self.size.get(&ra).copied().unwrap_or(1);
+        let size_b = self.size.get(&rb).copied().unwrap_or(1);
+        if size_a < size_b {
+            std::mem::swap(&mut ra, &mut rb);
+        }
+        self.parent.insert(rb, ra);
+        self.size.insert(ra, size_a + size_b);
+        self.size.remove(&rb);
+        true
+    }
+
+    /// Whether `a` and `b` are in the same set.
+    pub fn connected(&mut self, a: NodeInd, b: NodeInd) -> bool {
+        self.find(a) == self.find(b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut sets = UnionFind::new();
+        assert!(!sets.connected(0, 1));
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(3, 10));
+        assert!(!sets.union(10, 2));
+        assert!(sets.connected(0, 1));
+        assert!(!sets.connected(1, 2));
+        assert!(sets.union(1, 10));
+        assert!((0..4).all(|n| sets.find(n) == sets.find(10)));
+        assert_eq!(sets.find(7), 7);
+    }
+}
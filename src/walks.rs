@@ -0,0 +1,198 @@
+//! Random walks on graphs.
+
+use std::collections::HashSet;
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::graph_base::{GraphType, GraphView, NodeInd};
+
+/// Takes a random walk of at most `steps` steps from `start`, at each step
+/// following an edge from the current node chosen uniformly at random. Edge
+/// direction is respected, and parallel edges make a neighbor proportionally
+/// more likely. The walk stops early if it reaches a node with no edges going
+/// out of it. The returned walk includes `start`, so it has at most `steps + 1`
+/// nodes.
+pub fn random_walk<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, R: Rng + ?Sized>(
+    graph: &G,
+    start: &NodeInd,
+    steps: usize,
+    rng: &mut R,
+) -> Vec<NodeInd> {
+    let mut walk = vec![*start];
+    let mut curr = *start;
+    for _ in 0..steps {
+        let nbrs: Vec<NodeInd> = graph.neighbors(&curr).collect();
+        match nbrs.choose(rng) {
+            Some(&next) => {
+                walk.push(next);
+                curr = next;
+            }
+            None => break,
+        }
+    }
+    walk
+}
+
+/// Takes a biased random walk of at most `steps` steps from `start`, as in
+/// node2vec. After the first step, which is uniform, each neighbor x of the
+/// current node is weighted by where it is relative to the previous node t:
+/// 1/p if x is t itself, 1 if x is a neighbor of t, and 1/q otherwise. A low
+/// `p` makes the walk backtrack often, a high `q` keeps it near where it
+/// started like a BFS, and a low `q` pushes it outwards like a DFS. With
+/// `p = q = 1` this is exactly [`random_walk`], down to how it uses `rng`.
+///
+/// Like [`random_walk`], this respects edge direction, counts parallel edges
+/// separately, and stops early at dead ends. Panics if `p` or `q` isn't a
+/// positive, finite number.
+pub fn node2vec_walk<N, E, Ty: GraphType, G: GraphView<N, E, Ty>, R: Rng + ?Sized>(
+    graph: &G,
+    start: &NodeInd,
+    steps: usize,
+    p: f64,
+    q: f64,
+    rng: &mut R,
+) -> Vec<NodeInd> {
+    assert!(
+        p > 0.0 && q > 0.0 && p.is_finite() && q.is_finite(),
+        "node2vec needs positive, finite p and q, but got p = {} and q = {}",
+        p,
+        q
+    );
+    let mut walk = vec![*start];
+    let mut prev: Option<NodeInd> = None;
+    let mut curr = *start;
+    for _ in 0..steps {
+        let nbrs: Vec<NodeInd> = graph.neighbors(&curr).collect();
+        if nbrs.is_empty() {
+            break;
+        }
+        let next = match prev {
+            Some(prev) if p != 1.0 || q != 1.0 => {
+                let prev_nbrs: HashSet<NodeInd> = graph.neighbors(&prev).collect();
+                let weights = nbrs.iter().map(|x| {
+                    if *x == prev {
+                        1.0 / p
+                    } else if prev_nbrs.contains(x) {
+                        1.0
+                    } else {
+                        1.0 / q
+                    }
+                });
+                nbrs[WeightedIndex::new(weights).unwrap().sample(rng)]
+            }
+            _ => *nbrs.choose(rng).unwrap(),
+        };
+        walk.push(next);
+        prev = Some(curr);
+        curr = next;
+    }
+    walk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_base::{Directed, Undirected};
+    use crate::test_util::graph_from_edges;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_random_walk_follows_edges() {
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3), (3, 1), (1, 4)];
+        let g = graph_from_edges::<Directed>(5, &edges);
+        let mut rng = StdRng::seed_from_u64(1234);
+        for _ in 0..20 {
+            let walk = random_walk(&g, &0, 30, &mut rng);
+            assert_eq!(walk[0], 0);
+            assert!(walk.len() <= 31);
+            for pair in walk.windows(2) {
+                assert!(edges.contains(&(pair[0], pair[1])));
+            }
+            // node 4 is a dead end, so the walk must have stopped there
+            if walk.len() < 31 {
+                assert_eq!(walk.last(), Some(&4));
+            }
+        }
+    }
+
+    /// Makes a 6x6 grid graph.
+    fn grid() -> Vec<(NodeInd, NodeInd)> {
+        let mut edges = vec![];
+        for i in 0..6 {
+            for j in 0..6 {
+                if i < 5 {
+                    edges.push((6 * i + j, 6 * (i + 1) + j));
+                }
+                if j < 5 {
+                    edges.push((6 * i + j, 6 * i + j + 1));
+                }
+            }
+        }
+        edges
+    }
+
+    /// The fraction of steps that go back to the node before.
+    fn backtrack_rate(walk: &[NodeInd]) -> f64 {
+        let backtracks = walk.windows(3).filter(|w| w[0] == w[2]).count();
+        backtracks as f64 / (walk.len() - 2) as f64
+    }
+
+    #[test]
+    fn test_node2vec_unbiased() {
+        let g = graph_from_edges::<Undirected>(36, &grid());
+        let mut rng1 = StdRng::seed_from_u64(77);
+        let mut rng2 = StdRng::seed_from_u64(77);
+        for _ in 0..10 {
+            assert_eq!(
+                node2vec_walk(&g, &0, 50, 1.0, 1.0, &mut rng1),
+                random_walk(&g, &0, 50, &mut rng2)
+            );
+        }
+    }
+
+    #[test]
+    fn test_node2vec_biased() {
+        let edges = grid();
+        let g = graph_from_edges::<Undirected>(36, &edges);
+        let mut rng = StdRng::seed_from_u64(77);
+
+        let local = node2vec_walk(&g, &14, 2000, 0.01, 1.0, &mut rng);
+        let outward = node2vec_walk(&g, &14, 2000, 100.0, 0.01, &mut rng);
+        let uniform = random_walk(&g, &14, 2000, &mut rng);
+        assert!(backtrack_rate(&local) > 0.9);
+        assert!(backtrack_rate(&outward) < 0.05);
+        assert!(backtrack_rate(&local) > backtrack_rate(&uniform));
+        assert!(backtrack_rate(&outward) < backtrack_rate(&uniform));
+
+        for walk in [local, outward] {
+            for pair in walk.windows(2) {
+                assert!(edges.contains(&(pair[0], pair[1])) || edges.contains(&(pair[1], pair[0])));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_node2vec_zero_q() {
+        let g = graph_from_edges::<Undirected>(36, &grid());
+        node2vec_walk(&g, &14, 10, 1.0, 0.0, &mut StdRng::seed_from_u64(77));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_node2vec_infinite_p_q() {
+        // every weight after the first step would be 0
+        let g = graph_from_edges::<Undirected>(36, &grid());
+        node2vec_walk(
+            &g,
+            &14,
+            10,
+            f64::INFINITY,
+            f64::INFINITY,
+            &mut StdRng::seed_from_u64(77),
+        );
+    }
+}